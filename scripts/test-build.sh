@@ -31,6 +31,8 @@ cd src-tauri
 cargo check
 cargo test
 cargo clippy -- -D warnings
+# 作为库使用时不能依赖 tauri
+cargo clippy --no-default-features --lib -- -D warnings
 
 # 构建Tauri应用
 echo "📱 构建Tauri应用..."
@@ -0,0 +1,351 @@
+// 命令行工具：不启动界面，直接使用与桌面端相同的连接和传输逻辑
+// 结果输出到 stdout（--json 时为 JSON），传输进度输出到 stderr
+
+use clap::{Parser, Subcommand};
+use serde::Serialize;
+use sftp_web_lib::{
+    i18n, ops, ConnectOptions, FileInfo, Locale, PrivateKey, ProgressListener, ProxyConfig, ProxyType, SftpClient,
+    SftpError, TimeoutConfig,
+};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::Instant;
+
+#[derive(Parser)]
+#[command(name = "sftp-cli", version, about = "SFTP 命令行工具")]
+struct Cli {
+    /// 服务器地址
+    #[arg(long, short = 'H', global = true, env = "SFTP_HOST")]
+    host: Option<String>,
+
+    /// 端口
+    #[arg(long, short = 'P', global = true, default_value_t = 22, env = "SFTP_PORT")]
+    port: u16,
+
+    /// 用户名
+    #[arg(long, short = 'u', global = true, env = "SFTP_USER")]
+    user: Option<String>,
+
+    /// 密码，建议通过环境变量传入
+    #[arg(long, global = true, env = "SFTP_PASSWORD", hide_env_values = true)]
+    password: Option<String>,
+
+    /// 私钥文件，指定后使用公钥认证
+    #[arg(long, short = 'i', global = true, env = "SFTP_IDENTITY")]
+    identity: Option<PathBuf>,
+
+    /// 私钥口令
+    #[arg(long, global = true, env = "SFTP_PASSPHRASE", hide_env_values = true, requires = "identity")]
+    passphrase: Option<String>,
+
+    /// SOCKS5 代理，格式为 host:port
+    #[arg(long, global = true, conflicts_with = "http_proxy")]
+    socks5: Option<String>,
+
+    /// HTTP CONNECT 代理，格式为 host:port
+    #[arg(long, global = true)]
+    http_proxy: Option<String>,
+
+    /// 连接超时（秒）
+    #[arg(long, global = true, default_value_t = 10)]
+    connect_timeout: u64,
+
+    /// 单次操作超时（秒），0 表示不限时
+    #[arg(long, global = true, default_value_t = 60)]
+    timeout: u64,
+
+    /// 以 JSON 输出结果
+    #[arg(long, global = true)]
+    json: bool,
+
+    /// 不显示传输进度
+    #[arg(long, short = 'q', global = true)]
+    quiet: bool,
+
+    /// 输出语言
+    #[arg(long, global = true, value_enum, default_value_t = Lang::Zh)]
+    lang: Lang,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Clone, Copy, clap::ValueEnum)]
+enum Lang {
+    Zh,
+    En,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// 列出目录
+    Ls {
+        #[arg(default_value = "/")]
+        path: String,
+    },
+    /// 下载文件
+    Get {
+        remote: String,
+        /// 本地路径，默认为当前目录下的同名文件
+        local: Option<String>,
+    },
+    /// 上传文件
+    Put {
+        local: String,
+        /// 远程路径，以 / 结尾时视为目录
+        remote: String,
+    },
+    /// 删除文件，指定 --dir 时删除空目录
+    Rm {
+        path: String,
+        #[arg(long, short = 'd')]
+        dir: bool,
+    },
+    /// 创建目录
+    Mkdir {
+        path: String,
+    },
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+enum Output {
+    Files(Vec<FileInfo>),
+    Transfer {
+        source: String,
+        destination: String,
+        bytes: u64,
+    },
+    Path {
+        path: String,
+    },
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    i18n::set_locale(match cli.lang {
+        Lang::Zh => Locale::Zh,
+        Lang::En => Locale::En,
+    });
+
+    match run(&cli) {
+        Ok(output) => {
+            print_output(&cli, &output);
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            if cli.json {
+                println!("{}", serde_json::json!({ "error": e }));
+            } else {
+                eprintln!("{}", e);
+            }
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(cli: &Cli) -> Result<Output, SftpError> {
+    let host = required(&cli.host, "--host")?;
+    let user = required(&cli.user, "--user")?;
+    let password = cli.password.clone().unwrap_or_default();
+
+    let proxy = proxy_config(cli)?;
+    let timeouts = TimeoutConfig {
+        connect_secs: cli.connect_timeout,
+        operation_secs: cli.timeout,
+        ..TimeoutConfig::default()
+    };
+
+    let client = SftpClient::connect(&ConnectOptions {
+        host,
+        port: cli.port,
+        username: user,
+        password,
+        private_key: cli.identity.clone().map(|path| PrivateKey {
+            path,
+            passphrase: cli.passphrase.clone(),
+        }),
+        proxy,
+        timeouts,
+    })?;
+
+    match &cli.command {
+        Command::Ls { path } => {
+            client.list_dir(path).map(Output::Files)
+        }
+        Command::Get { remote, local } => {
+            let local = local.clone().unwrap_or_else(|| {
+                Path::new(remote)
+                    .file_name()
+                    .map(|name| name.to_string_lossy().to_string())
+                    .unwrap_or_else(|| remote.clone())
+            });
+            let mut progress = Progress::new(remote, cli.quiet);
+            let bytes = client.download(remote, &local, &mut progress);
+            progress.finish();
+
+            Ok(Output::Transfer {
+                source: remote.clone(),
+                destination: local,
+                bytes: bytes?,
+            })
+        }
+        Command::Put { local, remote } => {
+            let remote = if remote.ends_with('/') {
+                let name = Path::new(local)
+                    .file_name()
+                    .map(|name| name.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                ops::join_remote_path(remote, &name)
+            } else {
+                remote.clone()
+            };
+            let mut progress = Progress::new(local, cli.quiet);
+            let bytes = client.upload(local, &remote, &mut progress);
+            progress.finish();
+
+            Ok(Output::Transfer {
+                source: local.clone(),
+                destination: remote,
+                bytes: bytes?,
+            })
+        }
+        Command::Rm { path, dir } => {
+            if *dir {
+                client.remove_dir(path)?;
+            } else {
+                client.remove_file(path)?;
+            }
+            Ok(Output::Path { path: path.clone() })
+        }
+        Command::Mkdir { path } => {
+            client.mkdir(path)?;
+            Ok(Output::Path { path: path.clone() })
+        }
+    }
+}
+
+fn required(value: &Option<String>, name: &str) -> Result<String, SftpError> {
+    value.clone().ok_or_else(|| {
+        SftpError::new(sftp_web_lib::ErrorCode::InvalidInput, i18n::format("error.missing_argument", &[&name]))
+    })
+}
+
+fn proxy_config(cli: &Cli) -> Result<Option<ProxyConfig>, SftpError> {
+    let (proxy_type, address) = match (&cli.socks5, &cli.http_proxy) {
+        (Some(address), _) => (ProxyType::Socks5, address),
+        (None, Some(address)) => (ProxyType::Http, address),
+        (None, None) => return Ok(None),
+    };
+
+    let invalid = || {
+        SftpError::new(sftp_web_lib::ErrorCode::InvalidInput, i18n::format("error.invalid_proxy_address", &[&address]))
+    };
+    let (host, port) = address.rsplit_once(':').ok_or_else(invalid)?;
+    let port = port.parse().map_err(|_| invalid())?;
+
+    Ok(Some(ProxyConfig {
+        proxy_type,
+        host: host.trim_matches(|c| c == '[' || c == ']').to_string(),
+        port,
+        username: None,
+        password: None,
+    }))
+}
+
+fn print_output(cli: &Cli, output: &Output) {
+    if cli.json {
+        println!("{}", serde_json::to_string(output).unwrap_or_default());
+        return;
+    }
+
+    match output {
+        Output::Files(files) => {
+            for file in files {
+                println!(
+                    "{} {:>8} {:>8} {:>12} {} {}",
+                    file.mode,
+                    file.uid.map(|uid| uid.to_string()).unwrap_or_default(),
+                    file.gid.map(|gid| gid.to_string()).unwrap_or_default(),
+                    file.size,
+                    file.modified.as_deref().unwrap_or("-"),
+                    match &file.link_target {
+                        Some(target) => format!("{} -> {}", file.name, target),
+                        None => file.name.clone(),
+                    }
+                );
+            }
+        }
+        Output::Transfer { source, destination, bytes } => {
+            println!("{} -> {} ({} bytes)", source, destination, bytes);
+        }
+        Output::Path { path } => println!("{}", path),
+    }
+}
+
+// stderr 上的单行进度显示
+struct Progress {
+    name: String,
+    quiet: bool,
+    started: Instant,
+    shown: bool,
+}
+
+impl Progress {
+    fn new(name: &str, quiet: bool) -> Self {
+        Progress {
+            name: name.to_string(),
+            quiet,
+            started: Instant::now(),
+            shown: false,
+        }
+    }
+
+    fn finish(&mut self) {
+        if self.shown {
+            eprintln!();
+        }
+    }
+}
+
+impl ProgressListener for Progress {
+    fn on_progress(&mut self, done: u64, total: u64) {
+        if self.quiet {
+            return;
+        }
+
+        let elapsed = self.started.elapsed().as_secs_f64();
+        let speed = if elapsed > 0.0 { done as f64 / elapsed } else { 0.0 };
+        let percent = (done * 100).checked_div(total).unwrap_or(0);
+
+        let mut stderr = std::io::stderr();
+        let _ = write!(
+            stderr,
+            "\r{} {}/{} ({}%) {}/s",
+            self.name,
+            human_size(done),
+            human_size(total),
+            percent,
+            human_size(speed as u64)
+        );
+        let _ = stderr.flush();
+        self.shown = true;
+    }
+}
+
+fn human_size(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[0])
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
@@ -0,0 +1,169 @@
+// 面向其他 Rust 程序的同步客户端接口，不依赖 Tauri
+// 内部直接复用 ops 中的实现，与桌面端和命令行工具行为一致
+
+use crate::error::SftpError;
+use crate::ops::{self, PrivateKey, ProgressListener};
+use crate::proxy::ProxyConfig;
+use crate::remote_fs::RemoteFs;
+use crate::sftp_fs::SftpFs;
+use crate::{FileInfo, TimeoutConfig};
+use ssh2::Session;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// 建立连接所需的参数
+#[derive(Debug, Clone)]
+pub struct ConnectOptions {
+    pub host: String,
+    pub port: u16,
+    pub username: String,
+    pub password: String,
+    /// 设置后使用公钥认证，不再使用密码
+    pub private_key: Option<PrivateKey>,
+    /// 为空时使用全局默认代理
+    pub proxy: Option<ProxyConfig>,
+    pub timeouts: TimeoutConfig,
+}
+
+impl ConnectOptions {
+    /// 端口 22，不指定代理，使用默认超时
+    pub fn new(host: impl Into<String>, username: impl Into<String>, password: impl Into<String>) -> Self {
+        ConnectOptions {
+            host: host.into(),
+            port: 22,
+            username: username.into(),
+            password: password.into(),
+            private_key: None,
+            proxy: None,
+            timeouts: TimeoutConfig::default(),
+        }
+    }
+}
+
+/// 一个已认证的 SFTP 连接
+///
+/// 所有方法都是阻塞的；在异步代码中请放到 `spawn_blocking` 里调用。
+/// 同一个连接上的操作不能并发执行，需要并发时请建立多个连接。
+pub struct SftpClient {
+    session: Session,
+    cancel_flag: Arc<AtomicBool>,
+}
+
+impl SftpClient {
+    /// 建立连接并完成认证（密码或公钥）
+    pub fn connect(options: &ConnectOptions) -> Result<Self, SftpError> {
+        let session = ops::connect(
+            &options.host,
+            options.port,
+            &options.username,
+            &options.password,
+            options.private_key.as_ref(),
+            options.proxy.as_ref(),
+            &options.timeouts,
+        )?;
+
+        Ok(Self::from_session(session))
+    }
+
+    /// 使用已经认证的会话
+    pub fn from_session(session: Session) -> Self {
+        SftpClient {
+            session,
+            cancel_flag: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// 底层的 libssh2 会话，用于本类型未覆盖的操作
+    pub fn session(&self) -> &Session {
+        &self.session
+    }
+
+    /// 取消标志，可以在其他线程中置为 true 来中止正在进行的传输
+    ///
+    /// 被中止的传输返回 `ErrorCode::Cancelled`，之后标志会自动复位。
+    pub fn cancel_flag(&self) -> Arc<AtomicBool> {
+        self.cancel_flag.clone()
+    }
+
+    /// 在该连接上打开一个 SFTP 文件系统，可以与其他 [`RemoteFs`] 实现互换使用
+    pub fn fs(&self) -> Result<SftpFs, SftpError> {
+        SftpFs::new(&self.session)
+    }
+
+    /// 列出目录内容，符号链接会解析目标
+    pub fn list_dir(&self, path: &str) -> Result<Vec<FileInfo>, SftpError> {
+        self.fs()?.list(path)
+    }
+
+    /// 获取单个路径的信息（跟随符号链接）
+    pub fn stat(&self, path: &str) -> Result<FileInfo, SftpError> {
+        self.fs()?.stat(path)
+    }
+
+    /// 下载远程文件到本地路径，返回传输的字节数
+    pub fn download(
+        &self,
+        remote_path: &str,
+        local_path: &str,
+        progress: &mut dyn ProgressListener,
+    ) -> Result<u64, SftpError> {
+        let fs = self.fs()?;
+        self.transfer(|cancel_flag| ops::download(&fs, remote_path, local_path, cancel_flag, progress))
+    }
+
+    /// 上传本地文件到远程路径，返回传输的字节数
+    pub fn upload(
+        &self,
+        local_path: &str,
+        remote_path: &str,
+        progress: &mut dyn ProgressListener,
+    ) -> Result<u64, SftpError> {
+        let fs = self.fs()?;
+        self.transfer(|cancel_flag| ops::upload(&fs, local_path, remote_path, cancel_flag, progress))
+    }
+
+    fn transfer(
+        &self,
+        run: impl FnOnce(&AtomicBool) -> Result<u64, SftpError>,
+    ) -> Result<u64, SftpError> {
+        let result = run(&self.cancel_flag);
+        self.cancel_flag.store(false, Ordering::SeqCst);
+        result
+    }
+
+    /// 创建目录（权限 0755）
+    pub fn mkdir(&self, path: &str) -> Result<(), SftpError> {
+        self.fs()?.mkdir(path)
+    }
+
+    /// 删除文件
+    pub fn remove_file(&self, path: &str) -> Result<(), SftpError> {
+        self.fs()?.remove_file(path)
+    }
+
+    /// 删除空目录
+    pub fn remove_dir(&self, path: &str) -> Result<(), SftpError> {
+        self.fs()?.remove_dir(path)
+    }
+
+    /// 重命名或移动；overwrite 为 true 时替换已存在的目标（目录只有为空时才能被替换）
+    pub fn rename(&self, source: &str, destination: &str, overwrite: bool) -> Result<(), SftpError> {
+        self.fs()?.rename(source, destination, overwrite)
+    }
+
+    /// 读取符号链接指向的路径
+    pub fn read_link(&self, path: &str) -> Result<String, SftpError> {
+        self.fs()?.read_link(path)
+    }
+
+    /// 创建符号链接 link_path -> target
+    pub fn symlink(&self, target: &str, link_path: &str) -> Result<(), SftpError> {
+        self.fs()?.symlink(target, link_path)
+    }
+
+    /// 断开连接
+    pub fn disconnect(self) -> Result<(), SftpError> {
+        self.session.disconnect(None, "", None)
+            .map_err(|e| SftpError::ssh("action.disconnect", e))
+    }
+}
@@ -0,0 +1,1415 @@
+// 桌面端的 tauri 命令层：连接注册表和前端调用的所有命令
+// 只在启用 gui 特性时编译，作为库使用时不依赖 tauri
+
+use crate::{
+    exec, forward, i18n, logging, ops, proxy, remote_copy, socks, sync, terminal,
+    ErrorCode, FileInfo, FtpFs, FtpOptions, LocalFs, Locale, MoveResult, Protocol, RemoteFs, S3Fs,
+    S3Options, ScpFs, SetStatOptions, SetStatSummary, SftpConnectionInfo, SftpError, SftpFs,
+    SyncOptions, SyncPlan, TimeoutConfig, WebDavFs, WebDavOptions, LOCAL_CONNECTION_ID,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::sync::atomic::AtomicBool;
+use ssh2::Session;
+use std::io::{Read, Write};
+use std::fs;
+use base64::{Engine as _, engine::general_purpose};
+use tauri::{Emitter, Manager};
+
+// 全局连接管理器；每个连接单独加锁，同一连接上的操作依次执行，不同连接之间互不阻塞。
+// 连接表本身只在查找、插入和移除时短暂加锁
+type Registry<T> = std::sync::LazyLock<Mutex<HashMap<String, Arc<Mutex<T>>>>>;
+
+static CONNECTIONS: Registry<Session> = std::sync::LazyLock::new(|| Mutex::new(HashMap::new()));
+
+// FTP 连接；终端、端口转发等基于 SSH 的功能不适用于这些连接
+static FTP_CONNECTIONS: Registry<FtpFs> = std::sync::LazyLock::new(|| Mutex::new(HashMap::new()));
+
+// WebDAV 连接
+static WEBDAV_CONNECTIONS: Registry<WebDavFs> = std::sync::LazyLock::new(|| Mutex::new(HashMap::new()));
+
+// S3 连接
+static S3_CONNECTIONS: Registry<S3Fs> = std::sync::LazyLock::new(|| Mutex::new(HashMap::new()));
+
+// 服务器没有 SFTP 子系统、改用 SCP 传输的 SSH 连接
+static SCP_ONLY: std::sync::LazyLock<Mutex<HashSet<String>>> =
+    std::sync::LazyLock::new(|| Mutex::new(HashSet::new()));
+
+// 终端、端口转发和远程命令使用的 SSH 会话
+pub(crate) fn ssh_session(connection_id: &str) -> Option<Arc<Mutex<Session>>> {
+    CONNECTIONS.lock().unwrap().get(connection_id).cloned()
+}
+
+// 按连接 ID 找到的连接，找到后即释放连接表的锁
+enum Connection {
+    Local,
+    Ssh { session: Arc<Mutex<Session>>, scp_only: bool },
+    Ftp(Arc<Mutex<FtpFs>>),
+    WebDav(Arc<Mutex<WebDavFs>>),
+    S3(Arc<Mutex<S3Fs>>),
+}
+
+// 已加锁的连接，在作用域内独占使用。SSH 会话与终端、端口转发和远程命令共用，
+// 这里只保留锁和会话的副本，文件操作由 LockedFs 每次调用时短暂加锁
+enum ConnectionGuard<'a> {
+    Local,
+    Ssh { lock: &'a Mutex<Session>, session: Session, scp_only: bool },
+    Ftp(MutexGuard<'a, FtpFs>),
+    WebDav(MutexGuard<'a, WebDavFs>),
+    S3(MutexGuard<'a, S3Fs>),
+}
+
+impl Connection {
+    fn find(connection_id: &str) -> Result<Self, SftpError> {
+        if connection_id == LOCAL_CONNECTION_ID {
+            return Ok(Connection::Local);
+        }
+        if let Some(ftp) = FTP_CONNECTIONS.lock().unwrap().get(connection_id) {
+            return Ok(Connection::Ftp(ftp.clone()));
+        }
+        if let Some(webdav) = WEBDAV_CONNECTIONS.lock().unwrap().get(connection_id) {
+            return Ok(Connection::WebDav(webdav.clone()));
+        }
+        if let Some(s3) = S3_CONNECTIONS.lock().unwrap().get(connection_id) {
+            return Ok(Connection::S3(s3.clone()));
+        }
+
+        let session = ssh_session(connection_id)
+            .ok_or_else(|| SftpError::not_connected(connection_id))?;
+        let scp_only = SCP_ONLY.lock().unwrap().contains(connection_id);
+        Ok(Connection::Ssh { session, scp_only })
+    }
+
+    fn lock(&self) -> ConnectionGuard<'_> {
+        match self {
+            Connection::Local => ConnectionGuard::Local,
+            Connection::Ssh { session, scp_only } => ConnectionGuard::Ssh {
+                lock: session,
+                session: session.lock().unwrap().clone(),
+                scp_only: *scp_only,
+            },
+            Connection::Ftp(ftp) => ConnectionGuard::Ftp(ftp.lock().unwrap()),
+            Connection::WebDav(webdav) => ConnectionGuard::WebDav(webdav.lock().unwrap()),
+            Connection::S3(s3) => ConnectionGuard::S3(s3.lock().unwrap()),
+        }
+    }
+}
+
+// 按连接取得的文件系统；SFTP 和 SCP 的包装借用会话，需要在锁的作用域内创建
+enum FsHandle<'a> {
+    Local(LocalFs),
+    Shared(&'a dyn RemoteFs),
+    Scp(LockedFs<'a, ScpFs<'a>>),
+    Sftp(LockedFs<'a, SftpFs>),
+}
+
+// SSH 上的文件系统：每次调用和打开的文件每次读写时才持有会话锁，通道也在锁内释放。
+// 长时间的传输和同步不会让同一连接上的终端、端口转发和远程命令一直等待
+struct LockedFs<'a, F> {
+    lock: &'a Mutex<Session>,
+    fs: Option<F>,
+}
+
+impl<'a, F: RemoteFs> LockedFs<'a, F> {
+    fn new(lock: &'a Mutex<Session>, open: impl FnOnce() -> Result<F, SftpError>) -> Result<Self, SftpError> {
+        let _session = lock.lock().unwrap();
+        Ok(LockedFs { lock, fs: Some(open()?) })
+    }
+
+    fn locked<'s, T>(&'s self, f: impl FnOnce(&'s F) -> T) -> T {
+        let _session = self.lock.lock().unwrap();
+        f(self.fs.as_ref().unwrap())
+    }
+
+    fn stream<S>(&self, stream: Result<S, SftpError>) -> Result<Box<LockedStream<'a, S>>, SftpError> {
+        Ok(Box::new(LockedStream { lock: self.lock, stream: Some(stream?) }))
+    }
+}
+
+impl<F> Drop for LockedFs<'_, F> {
+    fn drop(&mut self) {
+        let _session = self.lock.lock().unwrap();
+        self.fs.take();
+    }
+}
+
+impl<F: RemoteFs> RemoteFs for LockedFs<'_, F> {
+    fn list(&self, path: &str) -> Result<Vec<FileInfo>, SftpError> {
+        self.locked(|fs| fs.list(path))
+    }
+
+    fn stat(&self, path: &str) -> Result<FileInfo, SftpError> {
+        self.locked(|fs| fs.stat(path))
+    }
+
+    fn open_read(&self, path: &str) -> Result<Box<dyn Read + '_>, SftpError> {
+        Ok(self.stream(self.locked(|fs| fs.open_read(path)))?)
+    }
+
+    fn open_read_from(&self, path: &str, offset: u64) -> Result<Box<dyn Read + '_>, SftpError> {
+        Ok(self.stream(self.locked(|fs| fs.open_read_from(path, offset)))?)
+    }
+
+    fn open_write(&self, path: &str) -> Result<Box<dyn Write + '_>, SftpError> {
+        Ok(self.stream(self.locked(|fs| fs.open_write(path)))?)
+    }
+
+    fn open_write_sized(&self, path: &str, size: u64) -> Result<Box<dyn Write + '_>, SftpError> {
+        Ok(self.stream(self.locked(|fs| fs.open_write_sized(path, size)))?)
+    }
+
+    fn open_append(&self, path: &str) -> Result<Box<dyn Write + '_>, SftpError> {
+        Ok(self.stream(self.locked(|fs| fs.open_append(path)))?)
+    }
+
+    fn mkdir(&self, path: &str) -> Result<(), SftpError> {
+        self.locked(|fs| fs.mkdir(path))
+    }
+
+    fn remove_file(&self, path: &str) -> Result<(), SftpError> {
+        self.locked(|fs| fs.remove_file(path))
+    }
+
+    fn remove_dir(&self, path: &str) -> Result<(), SftpError> {
+        self.locked(|fs| fs.remove_dir(path))
+    }
+
+    fn rename(&self, source: &str, destination: &str, overwrite: bool) -> Result<(), SftpError> {
+        self.locked(|fs| fs.rename(source, destination, overwrite))
+    }
+
+    fn set_modified(&self, path: &str, modified: u64) -> Result<(), SftpError> {
+        self.locked(|fs| fs.set_modified(path, modified))
+    }
+
+    fn set_attributes(&self, path: &str, options: &SetStatOptions) -> Result<SetStatSummary, SftpError> {
+        self.locked(|fs| fs.set_attributes(path, options))
+    }
+
+    fn read_link(&self, path: &str) -> Result<String, SftpError> {
+        self.locked(|fs| fs.read_link(path))
+    }
+
+    fn symlink(&self, target: &str, link_path: &str) -> Result<(), SftpError> {
+        self.locked(|fs| fs.symlink(target, link_path))
+    }
+}
+
+// LockedFs 打开的文件，每次读写时持有会话锁
+struct LockedStream<'a, S> {
+    lock: &'a Mutex<Session>,
+    stream: Option<S>,
+}
+
+impl<S: Read> Read for LockedStream<'_, S> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let _session = self.lock.lock().unwrap();
+        self.stream.as_mut().unwrap().read(buf)
+    }
+}
+
+impl<S: Write> Write for LockedStream<'_, S> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let _session = self.lock.lock().unwrap();
+        self.stream.as_mut().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        let _session = self.lock.lock().unwrap();
+        self.stream.as_mut().unwrap().flush()
+    }
+}
+
+impl<S> Drop for LockedStream<'_, S> {
+    fn drop(&mut self) {
+        let _session = self.lock.lock().unwrap();
+        self.stream.take();
+    }
+}
+
+impl FsHandle<'_> {
+    fn as_fs(&self) -> &dyn RemoteFs {
+        match self {
+            FsHandle::Local(fs) => fs,
+            FsHandle::Shared(fs) => *fs,
+            FsHandle::Scp(fs) => fs,
+            FsHandle::Sftp(fs) => fs,
+        }
+    }
+}
+
+impl ConnectionGuard<'_> {
+    fn fs(&self) -> Result<FsHandle<'_>, SftpError> {
+        Ok(match self {
+            ConnectionGuard::Local => FsHandle::Local(LocalFs),
+            ConnectionGuard::Ssh { lock, session, scp_only: true } => FsHandle::Scp(LockedFs::new(lock, || Ok(ScpFs::new(session)))?),
+            ConnectionGuard::Ssh { lock, session, scp_only: false } => FsHandle::Sftp(LockedFs::new(lock, || SftpFs::new(session))?),
+            ConnectionGuard::Ftp(ftp) => FsHandle::Shared(&**ftp),
+            ConnectionGuard::WebDav(webdav) => FsHandle::Shared(&**webdav),
+            ConnectionGuard::S3(s3) => FsHandle::Shared(&**s3),
+        })
+    }
+}
+
+// 按连接 ID 取得文件系统并执行操作。FTP、WebDAV、S3 在执行期间持有这一个连接的锁，
+// SSH 连接只在每次文件操作和读写时加锁
+fn with_fs<T>(
+    connection_id: &str,
+    f: impl FnOnce(&dyn RemoteFs) -> Result<T, SftpError>,
+) -> Result<T, SftpError> {
+    let connection = Connection::find(connection_id)?;
+    let guard = connection.lock();
+    let fs = guard.fs()?;
+    f(fs.as_fs())
+}
+
+// 同时使用两个连接（连接间传输、同步），只锁这两个连接。按 ID 顺序加锁，
+// 避免方向相反的两个任务互相等待；两端是同一连接时只加一次锁。
+// FTP 连接同一时间只能有一个数据通道，不能同时作为两端
+fn with_fs_pair<T>(
+    first_id: &str,
+    second_id: &str,
+    f: impl FnOnce(&dyn RemoteFs, &dyn RemoteFs) -> Result<T, SftpError>,
+) -> Result<T, SftpError> {
+    let first = Connection::find(first_id)?;
+    if first_id == second_id {
+        if matches!(first, Connection::Ftp(_)) {
+            return Err(SftpError::new(ErrorCode::Unsupported, i18n::text("error.ftp_same_connection")));
+        }
+        let guard = first.lock();
+        let (first_fs, second_fs) = (guard.fs()?, guard.fs()?);
+        return f(first_fs.as_fs(), second_fs.as_fs());
+    }
+
+    let second = Connection::find(second_id)?;
+    let (first_guard, second_guard) = if first_id < second_id {
+        let first_guard = first.lock();
+        (first_guard, second.lock())
+    } else {
+        let second_guard = second.lock();
+        (first.lock(), second_guard)
+    };
+    let (first_fs, second_fs) = (first_guard.fs()?, second_guard.fs()?);
+    f(first_fs.as_fs(), second_fs.as_fs())
+}
+
+// 从所有连接表中移除并关闭连接。同一 ID 重新连接时也要先调用，
+// 否则按 ID 查找时可能仍然找到其他协议表中的旧连接
+fn close_connection(connection_id: &str) {
+    // 先关闭端口转发，确保远程监听在会话释放前被取消
+    forward::stop_connection(connection_id);
+
+    // 每张表只在移除时短暂加锁；FTP 的 QUIT 需要网络往返，在释放所有锁之后发送。
+    // 仍有操作在使用该连接时无法发送 QUIT，最后一个引用释放时直接关闭
+    CONNECTIONS.lock().unwrap().remove(connection_id);
+    SCP_ONLY.lock().unwrap().remove(connection_id);
+    let ftp = FTP_CONNECTIONS.lock().unwrap().remove(connection_id);
+    WEBDAV_CONNECTIONS.lock().unwrap().remove(connection_id);
+    S3_CONNECTIONS.lock().unwrap().remove(connection_id);
+
+    let ftp = ftp.and_then(Arc::into_inner).map(|ftp| ftp.into_inner().unwrap());
+    if let Some(Err(e)) = ftp.map(FtpFs::quit) {
+        tracing::debug!(connection_id = %connection_id, "FTP QUIT 失败: {}", e);
+    }
+}
+
+// 存储活跃的传输任务
+type TransferTasks = Mutex<HashMap<String, Arc<AtomicBool>>>;
+static TRANSFER_TASKS: std::sync::LazyLock<TransferTasks> = 
+    std::sync::LazyLock::new(|| Mutex::new(HashMap::new()));
+
+// 测试连接命令
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+async fn test_sftp_connection(
+    host: String,
+    port: u16,
+    username: String,
+    password: String,
+    proxy: Option<proxy::ProxyConfig>,
+    timeouts: Option<TimeoutConfig>,
+    protocol: Option<Protocol>,
+    ftp: Option<FtpOptions>,
+    webdav: Option<WebDavOptions>,
+    s3: Option<S3Options>,
+) -> Result<String, SftpError> {
+    tokio::task::spawn_blocking(move || {
+        let timeouts = timeouts.unwrap_or_default();
+        match protocol.unwrap_or_default() {
+            Protocol::Sftp => {
+                ops::connect(&host, port, &username, &password, None, proxy.as_ref(), &timeouts)?;
+            }
+            Protocol::Ftp => {
+                let ftp = ftp.unwrap_or_default();
+                // 连接成功后立即断开，QUIT 失败不影响测试结果
+                let _ = FtpFs::connect(&host, port, &username, &password, &ftp, proxy.as_ref(), &timeouts)?.quit();
+            }
+            Protocol::WebDav => {
+                let webdav = webdav.unwrap_or_default();
+                WebDavFs::connect(&host, port, &username, &password, &webdav, proxy.as_ref(), &timeouts)?;
+            }
+            Protocol::S3 => {
+                let s3 = s3.unwrap_or_default();
+                S3Fs::connect(&host, port, &username, &password, &s3, proxy.as_ref(), &timeouts)?;
+            }
+        }
+
+        Ok(i18n::text("msg.connect_ok").to_string())
+    }).await.map_err(SftpError::task)?
+}
+
+// 建立 SFTP 连接
+#[tauri::command]
+async fn connect_sftp(
+    connection_info: SftpConnectionInfo,
+) -> Result<String, SftpError> {
+    let connection_id = connection_info.id.clone();
+    if connection_id == LOCAL_CONNECTION_ID {
+        return Err(SftpError::new(ErrorCode::InvalidInput, i18n::format("error.reserved_connection_id", &[&connection_id])));
+    }
+
+    tokio::task::spawn_blocking(move || {
+        tracing::info!(connection_id = %connection_id, host = %connection_info.host, port = connection_info.port, protocol = ?connection_info.protocol, "建立连接");
+
+        if connection_info.protocol == Protocol::Ftp {
+            let ftp = FtpFs::connect(
+                &connection_info.host,
+                connection_info.port,
+                &connection_info.username,
+                &connection_info.password,
+                &connection_info.ftp,
+                connection_info.proxy.as_ref(),
+                &connection_info.timeouts,
+            ).inspect_err(|e| {
+                tracing::warn!(connection_id = %connection_id, code = ?e.code, "连接失败: {}", e);
+            })?;
+
+            close_connection(&connection_id);
+            FTP_CONNECTIONS.lock().unwrap().insert(connection_id.clone(), Arc::new(Mutex::new(ftp)));
+            return Ok(connection_id);
+        }
+
+        if connection_info.protocol == Protocol::WebDav {
+            let webdav = WebDavFs::connect(
+                &connection_info.host,
+                connection_info.port,
+                &connection_info.username,
+                &connection_info.password,
+                &connection_info.webdav,
+                connection_info.proxy.as_ref(),
+                &connection_info.timeouts,
+            ).inspect_err(|e| {
+                tracing::warn!(connection_id = %connection_id, code = ?e.code, "连接失败: {}", e);
+            })?;
+
+            close_connection(&connection_id);
+            WEBDAV_CONNECTIONS.lock().unwrap().insert(connection_id.clone(), Arc::new(Mutex::new(webdav)));
+            return Ok(connection_id);
+        }
+
+        if connection_info.protocol == Protocol::S3 {
+            let s3 = S3Fs::connect(
+                &connection_info.host,
+                connection_info.port,
+                &connection_info.username,
+                &connection_info.password,
+                &connection_info.s3,
+                connection_info.proxy.as_ref(),
+                &connection_info.timeouts,
+            ).inspect_err(|e| {
+                tracing::warn!(connection_id = %connection_id, code = ?e.code, "连接失败: {}", e);
+            })?;
+
+            close_connection(&connection_id);
+            S3_CONNECTIONS.lock().unwrap().insert(connection_id.clone(), Arc::new(Mutex::new(s3)));
+            return Ok(connection_id);
+        }
+
+        let session = ops::connect(
+            &connection_info.host,
+            connection_info.port,
+            &connection_info.username,
+            &connection_info.password,
+            None,
+            connection_info.proxy.as_ref(),
+            &connection_info.timeouts,
+        ).inspect_err(|e| {
+            tracing::warn!(connection_id = %connection_id, code = ?e.code, "连接失败: {}", e);
+        })?;
+
+        close_connection(&connection_id);
+
+        // 部分嵌入式设备和受限主机没有 SFTP 子系统，此时降级为 SCP
+        if let Err(e) = session.sftp() {
+            tracing::warn!(connection_id = %connection_id, "SFTP 子系统不可用，使用 SCP 兼容模式: {}", e);
+            SCP_ONLY.lock().unwrap().insert(connection_id.clone());
+        }
+
+        // 存储连接
+        CONNECTIONS.lock().unwrap().insert(connection_id.clone(), Arc::new(Mutex::new(session)));
+
+        Ok(connection_id)
+    }).await.map_err(SftpError::task)?
+}
+
+// 设置全局默认代理，传入空值表示直连
+#[tauri::command]
+async fn set_default_proxy(proxy: Option<proxy::ProxyConfig>) -> Result<String, SftpError> {
+    proxy::set_default(proxy);
+    Ok(i18n::text("msg.default_proxy_updated").to_string())
+}
+
+// 获取全局默认代理
+#[tauri::command]
+async fn get_default_proxy() -> Result<Option<proxy::ProxyConfig>, SftpError> {
+    Ok(proxy::get_default())
+}
+
+// 设置命令返回信息使用的语言
+#[tauri::command]
+async fn set_locale(locale: Locale) -> Result<String, SftpError> {
+    i18n::set_locale(locale);
+    Ok(i18n::text("msg.locale_updated").to_string())
+}
+
+// 获取当前语言
+#[tauri::command]
+async fn get_locale() -> Result<Locale, SftpError> {
+    Ok(i18n::locale())
+}
+
+// 列出目录内容
+#[tauri::command]
+async fn list_directory(
+    connection_id: String,
+    path: String,
+) -> Result<Vec<FileInfo>, SftpError> {
+    tracing::debug!(connection_id = %connection_id, path = %path, "列出目录");
+
+    tokio::task::spawn_blocking(move || {
+        let files = with_fs(&connection_id, |fs| fs.list(&path))
+            .inspect_err(|e| {
+                tracing::warn!(connection_id = %connection_id, path = %path, code = ?e.code, "读取目录失败: {}", e);
+            })?;
+
+        tracing::debug!(connection_id = %connection_id, path = %path, count = files.len(), "目录读取完成");
+        Ok(files)
+    }).await.map_err(SftpError::task)?
+}
+
+// 下载文件（带进度更新）
+#[tauri::command]
+async fn download_file_with_progress(
+    app_handle: tauri::AppHandle,
+    connection_id: String,
+    remote_path: String,
+    local_path: String,
+    transfer_id: String,
+    resume: Option<bool>,
+) -> Result<String, SftpError> {
+    tracing::info!(connection_id = %connection_id, transfer_id = %transfer_id, remote_path = %remote_path, local_path = %local_path, resume = ?resume, "开始下载");
+
+    // 创建取消标志
+    let cancel_flag = Arc::new(AtomicBool::new(false));
+    {
+        let mut tasks = TRANSFER_TASKS.lock().unwrap();
+        tasks.insert(transfer_id.clone(), cancel_flag.clone());
+    }
+
+    let task_id = transfer_id.clone();
+    let result = tokio::task::spawn_blocking(move || {
+        let mut progress = EventProgress::new(&app_handle, "download_progress", &transfer_id);
+
+        // resume 为 true 时从已存在的本地文件末尾继续
+        let result = with_fs(&connection_id, |fs| {
+            if resume.unwrap_or(false) {
+                ops::download_resume(fs, &remote_path, &local_path, &cancel_flag, &mut progress)
+            } else {
+                ops::download(fs, &remote_path, &local_path, &cancel_flag, &mut progress)
+            }
+        });
+        match result {
+            Ok(bytes_copied) => {
+                tracing::info!(transfer_id = %transfer_id, bytes_copied, "下载完成");
+                progress.finish("completed");
+
+                Ok(i18n::format("msg.download_done", &[&bytes_copied]))
+            }
+            Err(e) if e.code == ErrorCode::Cancelled => {
+                tracing::info!(transfer_id = %transfer_id, bytes_copied = progress.transferred, "下载已取消");
+                progress.finish("cancelled");
+                Err(e)
+            }
+            Err(e) => {
+                tracing::warn!(transfer_id = %transfer_id, bytes_copied = progress.transferred, code = ?e.code, "下载失败: {}", e);
+                Err(e)
+            }
+        }
+    }).await.map_err(SftpError::task)?;
+
+    TRANSFER_TASKS.lock().unwrap().remove(&task_id);
+    result
+}
+
+// 进度百分比
+fn percent(done: u64, total: u64) -> u32 {
+    if total > 0 {
+        (done as f64 / total as f64 * 100.0) as u32
+    } else {
+        0
+    }
+}
+
+// 将传输进度作为事件推送给前端
+struct EventProgress<'a> {
+    app_handle: &'a tauri::AppHandle,
+    event: &'static str,
+    transfer_id: &'a str,
+    transferred: u64,
+    total: u64,
+}
+
+impl<'a> EventProgress<'a> {
+    fn new(app_handle: &'a tauri::AppHandle, event: &'static str, transfer_id: &'a str) -> Self {
+        EventProgress {
+            app_handle,
+            event,
+            transfer_id,
+            transferred: 0,
+            total: 0,
+        }
+    }
+
+    fn payload(&self, progress: u32) -> serde_json::Value {
+        serde_json::json!({
+            "transfer_id": self.transfer_id,
+            "bytes_copied": self.transferred,
+            "total_size": self.total,
+            "progress": progress
+        })
+    }
+
+    // 发送最终状态，state 为 completed 或 cancelled
+    fn finish(&self, state: &str) {
+        let progress = if state == "completed" { 100 } else { percent(self.transferred, self.total) };
+        let mut payload = self.payload(progress);
+        payload[state] = serde_json::Value::Bool(true);
+        let _ = self.app_handle.emit(self.event, payload);
+    }
+}
+
+impl ops::ProgressListener for EventProgress<'_> {
+    fn on_progress(&mut self, transferred: u64, total: u64) {
+        self.transferred = transferred;
+        self.total = total;
+
+        let _ = self.app_handle.emit(self.event, self.payload(percent(transferred, total)));
+        tracing::trace!(transfer_id = %self.transfer_id, bytes_copied = transferred, total_size = total, "传输进度");
+    }
+}
+
+// 保留原有的下载函数作为备用
+#[tauri::command]
+async fn download_file(
+    connection_id: String,
+    remote_path: String,
+    local_path: String,
+) -> Result<String, SftpError> {
+    tracing::info!(connection_id = %connection_id, remote_path = %remote_path, local_path = %local_path, "开始下载");
+
+    tokio::task::spawn_blocking(move || {
+        let bytes_copied = with_fs(&connection_id, |fs| {
+            ops::download(fs, &remote_path, &local_path, &AtomicBool::new(false), &mut ops::NoProgress)
+        })?;
+
+        tracing::info!(connection_id = %connection_id, remote_path = %remote_path, bytes_copied, "下载完成");
+        Ok(i18n::format("msg.download_done", &[&bytes_copied]))
+    }).await.map_err(SftpError::task)?
+}
+
+// 上传文件
+#[tauri::command]
+async fn upload_file(
+    connection_id: String,
+    local_path: String,
+    remote_path: String,
+) -> Result<String, SftpError> {
+    tokio::task::spawn_blocking(move || {
+        let bytes_copied = with_fs(&connection_id, |fs| {
+            ops::upload(fs, &local_path, &remote_path, &AtomicBool::new(false), &mut ops::NoProgress)
+        })?;
+
+        tracing::info!(connection_id = %connection_id, remote_path = %remote_path, bytes_copied, "上传完成");
+        Ok(i18n::text("msg.upload_done").to_string())
+    }).await.map_err(SftpError::task)?
+}
+
+// 上传文件数据（从前端传来的 base64 数据）
+#[tauri::command]
+async fn upload_file_data(
+    connection_id: String,
+    remote_path: String,
+    file_data: String,
+    file_name: String,
+) -> Result<String, SftpError> {
+    tokio::task::spawn_blocking(move || {
+        // 解码 base64 数据
+        let decoded_data = general_purpose::STANDARD.decode(&file_data)
+            .map_err(|e| SftpError::new(ErrorCode::InvalidInput, i18n::format("error.decode_file_data", &[&e])))?;
+
+        with_fs(&connection_id, |fs| {
+            let mut remote_file = fs.open_write_sized(&remote_path, decoded_data.len() as u64)?;
+            remote_file.write_all(&decoded_data)
+                .and_then(|_| remote_file.flush())
+                .map_err(|e| SftpError::io("action.write_file_data", e))
+        })?;
+
+        Ok(i18n::format("msg.file_uploaded", &[&file_name]))
+    }).await.map_err(SftpError::task)?
+}
+
+// 创建目录
+#[tauri::command]
+async fn create_directory(
+    connection_id: String,
+    path: String,
+) -> Result<String, SftpError> {
+    tokio::task::spawn_blocking(move || {
+        with_fs(&connection_id, |fs| fs.mkdir(&path))?;
+
+        Ok(i18n::text("msg.dir_created").to_string())
+    }).await.map_err(SftpError::task)?
+}
+
+// 删除文件或目录
+#[tauri::command]
+async fn delete_file(
+    connection_id: String,
+    path: String,
+    is_dir: bool,
+) -> Result<String, SftpError> {
+    tokio::task::spawn_blocking(move || {
+        with_fs(&connection_id, |fs| {
+            if is_dir {
+                fs.remove_dir(&path)
+            } else {
+                fs.remove_file(&path)
+            }
+        })?;
+
+        Ok(i18n::text("msg.deleted").to_string())
+    }).await.map_err(SftpError::task)?
+}
+
+// 重命名或移动远程文件/目录
+#[tauri::command]
+async fn rename_file(
+    connection_id: String,
+    source: String,
+    destination: String,
+    overwrite: bool,
+) -> Result<String, SftpError> {
+    tokio::task::spawn_blocking(move || {
+        with_fs(&connection_id, |fs| fs.rename(&source, &destination, overwrite))?;
+
+        Ok(i18n::text("msg.renamed").to_string())
+    }).await.map_err(SftpError::task)?
+}
+
+// 批量移动到目标目录，每个条目单独返回结果
+#[tauri::command]
+async fn move_files(
+    connection_id: String,
+    sources: Vec<String>,
+    target_dir: String,
+    overwrite: bool,
+) -> Result<Vec<MoveResult>, SftpError> {
+    tokio::task::spawn_blocking(move || {
+        with_fs(&connection_id, |fs| {
+            let results = sources.into_iter().map(|source| {
+                let name = Path::new(&source)
+                    .file_name()
+                    .map(|n| n.to_string_lossy().to_string());
+
+                let Some(name) = name else {
+                    return MoveResult {
+                        destination: String::new(),
+                        source,
+                        success: false,
+                        error: Some(SftpError::new(ErrorCode::InvalidInput, i18n::text("error.invalid_source"))),
+                    };
+                };
+
+                let destination = ops::join_remote_path(&target_dir, &name);
+                let result = fs.rename(&source, &destination, overwrite);
+
+                MoveResult {
+                    source,
+                    destination,
+                    success: result.is_ok(),
+                    error: result.err(),
+                }
+            }).collect();
+
+            Ok(results)
+        })
+    }).await.map_err(SftpError::task)?
+}
+
+// 修改权限、所有者和时间戳
+#[tauri::command]
+async fn set_file_attributes(
+    connection_id: String,
+    path: String,
+    options: SetStatOptions,
+) -> Result<SetStatSummary, SftpError> {
+    tokio::task::spawn_blocking(move || {
+        with_fs(&connection_id, |fs| fs.set_attributes(&path, &options))
+    }).await.map_err(SftpError::task)?
+}
+
+// 按连接类型选择复制方式，返回使用的方式和复制的字节数
+// WebDAV 和 S3 由服务器完成复制，没有中间进度；SFTP 见 remote_copy；
+// 本机和 SCP 连接经本进程流式复制。一个 FTP 控制连接不能同时读写两个文件，不支持复制
+fn copy_on_connection(
+    connection_id: &str,
+    request: &remote_copy::CopyRequest,
+    cancel_flag: &AtomicBool,
+    progress: &mut dyn ops::ProgressListener,
+) -> Result<(&'static str, u64), SftpError> {
+    let connection = Connection::find(connection_id)?;
+    let guard = connection.lock();
+    match &guard {
+        ConnectionGuard::WebDav(webdav) => {
+            let size = webdav.stat(request.source)?.size;
+            webdav.copy(request.source, request.destination, request.overwrite)?;
+            Ok(("COPY", size))
+        }
+        ConnectionGuard::S3(s3) => s3.copy(request.source, request.destination, request.overwrite)
+            .map(|size| ("CopyObject", size)),
+        ConnectionGuard::Ftp(_) => Err(SftpError::new(ErrorCode::Unsupported, i18n::text("error.ftp_copy_unsupported"))),
+        ConnectionGuard::Ssh { lock, scp_only: false, .. } => {
+            let session = lock.lock().unwrap();
+            remote_copy::copy_remote(&session, request, cancel_flag, progress)
+                .map(|(method, bytes_copied)| (method.label(), bytes_copied))
+        }
+        ConnectionGuard::Local | ConnectionGuard::Ssh { scp_only: true, .. } => {
+            let fs = guard.fs()?;
+            let transfer = ops::TransferRequest {
+                source: request.source,
+                destination: request.destination,
+                overwrite: request.overwrite,
+                resume: false,
+            };
+            ops::transfer(fs.as_fs(), fs.as_fs(), &transfer, cancel_flag, progress).map(|bytes_copied| ("stream", bytes_copied))
+        }
+    }
+}
+
+// 在服务器上复制文件或目录（目录递归复制）
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+async fn copy_remote_file(
+    app_handle: tauri::AppHandle,
+    connection_id: String,
+    source: String,
+    destination: String,
+    transfer_id: String,
+    overwrite: bool,
+    allow_shell: bool,
+) -> Result<String, SftpError> {
+    tracing::info!(connection_id = %connection_id, transfer_id = %transfer_id, source = %source, destination = %destination, "开始远程复制");
+
+    let cancel_flag = Arc::new(AtomicBool::new(false));
+    {
+        let mut tasks = TRANSFER_TASKS.lock().unwrap();
+        tasks.insert(transfer_id.clone(), cancel_flag.clone());
+    }
+
+    let task_id = transfer_id.clone();
+    let result = tokio::task::spawn_blocking(move || {
+        let request = remote_copy::CopyRequest {
+            source: &source,
+            destination: &destination,
+            overwrite,
+            allow_shell,
+        };
+
+        let mut progress = EventProgress::new(&app_handle, "copy_progress", &transfer_id);
+
+        match copy_on_connection(&connection_id, &request, &cancel_flag, &mut progress) {
+            Ok((method, bytes_copied)) => {
+                tracing::info!(transfer_id = %transfer_id, method, bytes_copied, "远程复制完成");
+                progress.total = bytes_copied;
+                progress.transferred = bytes_copied;
+                progress.finish("completed");
+                Ok(i18n::format("msg.copy_done", &[&method, &bytes_copied]))
+            }
+            Err(e) => {
+                tracing::warn!(transfer_id = %transfer_id, code = ?e.code, "远程复制失败: {}", e);
+                let _ = app_handle.emit("copy_progress", serde_json::json!({
+                    "transfer_id": transfer_id,
+                    "cancelled": cancel_flag.load(std::sync::atomic::Ordering::SeqCst),
+                    "error": e
+                }));
+                Err(e)
+            }
+        }
+    }).await.map_err(SftpError::task)?;
+
+    TRANSFER_TASKS.lock().unwrap().remove(&task_id);
+    result
+}
+
+// 在两个连接之间直接传输文件或目录，数据经由本进程中转，不写入本地磁盘
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+async fn transfer_between_connections(
+    app_handle: tauri::AppHandle,
+    source_connection_id: String,
+    source_path: String,
+    destination_connection_id: String,
+    destination_path: String,
+    transfer_id: String,
+    overwrite: bool,
+    resume: Option<bool>,
+) -> Result<String, SftpError> {
+    tracing::info!(
+        transfer_id = %transfer_id,
+        source_connection_id = %source_connection_id,
+        destination_connection_id = %destination_connection_id,
+        source = %source_path,
+        destination = %destination_path,
+        "开始连接间传输"
+    );
+
+    let cancel_flag = Arc::new(AtomicBool::new(false));
+    {
+        let mut tasks = TRANSFER_TASKS.lock().unwrap();
+        tasks.insert(transfer_id.clone(), cancel_flag.clone());
+    }
+
+    let task_id = transfer_id.clone();
+    let result = tokio::task::spawn_blocking(move || {
+        let request = ops::TransferRequest {
+            source: &source_path,
+            destination: &destination_path,
+            overwrite,
+            resume: resume.unwrap_or(false),
+        };
+
+        let mut progress = EventProgress::new(&app_handle, "transfer_progress", &transfer_id);
+        let result = with_fs_pair(&source_connection_id, &destination_connection_id, |source, destination| {
+            ops::transfer(source, destination, &request, &cancel_flag, &mut progress)
+        });
+
+        match result {
+            Ok(bytes_copied) => {
+                tracing::info!(transfer_id = %transfer_id, bytes_copied, "连接间传输完成");
+                progress.finish("completed");
+                Ok(i18n::format("msg.transfer_done", &[&bytes_copied]))
+            }
+            Err(e) => {
+                let cancelled = e.code == ErrorCode::Cancelled;
+                if cancelled {
+                    tracing::info!(transfer_id = %transfer_id, bytes_copied = progress.transferred, "连接间传输已取消");
+                } else {
+                    tracing::warn!(transfer_id = %transfer_id, bytes_copied = progress.transferred, code = ?e.code, "连接间传输失败: {}", e);
+                }
+                let _ = app_handle.emit("transfer_progress", serde_json::json!({
+                    "transfer_id": transfer_id,
+                    "cancelled": cancelled,
+                    "error": e
+                }));
+                Err(e)
+            }
+        }
+    }).await.map_err(SftpError::task)?;
+
+    TRANSFER_TASKS.lock().unwrap().remove(&task_id);
+    result
+}
+
+// 比较两个目录（可以来自不同连接），返回同步计划供界面预览
+#[tauri::command]
+async fn plan_sync(
+    left_connection_id: String,
+    left_path: String,
+    right_connection_id: String,
+    right_path: String,
+    options: SyncOptions,
+    transfer_id: String,
+) -> Result<SyncPlan, SftpError> {
+    tracing::info!(transfer_id = %transfer_id, left = %left_path, right = %right_path, direction = ?options.direction, "生成同步计划");
+
+    let cancel_flag = Arc::new(AtomicBool::new(false));
+    TRANSFER_TASKS.lock().unwrap().insert(transfer_id.clone(), cancel_flag.clone());
+
+    let result = tokio::task::spawn_blocking(move || {
+        with_fs_pair(&left_connection_id, &right_connection_id, |left, right| {
+            sync::plan(left, &left_path, right, &right_path, &options, &cancel_flag)
+        })
+    }).await.map_err(SftpError::task)?;
+
+    TRANSFER_TASKS.lock().unwrap().remove(&transfer_id);
+    result
+}
+
+// 按界面确认后的计划执行同步，进度通过 sync_progress 事件推送
+#[tauri::command]
+async fn apply_sync(
+    app_handle: tauri::AppHandle,
+    left_connection_id: String,
+    right_connection_id: String,
+    plan: SyncPlan,
+    transfer_id: String,
+) -> Result<String, SftpError> {
+    tracing::info!(transfer_id = %transfer_id, left = %plan.left_root, right = %plan.right_root, items = plan.items.len(), "开始同步");
+
+    let cancel_flag = Arc::new(AtomicBool::new(false));
+    TRANSFER_TASKS.lock().unwrap().insert(transfer_id.clone(), cancel_flag.clone());
+
+    let task_id = transfer_id.clone();
+    let result = tokio::task::spawn_blocking(move || {
+        let mut progress = EventProgress::new(&app_handle, "sync_progress", &transfer_id);
+        let result = with_fs_pair(&left_connection_id, &right_connection_id, |left, right| {
+            sync::apply(left, right, &plan, &cancel_flag, &mut progress)
+        });
+
+        match result {
+            Ok(summary) => {
+                tracing::info!(transfer_id = %transfer_id, ?summary, "同步完成");
+                progress.finish("completed");
+                Ok(i18n::format("msg.sync_done", &[&summary.uploaded, &summary.downloaded, &summary.deleted, &summary.conflicts]))
+            }
+            Err(e) => {
+                let cancelled = e.code == ErrorCode::Cancelled;
+                tracing::warn!(transfer_id = %transfer_id, bytes_copied = progress.transferred, code = ?e.code, "同步中止: {}", e);
+                let _ = app_handle.emit("sync_progress", serde_json::json!({
+                    "transfer_id": transfer_id,
+                    "cancelled": cancelled,
+                    "error": e
+                }));
+                Err(e)
+            }
+        }
+    }).await.map_err(SftpError::task)?;
+
+    TRANSFER_TASKS.lock().unwrap().remove(&task_id);
+    result
+}
+
+// 读取符号链接指向的路径
+#[tauri::command]
+async fn read_symlink(
+    connection_id: String,
+    path: String,
+) -> Result<String, SftpError> {
+    tokio::task::spawn_blocking(move || {
+        with_fs(&connection_id, |fs| fs.read_link(&path))
+    }).await.map_err(SftpError::task)?
+}
+
+// 创建符号链接 link_path -> target
+#[tauri::command]
+async fn create_symlink(
+    connection_id: String,
+    target: String,
+    link_path: String,
+) -> Result<String, SftpError> {
+    tokio::task::spawn_blocking(move || {
+        with_fs(&connection_id, |fs| fs.symlink(&target, &link_path))?;
+        Ok(i18n::text("msg.symlink_created").to_string())
+    }).await.map_err(SftpError::task)?
+}
+
+// 在远程主机上执行一次性命令
+// 指定 stream_id 时，输出会通过 exec_output 事件实时推送
+#[tauri::command]
+async fn exec_command(
+    app_handle: tauri::AppHandle,
+    connection_id: String,
+    command: String,
+    timeout_secs: Option<u64>,
+    stream_id: Option<String>,
+) -> Result<exec::ExecOutput, SftpError> {
+    // 命令行中可能带有密码等敏感参数，info 级别只记录程序名
+    let program = command.split_whitespace().next().unwrap_or_default();
+    tracing::info!(connection_id = %connection_id, program = %program, command_len = command.len(), "执行远程命令");
+    tracing::debug!(connection_id = %connection_id, command = %command, "远程命令内容");
+
+    tokio::task::spawn_blocking(move || {
+        let timeout = timeout_secs.map(std::time::Duration::from_secs);
+
+        let mut on_output = |stream: exec::OutputStream, data: &[u8]| {
+            if let Some(stream_id) = &stream_id {
+                let _ = app_handle.emit("exec_output", serde_json::json!({
+                    "stream_id": stream_id,
+                    "stream": stream.name(),
+                    "data": general_purpose::STANDARD.encode(data)
+                }));
+            }
+        };
+
+        exec::run_on_connection(&connection_id, &command, timeout, &mut on_output)
+    }).await.map_err(SftpError::task)?
+}
+
+// 打开交互式终端，输出通过 terminal_output 事件推送
+#[tauri::command]
+async fn open_terminal(
+    app_handle: tauri::AppHandle,
+    connection_id: String,
+    cols: u32,
+    rows: u32,
+) -> Result<String, SftpError> {
+    tokio::task::spawn_blocking(move || {
+        terminal::open(app_handle, connection_id, cols, rows)
+    }).await.map_err(SftpError::task)?
+}
+
+// 向终端发送键盘输入
+#[tauri::command]
+async fn write_terminal(terminal_id: String, data: String) -> Result<(), SftpError> {
+    terminal::send(&terminal_id, terminal::TerminalCommand::Input(data.into_bytes()))
+}
+
+// 调整终端窗口大小
+#[tauri::command]
+async fn resize_terminal(terminal_id: String, cols: u32, rows: u32) -> Result<(), SftpError> {
+    terminal::send(&terminal_id, terminal::TerminalCommand::Resize(cols, rows))
+}
+
+// 关闭终端
+#[tauri::command]
+async fn close_terminal(terminal_id: String) -> Result<String, SftpError> {
+    terminal::send(&terminal_id, terminal::TerminalCommand::Close)?;
+    Ok(i18n::text("msg.terminal_closed").to_string())
+}
+
+// 列出连接上打开的终端
+#[tauri::command]
+async fn list_terminals(connection_id: String) -> Result<Vec<String>, SftpError> {
+    Ok(terminal::list(&connection_id))
+}
+
+// 创建本地端口转发（ssh -L）
+#[tauri::command]
+async fn start_local_forward(
+    app_handle: tauri::AppHandle,
+    connection_id: String,
+    bind_address: Option<String>,
+    bind_port: u16,
+    target_host: String,
+    target_port: u16,
+) -> Result<forward::ForwardInfo, SftpError> {
+    tokio::task::spawn_blocking(move || {
+        let bind_address = bind_address.unwrap_or_else(|| "127.0.0.1".to_string());
+        forward::start_local(app_handle, connection_id, bind_address, bind_port, target_host, target_port)
+    }).await.map_err(SftpError::task)?
+}
+
+// 创建远程端口转发（ssh -R），将远程主机上的端口转回本地服务
+#[tauri::command]
+async fn start_remote_forward(
+    app_handle: tauri::AppHandle,
+    connection_id: String,
+    remote_host: Option<String>,
+    remote_port: u16,
+    target_host: String,
+    target_port: u16,
+) -> Result<forward::ForwardInfo, SftpError> {
+    tokio::task::spawn_blocking(move || {
+        let remote_host = remote_host.unwrap_or_else(|| "localhost".to_string());
+        forward::start_remote(app_handle, connection_id, remote_host, remote_port, target_host, target_port)
+    }).await.map_err(SftpError::task)?
+}
+
+// 启动 SOCKS5 代理（ssh -D），可选用户名/密码认证
+#[tauri::command]
+async fn start_socks_proxy(
+    app_handle: tauri::AppHandle,
+    connection_id: String,
+    bind_address: Option<String>,
+    bind_port: u16,
+    username: Option<String>,
+    password: Option<String>,
+) -> Result<forward::ForwardInfo, SftpError> {
+    tokio::task::spawn_blocking(move || {
+        let bind_address = bind_address.unwrap_or_else(|| "127.0.0.1".to_string());
+        let credentials = username.map(|username| socks::SocksCredentials {
+            username,
+            password: password.unwrap_or_default(),
+        });
+        socks::start(app_handle, connection_id, bind_address, bind_port, credentials)
+    }).await.map_err(SftpError::task)?
+}
+
+// 关闭端口转发
+#[tauri::command]
+async fn stop_forward(forward_id: String) -> Result<String, SftpError> {
+    forward::stop(&forward_id)?;
+    Ok(i18n::text("msg.forward_stopped").to_string())
+}
+
+// 列出端口转发及其流量统计，不指定连接时返回全部
+#[tauri::command]
+async fn list_forwards(connection_id: Option<String>) -> Result<Vec<forward::ForwardInfo>, SftpError> {
+    Ok(forward::list(connection_id.as_deref()))
+}
+
+// 连接实际使用的文件访问方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConnectionMode {
+    Sftp,
+    // SFTP 子系统不可用，降级为 SCP 传输加远程命令
+    Scp,
+    Ftp,
+    #[serde(rename = "webdav")]
+    WebDav,
+    S3,
+    Local,
+}
+
+// 查询连接模式，界面据此提示降级的连接
+#[tauri::command]
+async fn get_connection_mode(connection_id: String) -> Result<ConnectionMode, SftpError> {
+    // 只查连接表，不等待连接上正在进行的操作
+    Ok(match Connection::find(&connection_id)? {
+        Connection::Local => ConnectionMode::Local,
+        Connection::Ssh { scp_only: true, .. } => ConnectionMode::Scp,
+        Connection::Ssh { scp_only: false, .. } => ConnectionMode::Sftp,
+        Connection::Ftp(_) => ConnectionMode::Ftp,
+        Connection::WebDav(_) => ConnectionMode::WebDav,
+        Connection::S3(_) => ConnectionMode::S3,
+    })
+}
+
+// 获取连接状态和信息
+#[tauri::command]
+async fn get_connection_info(connection_id: String) -> Result<String, SftpError> {
+    tokio::task::spawn_blocking(move || {
+        let connection = Connection::find(&connection_id)?;
+        let guard = connection.lock();
+        match guard {
+            ConnectionGuard::Ftp(ftp) => match ftp.pwd() {
+                Ok(cwd) => Ok(i18n::format("msg.connection_active", &[&cwd])),
+                Err(e) => Ok(i18n::format("msg.connection_active_no_cwd", &[&e])),
+            },
+            ConnectionGuard::WebDav(webdav) => Ok(i18n::format("msg.connection_active", &[&webdav.root_url()])),
+            ConnectionGuard::S3(s3) => Ok(i18n::format("msg.connection_active", &[&format!("s3://{}", s3.bucket())])),
+            ConnectionGuard::Ssh { scp_only: true, .. } => Ok(i18n::text("msg.connection_active_scp").to_string()),
+            ConnectionGuard::Ssh { lock, scp_only: false, .. } => {
+                let session = lock.lock().unwrap();
+                let sftp = session.sftp()
+                    .map_err(|e| SftpError::ssh("action.create_sftp", e))?;
+
+                // 尝试获取当前工作目录
+                match sftp.realpath(Path::new(".")) {
+                    Ok(cwd) => Ok(i18n::format("msg.connection_active", &[&cwd.display()])),
+                    Err(e) => Ok(i18n::format("msg.connection_active_no_cwd", &[&e])),
+                }
+            }
+            ConnectionGuard::Local => Err(SftpError::not_connected(&connection_id)),
+        }
+    }).await.map_err(SftpError::task)?
+}
+
+// 获取默认下载目录
+#[tauri::command]
+async fn get_downloads_directory() -> Result<String, SftpError> {
+    // 获取用户的下载目录
+    let downloads_dir = if cfg!(target_os = "windows") {
+        // Windows: 使用用户目录下的 Downloads 文件夹
+        match std::env::var("USERPROFILE") {
+            Ok(user_profile) => format!("{}\\Downloads", user_profile),
+            Err(_) => {
+                // 备选方案：使用当前目录下的 downloads 文件夹
+                let current_dir = std::env::current_dir()
+                    .map_err(|e| SftpError::io("action.current_dir", e))?;
+                format!("{}\\downloads", current_dir.display())
+            }
+        }
+    } else {
+        // Unix/Linux/macOS: 使用用户目录下的 Downloads 文件夹
+        match std::env::var("HOME") {
+            Ok(home) => format!("{}/Downloads", home),
+            Err(_) => {
+                // 备选方案：使用当前目录下的 downloads 文件夹
+                let current_dir = std::env::current_dir()
+                    .map_err(|e| SftpError::io("action.current_dir", e))?;
+                format!("{}/downloads", current_dir.display())
+            }
+        }
+    };
+
+    tracing::debug!(downloads_dir = %downloads_dir, "默认下载目录");
+
+    // 确保目录存在
+    fs::create_dir_all(&downloads_dir)
+        .map_err(|e| SftpError::io("action.create_downloads_dir", e))?;
+
+    Ok(downloads_dir)
+}
+
+// 断开连接
+#[tauri::command]
+async fn disconnect_sftp(connection_id: String) -> Result<String, SftpError> {
+    tokio::task::spawn_blocking(move || {
+        close_connection(&connection_id);
+        tracing::info!(connection_id = %connection_id, "连接已断开");
+        Ok(i18n::text("msg.disconnected").to_string())
+    }).await.map_err(SftpError::task)?
+}
+
+// 打开文件夹
+#[tauri::command]
+async fn open_file_folder(path: String) -> Result<String, SftpError> {
+    tracing::debug!(path = %path, "打开文件夹");
+
+    tokio::task::spawn_blocking(move || {
+        #[cfg(target_os = "windows")]
+        {
+            use std::process::Command;
+            let result = Command::new("explorer")
+                .arg(&path)
+                .spawn();
+
+            match result {
+                Ok(_) => Ok(i18n::format("msg.folder_opened", &[&path])),
+                Err(e) => Err(SftpError::io("action.open_folder", e))
+            }
+        }
+
+        #[cfg(target_os = "macos")]
+        {
+            use std::process::Command;
+            let result = Command::new("open")
+                .arg(&path)
+                .spawn();
+
+            match result {
+                Ok(_) => Ok(i18n::format("msg.folder_opened", &[&path])),
+                Err(e) => Err(SftpError::io("action.open_folder", e))
+            }
+        }
+
+        #[cfg(target_os = "linux")]
+        {
+            use std::process::Command;
+            let result = Command::new("xdg-open")
+                .arg(&path)
+                .spawn();
+
+            match result {
+                Ok(_) => Ok(i18n::format("msg.folder_opened", &[&path])),
+                Err(e) => Err(SftpError::io("action.open_folder", e))
+            }
+        }
+
+        #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+        {
+            Err(SftpError::new(ErrorCode::Unsupported, i18n::text("error.unsupported_os")))
+        }
+    }).await.map_err(SftpError::task)?
+}
+
+// 取消传输任务
+#[tauri::command]
+async fn cancel_transfer(transfer_id: String) -> Result<String, SftpError> {
+    tracing::info!(transfer_id = %transfer_id, "取消传输");
+
+    let mut tasks = TRANSFER_TASKS.lock().unwrap();
+    if let Some(cancel_flag) = tasks.get(&transfer_id) {
+        cancel_flag.store(true, std::sync::atomic::Ordering::SeqCst);
+        tasks.remove(&transfer_id);
+        Ok(i18n::format("msg.transfer_cancelled", &[&transfer_id]))
+    } else {
+        Err(SftpError::new(ErrorCode::NotFound, i18n::format("error.transfer_not_found", &[&transfer_id])))
+    }
+}
+
+// 读取最近的日志，用于在界面中查看或附在问题反馈中
+#[tauri::command]
+async fn read_logs(max_lines: Option<usize>) -> Result<Vec<String>, SftpError> {
+    tokio::task::spawn_blocking(move || {
+        logging::read_recent(max_lines.unwrap_or(logging::DEFAULT_READ_LINES))
+    }).await.map_err(SftpError::task)?
+}
+
+// 将全部日志导出到指定文件
+#[tauri::command]
+async fn export_logs(destination: String) -> Result<String, SftpError> {
+    tokio::task::spawn_blocking(move || {
+        logging::export(Path::new(&destination))?;
+        Ok(i18n::format("msg.logs_exported", &[&destination]))
+    }).await.map_err(SftpError::task)?
+}
+
+// 获取日志目录
+#[tauri::command]
+async fn get_log_directory() -> Result<String, SftpError> {
+    Ok(logging::log_dir()?.to_string_lossy().to_string())
+}
+
+#[cfg_attr(mobile, tauri::mobile_entry_point)]
+pub fn run() {
+    tauri::Builder::default()
+        .plugin(tauri_plugin_opener::init())
+        .setup(|app| {
+            let log_dir = app.path().app_data_dir()?.join("logs");
+            if let Err(e) = logging::init(log_dir) {
+                eprintln!("{}", e);
+            }
+            Ok(())
+        })
+        .invoke_handler(tauri::generate_handler![
+            test_sftp_connection,
+            connect_sftp,
+            set_default_proxy,
+            get_default_proxy,
+            set_locale,
+            get_locale,
+            list_directory,
+            download_file,
+            download_file_with_progress,
+            upload_file,
+            upload_file_data,
+            create_directory,
+            delete_file,
+            rename_file,
+            move_files,
+            set_file_attributes,
+            copy_remote_file,
+            transfer_between_connections,
+            plan_sync,
+            apply_sync,
+            read_symlink,
+            create_symlink,
+            exec_command,
+            open_terminal,
+            write_terminal,
+            resize_terminal,
+            close_terminal,
+            list_terminals,
+            start_local_forward,
+            start_remote_forward,
+            start_socks_proxy,
+            stop_forward,
+            list_forwards,
+            get_connection_info,
+            get_connection_mode,
+            get_downloads_directory,
+            disconnect_sftp,
+            open_file_folder,
+            cancel_transfer,
+            read_logs,
+            export_logs,
+            get_log_directory
+        ])
+        .run(tauri::generate_context!())
+        .expect("error while running tauri application");
+}
@@ -0,0 +1,289 @@
+// 命令返回给前端的错误类型
+// code 为稳定的错误分类，前端据此区分处理，不依赖 message 的具体文本
+
+use crate::i18n;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ErrorCode {
+    // 无法建立 TCP 连接或握手失败
+    ConnectionFailed,
+    // 连接在操作过程中断开
+    ConnectionLost,
+    // 连接 ID 不存在（未连接或已断开）
+    NotConnected,
+    AuthFailed,
+    // 服务器主机密钥与 known_hosts 中的记录不一致
+    HostKeyMismatch,
+    ProxyFailed,
+    NotFound,
+    PermissionDenied,
+    AlreadyExists,
+    Timeout,
+    Cancelled,
+    Unsupported,
+    InvalidInput,
+    // 本地文件系统错误
+    LocalIo,
+    // 服务器返回的其他错误
+    Failure,
+    // 应用内部错误，例如后台任务异常退出
+    Internal,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SftpError {
+    pub code: ErrorCode,
+    // 服务器返回的 SFTP 状态码（SSH_FX_*），非 SFTP 错误时为空
+    pub sftp_status: Option<i32>,
+    pub message: String,
+}
+
+// libssh2 会话错误码
+const LIBSSH2_ERROR_SOCKET_SEND: i32 = -7;
+const LIBSSH2_ERROR_TIMEOUT: i32 = -9;
+const LIBSSH2_ERROR_SOCKET_DISCONNECT: i32 = -13;
+const LIBSSH2_ERROR_PUBLICKEY_UNVERIFIED: i32 = -16;
+const LIBSSH2_ERROR_AUTHENTICATION_FAILED: i32 = -18;
+const LIBSSH2_ERROR_PUBLICKEY_UNRECOGNIZED: i32 = -19;
+const LIBSSH2_ERROR_CHANNEL_CLOSED: i32 = -26;
+const LIBSSH2_ERROR_SOCKET_RECV: i32 = -43;
+
+// SFTP 状态码
+const SSH_FX_NO_SUCH_FILE: i32 = 2;
+const SSH_FX_PERMISSION_DENIED: i32 = 3;
+const SSH_FX_NO_CONNECTION: i32 = 6;
+const SSH_FX_CONNECTION_LOST: i32 = 7;
+const SSH_FX_OP_UNSUPPORTED: i32 = 8;
+const SSH_FX_NO_SUCH_PATH: i32 = 10;
+const SSH_FX_FILE_ALREADY_EXISTS: i32 = 11;
+const SSH_FX_WRITE_PROTECT: i32 = 12;
+
+impl SftpError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        SftpError {
+            code,
+            sftp_status: None,
+            message: message.into(),
+        }
+    }
+
+    // SSH/SFTP 操作失败，action 为 i18n 中的操作名称 key
+    pub(crate) fn ssh(action: &'static str, e: ssh2::Error) -> Self {
+        SftpError::from_ssh(i18n::text(action), e)
+    }
+
+    // 同 ssh，错误信息中附带出错的路径
+    pub(crate) fn ssh_at(action: &'static str, path: &str, e: ssh2::Error) -> Self {
+        SftpError::from_ssh(&format!("{} [{}]", i18n::text(action), path), e)
+    }
+
+    pub(crate) fn io(action: &'static str, e: std::io::Error) -> Self {
+        SftpError::from_io(i18n::text(action), e)
+    }
+
+    pub(crate) fn io_at(action: &'static str, path: &str, e: std::io::Error) -> Self {
+        SftpError::from_io(&format!("{} [{}]", i18n::text(action), path), e)
+    }
+
+    // FTP 操作失败，按服务器返回的应答码分类
+    pub(crate) fn ftp(action: &'static str, e: suppaftp::FtpError) -> Self {
+        SftpError::from_ftp(i18n::text(action), e)
+    }
+
+    pub(crate) fn ftp_at(action: &'static str, path: &str, e: suppaftp::FtpError) -> Self {
+        SftpError::from_ftp(&format!("{} [{}]", i18n::text(action), path), e)
+    }
+
+    fn from_ftp(action: &str, e: suppaftp::FtpError) -> Self {
+        let code = match &e {
+            suppaftp::FtpError::ConnectionError(io_error) => match io_error.kind() {
+                std::io::ErrorKind::TimedOut | std::io::ErrorKind::WouldBlock => ErrorCode::Timeout,
+                std::io::ErrorKind::ConnectionRefused => ErrorCode::ConnectionFailed,
+                _ => ErrorCode::ConnectionLost,
+            },
+            suppaftp::FtpError::SecureError(_) => ErrorCode::ConnectionFailed,
+            suppaftp::FtpError::UnexpectedResponse(response) => ftp_reply_code(response),
+            suppaftp::FtpError::InvalidAddress(_) => ErrorCode::InvalidInput,
+            suppaftp::FtpError::BadResponse => ErrorCode::Failure,
+        };
+
+        SftpError::new(code, describe(action, code, &e))
+    }
+
+    // HTTP 请求未能完成（连接、TLS、超时等）
+    pub(crate) fn http(action: &'static str, path: &str, e: reqwest::Error) -> Self {
+        let code = if e.is_timeout() {
+            ErrorCode::Timeout
+        } else if e.is_connect() {
+            ErrorCode::ConnectionFailed
+        } else if e.is_builder() {
+            ErrorCode::InvalidInput
+        } else {
+            ErrorCode::ConnectionLost
+        };
+
+        SftpError::new(code, describe(&format!("{} [{}]", i18n::text(action), path), code, &e))
+    }
+
+    // 服务器以错误状态码响应
+    pub(crate) fn http_status(action: &'static str, path: &str, status: reqwest::StatusCode) -> Self {
+        let code = http_status_code(status);
+        SftpError::new(code, describe(&format!("{} [{}]", i18n::text(action), path), code, &status))
+    }
+
+    // S3 错误响应，error_code 为响应体中的 <Code>，优先于状态码分类
+    pub(crate) fn s3(action: &'static str, path: &str, status: reqwest::StatusCode, error_code: &str, message: &str) -> Self {
+        let code = match error_code {
+            "InvalidAccessKeyId" | "SignatureDoesNotMatch" => ErrorCode::AuthFailed,
+            "NoSuchKey" | "NoSuchBucket" | "NoSuchUpload" => ErrorCode::NotFound,
+            "AccessDenied" => ErrorCode::PermissionDenied,
+            "RequestTimeout" => ErrorCode::Timeout,
+            _ => http_status_code(status),
+        };
+
+        let detail = match (error_code.is_empty(), message.is_empty()) {
+            (true, _) => status.to_string(),
+            (false, true) => error_code.to_string(),
+            (false, false) => format!("{}: {}", error_code, message),
+        };
+        SftpError::new(code, describe(&format!("{} [{}]", i18n::text(action), path), code, &detail))
+    }
+
+    // 根据 libssh2 错误码或 SFTP 状态码分类
+    fn from_ssh(action: &str, e: ssh2::Error) -> Self {
+        let (code, sftp_status) = match e.code() {
+            ssh2::ErrorCode::SFTP(status) => (sftp_status_code(status), Some(status)),
+            ssh2::ErrorCode::Session(code) => (session_error_code(code), None),
+        };
+
+        SftpError {
+            code,
+            sftp_status,
+            message: describe(action, code, &e),
+        }
+    }
+
+    // 直接解析协议数据包得到的 SFTP 状态码
+    pub(crate) fn sftp_status(status: i32, message: impl Into<String>) -> Self {
+        SftpError {
+            code: sftp_status_code(status),
+            sftp_status: Some(status),
+            message: message.into(),
+        }
+    }
+
+    // 本地或经 io::Error 包装的远程读写失败；远程读写流在 io::Error 中携带的 SftpError 原样取回，保留错误分类
+    fn from_io(action: &str, e: std::io::Error) -> Self {
+        if let Some(error) = e.get_ref().and_then(|inner| inner.downcast_ref::<SftpError>()) {
+            return error.clone();
+        }
+
+        let code = match e.kind() {
+            std::io::ErrorKind::NotFound => ErrorCode::NotFound,
+            std::io::ErrorKind::PermissionDenied => ErrorCode::PermissionDenied,
+            std::io::ErrorKind::AlreadyExists => ErrorCode::AlreadyExists,
+            std::io::ErrorKind::TimedOut | std::io::ErrorKind::WouldBlock => ErrorCode::Timeout,
+            std::io::ErrorKind::ConnectionRefused
+            | std::io::ErrorKind::ConnectionReset
+            | std::io::ErrorKind::ConnectionAborted
+            | std::io::ErrorKind::HostUnreachable
+            | std::io::ErrorKind::NetworkUnreachable => ErrorCode::ConnectionFailed,
+            _ => ErrorCode::LocalIo,
+        };
+
+        SftpError::new(code, describe(action, code, &e))
+    }
+
+    pub(crate) fn with_code(mut self, code: ErrorCode) -> Self {
+        self.code = code;
+        self
+    }
+
+    #[cfg(feature = "gui")]
+    pub(crate) fn not_connected(connection_id: &str) -> Self {
+        SftpError::new(ErrorCode::NotConnected, i18n::format("error.not_connected", &[&connection_id]))
+    }
+
+    pub(crate) fn cancelled() -> Self {
+        SftpError::new(ErrorCode::Cancelled, i18n::text("error.cancelled"))
+    }
+
+    #[cfg(feature = "gui")]
+    pub(crate) fn task(e: tokio::task::JoinError) -> Self {
+        SftpError::new(ErrorCode::Internal, i18n::format("error.task", &[&e]))
+    }
+}
+
+impl fmt::Display for SftpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for SftpError {}
+
+fn describe(action: &str, code: ErrorCode, e: &dyn fmt::Display) -> String {
+    let key = if code == ErrorCode::Timeout { "error.timed_out" } else { "error.failed" };
+    i18n::format(key, &[&action, e])
+}
+
+fn session_error_code(code: i32) -> ErrorCode {
+    match code {
+        LIBSSH2_ERROR_TIMEOUT => ErrorCode::Timeout,
+        LIBSSH2_ERROR_AUTHENTICATION_FAILED
+        | LIBSSH2_ERROR_PUBLICKEY_UNVERIFIED
+        | LIBSSH2_ERROR_PUBLICKEY_UNRECOGNIZED => ErrorCode::AuthFailed,
+        LIBSSH2_ERROR_SOCKET_SEND
+        | LIBSSH2_ERROR_SOCKET_RECV
+        | LIBSSH2_ERROR_SOCKET_DISCONNECT
+        | LIBSSH2_ERROR_CHANNEL_CLOSED => ErrorCode::ConnectionLost,
+        _ => ErrorCode::Failure,
+    }
+}
+
+fn sftp_status_code(status: i32) -> ErrorCode {
+    match status {
+        SSH_FX_NO_SUCH_FILE | SSH_FX_NO_SUCH_PATH => ErrorCode::NotFound,
+        SSH_FX_PERMISSION_DENIED | SSH_FX_WRITE_PROTECT => ErrorCode::PermissionDenied,
+        SSH_FX_FILE_ALREADY_EXISTS => ErrorCode::AlreadyExists,
+        SSH_FX_OP_UNSUPPORTED => ErrorCode::Unsupported,
+        SSH_FX_NO_CONNECTION | SSH_FX_CONNECTION_LOST => ErrorCode::ConnectionLost,
+        _ => ErrorCode::Failure,
+    }
+}
+
+// FTP 应答码：550 同时用于文件不存在和无权限，只能根据应答文本区分
+fn ftp_reply_code(response: &suppaftp::types::Response) -> ErrorCode {
+    match response.status.code() {
+        530 | 532 => ErrorCode::AuthFailed,
+        550 => {
+            let text = String::from_utf8_lossy(&response.body).to_lowercase();
+            if text.contains("permission") || text.contains("denied") {
+                ErrorCode::PermissionDenied
+            } else {
+                ErrorCode::NotFound
+            }
+        }
+        553 => ErrorCode::PermissionDenied,
+        421 | 425 | 426 => ErrorCode::ConnectionLost,
+        500 | 502 | 504 => ErrorCode::Unsupported,
+        501 => ErrorCode::InvalidInput,
+        _ => ErrorCode::Failure,
+    }
+}
+
+// WebDAV 常见状态码：409 表示父目录不存在，412 表示 Overwrite: F 时目标已存在，423 表示被锁定
+fn http_status_code(status: reqwest::StatusCode) -> ErrorCode {
+    match status.as_u16() {
+        401 => ErrorCode::AuthFailed,
+        403 | 423 => ErrorCode::PermissionDenied,
+        404 | 409 | 410 => ErrorCode::NotFound,
+        412 => ErrorCode::AlreadyExists,
+        408 | 504 => ErrorCode::Timeout,
+        400 | 414 => ErrorCode::InvalidInput,
+        405 | 501 => ErrorCode::Unsupported,
+        _ => ErrorCode::Failure,
+    }
+}
@@ -0,0 +1,171 @@
+// 通过 exec 通道在远程主机上执行命令
+// 输出在非阻塞模式下轮询读取，stdout 和 stderr 交替读取，避免其中一路写满窗口后互相等待
+
+#[cfg(feature = "gui")]
+use crate::commands::ssh_session;
+use crate::error::{ErrorCode, SftpError};
+use crate::i18n;
+use serde::{Deserialize, Serialize};
+use ssh2::{Channel, Session};
+use std::io::{ErrorKind, Read};
+use std::time::{Duration, Instant};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExecOutput {
+    pub stdout: String,
+    pub stderr: String,
+    pub exit_status: i32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum OutputStream {
+    Stdout,
+    Stderr,
+}
+
+#[cfg(feature = "gui")]
+impl OutputStream {
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            OutputStream::Stdout => "stdout",
+            OutputStream::Stderr => "stderr",
+        }
+    }
+}
+
+struct Execution {
+    channel: Channel,
+    stdout: Vec<u8>,
+    stderr: Vec<u8>,
+}
+
+impl Execution {
+    fn start(session: &Session, command: &str) -> Result<Self, SftpError> {
+        let mut channel = session.channel_session()
+            .map_err(|e| SftpError::ssh("action.open_channel", e))?;
+        channel.exec(command)
+            .map_err(|e| SftpError::ssh("action.exec_command", e))?;
+        // 关闭标准输入，避免远程命令等待输入
+        let _ = channel.send_eof();
+
+        Ok(Execution {
+            channel,
+            stdout: Vec::new(),
+            stderr: Vec::new(),
+        })
+    }
+
+    // 以非阻塞方式读取当前可用的输出，返回命令是否已经结束
+    fn poll(&mut self, session: &Session, on_output: &mut dyn FnMut(OutputStream, &[u8])) -> Result<bool, SftpError> {
+        let mut buffer = [0u8; 16 * 1024];
+
+        session.set_blocking(false);
+        let result = (|| {
+            for stream in [OutputStream::Stdout, OutputStream::Stderr] {
+                loop {
+                    let read = match stream {
+                        OutputStream::Stdout => self.channel.read(&mut buffer),
+                        OutputStream::Stderr => self.channel.stderr().read(&mut buffer),
+                    };
+                    match read {
+                        Ok(0) => break,
+                        Ok(n) => {
+                            on_output(stream, &buffer[..n]);
+                            match stream {
+                                OutputStream::Stdout => self.stdout.extend_from_slice(&buffer[..n]),
+                                OutputStream::Stderr => self.stderr.extend_from_slice(&buffer[..n]),
+                            }
+                        }
+                        Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                        Err(e) => return Err(SftpError::io("action.read_command_output", e)),
+                    }
+                }
+            }
+            Ok(self.channel.eof())
+        })();
+        session.set_blocking(true);
+
+        result
+    }
+
+    fn finish(mut self) -> Result<ExecOutput, SftpError> {
+        self.channel.close()
+            .and_then(|_| self.channel.wait_close())
+            .map_err(|e| SftpError::ssh("action.close_channel", e))?;
+        let exit_status = self.channel.exit_status()
+            .map_err(|e| SftpError::ssh("action.exit_status", e))?;
+
+        Ok(ExecOutput {
+            stdout: String::from_utf8_lossy(&self.stdout).to_string(),
+            stderr: String::from_utf8_lossy(&self.stderr).to_string(),
+            exit_status,
+        })
+    }
+
+    fn abort(mut self) {
+        let _ = self.channel.close();
+    }
+}
+
+// 在调用方已持有的会话上执行命令并等待结束，会话设置的操作超时作为整条命令的期限
+pub(crate) fn run(session: &Session, command: &str) -> Result<ExecOutput, SftpError> {
+    let timeout = match session.timeout() {
+        0 => None,
+        millis => Some(Duration::from_millis(millis as u64)),
+    };
+    let deadline = timeout.map(|timeout| Instant::now() + timeout);
+    let mut execution = Execution::start(session, command)?;
+
+    while !execution.poll(session, &mut |_, _| {})? {
+        if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            execution.abort();
+            return Err(SftpError::new(ErrorCode::Timeout, i18n::text("error.exec_timeout")));
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    }
+
+    execution.finish()
+}
+
+// 执行命令，轮询之间释放连接锁，以免长时间运行的命令阻塞其他操作
+#[cfg(feature = "gui")]
+pub(crate) fn run_on_connection(
+    connection_id: &str,
+    command: &str,
+    timeout: Option<Duration>,
+    on_output: &mut dyn FnMut(OutputStream, &[u8]),
+) -> Result<ExecOutput, SftpError> {
+    let deadline = timeout.map(|timeout| Instant::now() + timeout);
+
+    let mut execution = {
+        let connection = ssh_session(connection_id)
+            .ok_or_else(|| SftpError::not_connected(connection_id))?;
+        let session = connection.lock().unwrap();
+        Execution::start(&session, command)?
+    };
+
+    loop {
+        let connection = ssh_session(connection_id)
+            .ok_or_else(|| SftpError::new(ErrorCode::ConnectionLost, i18n::text("error.connection_lost")))?;
+        let session = connection.lock().unwrap();
+
+        if execution.poll(&session, on_output)? {
+            return execution.finish();
+        }
+
+        if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            execution.abort();
+            return Err(SftpError::new(ErrorCode::Timeout, i18n::text("error.exec_timeout")));
+        }
+
+        drop(session);
+        std::thread::sleep(POLL_INTERVAL);
+    }
+}
+
+// 用单引号包裹参数，供远程 shell 安全使用
+pub(crate) fn shell_quote(arg: &str) -> String {
+    format!("'{}'", arg.replace('\'', r"'\''"))
+}
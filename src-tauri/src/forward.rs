@@ -0,0 +1,489 @@
+// 端口转发
+// 每个转发由一个后台线程负责：接受新连接，并在本地 TCP 连接与 SSH 通道之间搬运数据。
+// 与终端相同，通道读写只在持有连接锁且会话临时切换为非阻塞模式时进行
+
+use crate::commands::ssh_session;
+use crate::error::{ErrorCode, SftpError};
+use crate::i18n;
+use serde::{Deserialize, Serialize};
+use ssh2::{Channel, Session};
+use std::collections::HashMap;
+use std::io::{ErrorKind, Read, Write};
+use std::net::{Shutdown, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::Emitter;
+
+const IDLE_POLL_INTERVAL: Duration = Duration::from_millis(20);
+const BUSY_POLL_INTERVAL: Duration = Duration::from_millis(1);
+// 单方向缓冲上限，超过后暂停读取，等待对端消费
+const MAX_BUFFERED: usize = 256 * 1024;
+const LOCAL_CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+// 断开连接时等待转发线程退出的最长时间
+const STOP_WAIT_TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ForwardKind {
+    Local,
+    Remote,
+    Dynamic,
+}
+
+// 转发的配置和运行统计
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ForwardInfo {
+    pub id: String,
+    pub connection_id: String,
+    pub kind: ForwardKind,
+    pub bind_address: String,
+    pub bind_port: u16,
+    pub target_host: String,
+    pub target_port: u16,
+    pub bytes_in: u64,
+    pub bytes_out: u64,
+    pub active_connections: u64,
+    pub total_connections: u64,
+}
+
+#[derive(Default)]
+pub(crate) struct ForwardStats {
+    // 从远程流向本地的字节数
+    bytes_in: AtomicU64,
+    // 从本地流向远程的字节数
+    bytes_out: AtomicU64,
+    active_connections: AtomicU64,
+    total_connections: AtomicU64,
+}
+
+struct ForwardHandle {
+    info: ForwardInfo,
+    stats: Arc<ForwardStats>,
+    stop_flag: Arc<AtomicBool>,
+}
+
+impl ForwardHandle {
+    fn snapshot(&self) -> ForwardInfo {
+        ForwardInfo {
+            bytes_in: self.stats.bytes_in.load(Ordering::Relaxed),
+            bytes_out: self.stats.bytes_out.load(Ordering::Relaxed),
+            active_connections: self.stats.active_connections.load(Ordering::Relaxed),
+            total_connections: self.stats.total_connections.load(Ordering::Relaxed),
+            ..self.info.clone()
+        }
+    }
+}
+
+static FORWARDS: std::sync::LazyLock<Mutex<HashMap<String, ForwardHandle>>> =
+    std::sync::LazyLock::new(|| Mutex::new(HashMap::new()));
+
+static NEXT_FORWARD_ID: AtomicU64 = AtomicU64::new(1);
+
+// 新连接的来源，不同类型的转发各自实现
+pub(crate) trait PipeSource: Send {
+    // 在持有连接锁、会话处于阻塞模式时调用，返回新建立的数据管道
+    fn accept(&mut self, session: &Session) -> Vec<Pipe>;
+}
+
+// 一条本地 TCP 连接与 SSH 通道之间的双向管道
+pub(crate) struct Pipe {
+    stream: TcpStream,
+    channel: Channel,
+    to_channel: Vec<u8>,
+    to_stream: Vec<u8>,
+    stream_eof: bool,
+    channel_eof: bool,
+    eof_sent: bool,
+    failed: bool,
+}
+
+impl Pipe {
+    pub(crate) fn new(stream: TcpStream, channel: Channel) -> std::io::Result<Self> {
+        stream.set_nonblocking(true)?;
+        let _ = stream.set_nodelay(true);
+
+        Ok(Pipe {
+            stream,
+            channel,
+            to_channel: Vec::new(),
+            to_stream: Vec::new(),
+            stream_eof: false,
+            channel_eof: false,
+            eof_sent: false,
+            failed: false,
+        })
+    }
+
+    // 搬运一轮数据，必须在会话处于非阻塞模式时调用；返回是否有数据流动
+    fn pump(&mut self, stats: &ForwardStats) -> bool {
+        let mut buffer = [0u8; 32 * 1024];
+        let mut progressed = false;
+
+        // 本地 -> 远程
+        if !self.stream_eof && self.to_channel.len() < MAX_BUFFERED {
+            match self.stream.read(&mut buffer) {
+                Ok(0) => self.stream_eof = true,
+                Ok(n) => {
+                    self.to_channel.extend_from_slice(&buffer[..n]);
+                    progressed = true;
+                }
+                Err(e) if e.kind() == ErrorKind::WouldBlock => {}
+                Err(_) => self.failed = true,
+            }
+        }
+
+        if !self.to_channel.is_empty() {
+            match self.channel.write(&self.to_channel) {
+                Ok(n) => {
+                    self.to_channel.drain(..n);
+                    stats.bytes_out.fetch_add(n as u64, Ordering::Relaxed);
+                    progressed |= n > 0;
+                }
+                Err(e) if e.kind() == ErrorKind::WouldBlock => {}
+                Err(_) => self.failed = true,
+            }
+        }
+
+        if self.stream_eof && self.to_channel.is_empty() && !self.eof_sent {
+            self.eof_sent = self.channel.send_eof().is_ok();
+        }
+
+        // 远程 -> 本地
+        if !self.channel_eof && self.to_stream.len() < MAX_BUFFERED {
+            match self.channel.read(&mut buffer) {
+                Ok(0) => self.channel_eof = self.channel.eof(),
+                Ok(n) => {
+                    self.to_stream.extend_from_slice(&buffer[..n]);
+                    progressed = true;
+                }
+                Err(e) if e.kind() == ErrorKind::WouldBlock => {}
+                Err(_) => self.failed = true,
+            }
+        }
+
+        if !self.to_stream.is_empty() {
+            match self.stream.write(&self.to_stream) {
+                Ok(n) => {
+                    self.to_stream.drain(..n);
+                    stats.bytes_in.fetch_add(n as u64, Ordering::Relaxed);
+                    progressed |= n > 0;
+                }
+                Err(e) if e.kind() == ErrorKind::WouldBlock => {}
+                Err(_) => self.failed = true,
+            }
+        }
+
+        if self.channel_eof && self.to_stream.is_empty() {
+            let _ = self.stream.shutdown(Shutdown::Write);
+        }
+
+        progressed
+    }
+
+    fn is_finished(&self) -> bool {
+        self.failed
+            || (self.stream_eof && self.channel_eof && self.to_channel.is_empty() && self.to_stream.is_empty())
+    }
+}
+
+// 本地端口转发：本地监听端口的每个连接都通过 direct-tcpip 通道连到目标地址
+struct LocalSource {
+    listener: TcpListener,
+    target_host: String,
+    target_port: u16,
+}
+
+impl PipeSource for LocalSource {
+    fn accept(&mut self, session: &Session) -> Vec<Pipe> {
+        let mut pipes = Vec::new();
+
+        loop {
+            let (stream, peer) = match self.listener.accept() {
+                Ok(accepted) => accepted,
+                Err(_) => break,
+            };
+
+            let peer_ip = peer.ip().to_string();
+            match session.channel_direct_tcpip(&self.target_host, self.target_port, Some((&peer_ip, peer.port()))) {
+                Ok(channel) => match Pipe::new(stream, channel) {
+                    Ok(pipe) => pipes.push(pipe),
+                    Err(e) => tracing::warn!("转发连接初始化失败: {}", e),
+                },
+                Err(e) => tracing::warn!(target_host = %self.target_host, target_port = self.target_port, "打开转发通道失败: {}", e),
+            }
+        }
+
+        pipes
+    }
+}
+
+// 远程端口转发：远程主机监听端口上的每个连接都转回本地服务
+// 连接本地服务（DNS 解析和 TCP 握手）在单独的线程中进行，不占用连接锁；
+// 通道在连接完成前暂存在 pending 中
+struct RemoteSource {
+    listener: ssh2::Listener,
+    target_host: String,
+    target_port: u16,
+    pending: Vec<(Channel, Receiver<std::io::Result<TcpStream>>)>,
+}
+
+impl PipeSource for RemoteSource {
+    fn accept(&mut self, session: &Session) -> Vec<Pipe> {
+        loop {
+            // 远程监听的 accept 会一直阻塞，只能在非阻塞模式下轮询
+            session.set_blocking(false);
+            let accepted = self.listener.accept();
+            session.set_blocking(true);
+
+            let Ok(channel) = accepted else {
+                break;
+            };
+
+            let (sender, receiver) = mpsc::channel();
+            let (host, port) = (self.target_host.clone(), self.target_port);
+            std::thread::spawn(move || {
+                let _ = sender.send(connect_local(&host, port));
+            });
+            self.pending.push((channel, receiver));
+        }
+
+        let mut pipes = Vec::new();
+        let mut waiting = Vec::new();
+        for (channel, receiver) in self.pending.drain(..) {
+            match receiver.try_recv() {
+                Ok(Ok(stream)) => match Pipe::new(stream, channel) {
+                    Ok(pipe) => pipes.push(pipe),
+                    Err(e) => tracing::warn!("转发连接初始化失败: {}", e),
+                },
+                Ok(Err(e)) => tracing::warn!(target_host = %self.target_host, target_port = self.target_port, "连接本地服务失败: {}", e),
+                Err(TryRecvError::Empty) => waiting.push((channel, receiver)),
+                Err(TryRecvError::Disconnected) => {}
+            }
+        }
+        self.pending = waiting;
+
+        pipes
+    }
+}
+
+fn connect_local(host: &str, port: u16) -> std::io::Result<TcpStream> {
+    use std::net::ToSocketAddrs;
+
+    let mut last_error = std::io::Error::new(ErrorKind::NotFound, "无法解析地址");
+    for addr in (host, port).to_socket_addrs()? {
+        match TcpStream::connect_timeout(&addr, LOCAL_CONNECT_TIMEOUT) {
+            Ok(stream) => return Ok(stream),
+            Err(e) => last_error = e,
+        }
+    }
+    Err(last_error)
+}
+
+// 创建本地端口转发（ssh -L），bind_port 为 0 时自动分配端口
+pub(crate) fn start_local(
+    app_handle: tauri::AppHandle,
+    connection_id: String,
+    bind_address: String,
+    bind_port: u16,
+    target_host: String,
+    target_port: u16,
+) -> Result<ForwardInfo, SftpError> {
+    if ssh_session(&connection_id).is_none() {
+        return Err(SftpError::not_connected(&connection_id));
+    }
+
+    let listener = TcpListener::bind((bind_address.as_str(), bind_port))
+        .map_err(|e| SftpError::io("action.listen_local", e))?;
+    listener.set_nonblocking(true)
+        .map_err(|e| SftpError::io("action.listen_local", e))?;
+    let bind_port = listener.local_addr()
+        .map(|addr| addr.port())
+        .unwrap_or(bind_port);
+
+    let info = ForwardInfo {
+        id: next_forward_id(&connection_id),
+        connection_id,
+        kind: ForwardKind::Local,
+        bind_address,
+        bind_port,
+        target_host: target_host.clone(),
+        target_port,
+        bytes_in: 0,
+        bytes_out: 0,
+        active_connections: 0,
+        total_connections: 0,
+    };
+
+    let source = LocalSource {
+        listener,
+        target_host,
+        target_port,
+    };
+
+    Ok(spawn(app_handle, info, Box::new(source)))
+}
+
+// 创建远程端口转发（ssh -R），remote_port 为 0 时由服务器分配端口
+pub(crate) fn start_remote(
+    app_handle: tauri::AppHandle,
+    connection_id: String,
+    remote_host: String,
+    remote_port: u16,
+    target_host: String,
+    target_port: u16,
+) -> Result<ForwardInfo, SftpError> {
+    let (listener, bound_port) = {
+        let connection = ssh_session(&connection_id)
+            .ok_or_else(|| SftpError::not_connected(&connection_id))?;
+        let session = connection.lock().unwrap();
+
+        session.channel_forward_listen(remote_port, Some(&remote_host), None)
+            .map_err(|e| SftpError::ssh("action.listen_remote", e))?
+    };
+
+    let info = ForwardInfo {
+        id: next_forward_id(&connection_id),
+        connection_id,
+        kind: ForwardKind::Remote,
+        bind_address: remote_host,
+        bind_port: bound_port,
+        target_host: target_host.clone(),
+        target_port,
+        bytes_in: 0,
+        bytes_out: 0,
+        active_connections: 0,
+        total_connections: 0,
+    };
+
+    let source = RemoteSource {
+        listener,
+        target_host,
+        target_port,
+        pending: Vec::new(),
+    };
+
+    Ok(spawn(app_handle, info, Box::new(source)))
+}
+
+pub(crate) fn stop(forward_id: &str) -> Result<(), SftpError> {
+    let forwards = FORWARDS.lock().unwrap();
+    let forward = forwards.get(forward_id)
+        .ok_or_else(|| SftpError::new(ErrorCode::NotFound, i18n::format("error.forward_not_found", &[&forward_id])))?;
+
+    forward.stop_flag.store(true, Ordering::SeqCst);
+    Ok(())
+}
+
+// 停止连接上的所有转发并等待它们退出，断开连接前调用以便远程监听被正常取消
+pub(crate) fn stop_connection(connection_id: &str) {
+    let has_forwards = |forwards: &HashMap<String, ForwardHandle>| {
+        forwards.values().any(|forward| forward.info.connection_id == connection_id)
+    };
+
+    {
+        let forwards = FORWARDS.lock().unwrap();
+        for forward in forwards.values().filter(|forward| forward.info.connection_id == connection_id) {
+            forward.stop_flag.store(true, Ordering::SeqCst);
+        }
+    }
+
+    let deadline = Instant::now() + STOP_WAIT_TIMEOUT;
+    while has_forwards(&FORWARDS.lock().unwrap()) && Instant::now() < deadline {
+        std::thread::sleep(Duration::from_millis(10));
+    }
+}
+
+pub(crate) fn list(connection_id: Option<&str>) -> Vec<ForwardInfo> {
+    FORWARDS.lock().unwrap()
+        .values()
+        .filter(|forward| connection_id.is_none_or(|id| forward.info.connection_id == id))
+        .map(ForwardHandle::snapshot)
+        .collect()
+}
+
+pub(crate) fn next_forward_id(connection_id: &str) -> String {
+    format!("{}-fwd-{}", connection_id, NEXT_FORWARD_ID.fetch_add(1, Ordering::SeqCst))
+}
+
+pub(crate) fn spawn(app_handle: tauri::AppHandle, info: ForwardInfo, source: Box<dyn PipeSource>) -> ForwardInfo {
+    let stats = Arc::new(ForwardStats::default());
+    let stop_flag = Arc::new(AtomicBool::new(false));
+
+    FORWARDS.lock().unwrap().insert(info.id.clone(), ForwardHandle {
+        info: info.clone(),
+        stats: stats.clone(),
+        stop_flag: stop_flag.clone(),
+    });
+
+    let forward_id = info.id.clone();
+    let connection_id = info.connection_id.clone();
+    std::thread::spawn(move || {
+        tracing::info!(connection_id = %connection_id, forward_id = %forward_id, "端口转发已启动");
+        emit_status(&app_handle, &forward_id, &connection_id, "running");
+
+        let status = run_worker(&connection_id, source, &stats, &stop_flag);
+
+        FORWARDS.lock().unwrap().remove(&forward_id);
+        tracing::info!(connection_id = %connection_id, forward_id = %forward_id, status, "端口转发已停止");
+        emit_status(&app_handle, &forward_id, &connection_id, status);
+    });
+
+    info
+}
+
+fn emit_status(app_handle: &tauri::AppHandle, forward_id: &str, connection_id: &str, status: &str) {
+    let _ = app_handle.emit("forward_status", serde_json::json!({
+        "forward_id": forward_id,
+        "connection_id": connection_id,
+        "status": status
+    }));
+}
+
+// 运行转发直到被停止或连接断开，返回最终状态
+fn run_worker(
+    connection_id: &str,
+    mut source: Box<dyn PipeSource>,
+    stats: &ForwardStats,
+    stop_flag: &AtomicBool,
+) -> &'static str {
+    let mut pipes: Vec<Pipe> = Vec::new();
+    let mut status = "stopped";
+
+    while !stop_flag.load(Ordering::SeqCst) {
+        let Some(connection) = ssh_session(connection_id) else {
+            status = "disconnected";
+            break;
+        };
+        let session = connection.lock().unwrap();
+
+        let accepted = source.accept(&session);
+        stats.total_connections.fetch_add(accepted.len() as u64, Ordering::Relaxed);
+        pipes.extend(accepted);
+
+        session.set_blocking(false);
+        let mut busy = false;
+        for pipe in pipes.iter_mut() {
+            busy |= pipe.pump(stats);
+        }
+        session.set_blocking(true);
+
+        // 通道需要在阻塞模式下释放
+        pipes.retain(|pipe| !pipe.is_finished());
+        stats.active_connections.store(pipes.len() as u64, Ordering::Relaxed);
+        drop(session);
+
+        std::thread::sleep(if busy { BUSY_POLL_INTERVAL } else { IDLE_POLL_INTERVAL });
+    }
+
+    // 退出时同样要在持有连接锁、阻塞模式下释放通道和远程监听；连接已从注册表移除时直接释放
+    if let Some(connection) = ssh_session(connection_id) {
+        let _session = connection.lock().unwrap();
+        pipes.clear();
+        drop(source);
+    }
+
+    stats.active_connections.store(0, Ordering::Relaxed);
+    status
+}
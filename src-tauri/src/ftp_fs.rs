@@ -0,0 +1,426 @@
+// FTP / FTPS 的 RemoteFs 实现
+// 目录列表使用 LIST（兼容面最广），文件信息从父目录的列表中查找；
+// 被动模式的数据连接始终连向控制连接的主机名，不使用服务器在 PASV 中返回的地址，
+// 这样既能穿过 NAT，也能与控制连接走同一个代理
+
+use crate::error::{ErrorCode, SftpError};
+use crate::ops::join_remote_path;
+use crate::proxy::{self, ProxyConfig, ProxyType};
+use crate::remote_fs::RemoteFs;
+use crate::{i18n, FileInfo, FileType, TimeoutConfig};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::io::{self, Read, Write};
+use std::time::{Duration, UNIX_EPOCH};
+use suppaftp::list::{File, PosixPexQuery};
+use suppaftp::native_tls::TlsConnector;
+use suppaftp::{FtpError, NativeTlsConnector, NativeTlsFtpStream as FtpStream};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FtpTls {
+    // 明文 FTP
+    #[default]
+    None,
+    // 连接后通过 AUTH TLS 升级（通常为 21 端口）
+    Explicit,
+    // 连接即开始 TLS（通常为 990 端口）
+    Implicit,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FtpMode {
+    #[default]
+    Passive,
+    Active,
+}
+
+// FTP 连接的附加设置
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FtpOptions {
+    pub tls: FtpTls,
+    pub mode: FtpMode,
+    // 接受自签名或不匹配的证书
+    pub accept_invalid_certs: bool,
+}
+
+/// 一个已登录的 FTP 控制连接
+///
+/// FTP 同一时间只能进行一个数据传输；打开的读写流未结束前不能调用其他方法。
+pub struct FtpFs {
+    stream: RefCell<FtpStream>,
+}
+
+impl FtpFs {
+    /// 连接并登录，传输类型设为二进制
+    pub fn connect(
+        host: &str,
+        port: u16,
+        username: &str,
+        password: &str,
+        options: &FtpOptions,
+        proxy: Option<&ProxyConfig>,
+        timeouts: &TimeoutConfig,
+    ) -> Result<Self, SftpError> {
+        let connect_timeout = timeouts.connect_timeout();
+        let tls_connector = || {
+            TlsConnector::builder()
+                .danger_accept_invalid_certs(options.accept_invalid_certs)
+                .danger_accept_invalid_hostnames(options.accept_invalid_certs)
+                .build()
+                .map(NativeTlsConnector::from)
+                .map_err(|e| SftpError::new(ErrorCode::Internal, i18n::format("error.failed", &[&i18n::text("action.tls_setup"), &e])))
+        };
+
+        let stream = if options.tls == FtpTls::Implicit {
+            // 隐式 TLS 由 suppaftp 自行建立 TCP 连接，无法经过代理
+            let proxy = proxy.cloned().or_else(proxy::get_default);
+            if proxy.is_some_and(|proxy| proxy.proxy_type != ProxyType::Direct) {
+                return Err(SftpError::new(ErrorCode::Unsupported, i18n::text("error.ftp_implicit_proxy")));
+            }
+
+            FtpStream::connect_secure_implicit((host, port), tls_connector()?, host)
+                .map_err(|e| connect_error("action.connect", e))?
+        } else {
+            let tcp = proxy::connect(host, port, proxy, connect_timeout)?;
+            FtpStream::connect_with_stream(tcp)
+                .map_err(|e| connect_error("action.connect", e))?
+        };
+
+        let mut stream = match options.mode {
+            FtpMode::Active => stream.active_mode(connect_timeout),
+            FtpMode::Passive => {
+                let (data_host, proxy) = (host.to_string(), proxy.cloned());
+                stream.passive_stream_builder(move |address| {
+                    proxy::connect(&data_host, address.port(), proxy.as_ref(), connect_timeout)
+                        .map_err(|e| FtpError::ConnectionError(io::Error::other(e.message)))
+                })
+            }
+        };
+
+        if options.tls == FtpTls::Explicit {
+            stream = stream.into_secure(tls_connector()?, host)
+                .map_err(|e| connect_error("action.tls_setup", e))?;
+        }
+
+        let operation_timeout = (timeouts.operation_secs > 0).then(|| Duration::from_secs(timeouts.operation_secs));
+        let control = stream.get_ref();
+        control.set_read_timeout(operation_timeout)
+            .and_then(|_| control.set_write_timeout(operation_timeout))
+            .map_err(|e| SftpError::io("action.connect", e))?;
+
+        stream.login(username, password)
+            .map_err(|e| SftpError::ftp("action.auth", e).with_code(ErrorCode::AuthFailed))?;
+        stream.transfer_type(suppaftp::types::FileType::Binary)
+            .map_err(|e| SftpError::ftp("action.connect", e))?;
+
+        Ok(FtpFs { stream: RefCell::new(stream) })
+    }
+
+    /// 当前工作目录
+    pub fn pwd(&self) -> Result<String, SftpError> {
+        self.stream.borrow_mut().pwd()
+            .map_err(|e| SftpError::ftp("action.current_dir", e))
+    }
+
+    /// 发送 QUIT 并关闭连接
+    pub fn quit(self) -> Result<(), SftpError> {
+        self.stream.into_inner().quit()
+            .map_err(|e| SftpError::ftp("action.disconnect", e))
+    }
+
+    fn list_entries(&self, path: &str) -> Result<Vec<File>, SftpError> {
+        let lines = self.stream.borrow_mut().list(Some(path))
+            .map_err(|e| SftpError::ftp_at("action.read_dir", path, e))?;
+
+        // 无法解析的行（如 "total 12"）直接跳过
+        Ok(lines.iter()
+            .filter_map(|line| File::try_from(line.as_str()).ok())
+            .filter(|file| file.name() != "." && file.name() != "..")
+            .collect())
+    }
+}
+
+// 连接阶段的一般性失败归为 ConnectionFailed，超时保留原分类
+fn connect_error(action: &'static str, e: FtpError) -> SftpError {
+    let err = SftpError::ftp(action, e);
+    match err.code {
+        ErrorCode::Timeout => err,
+        _ => err.with_code(ErrorCode::ConnectionFailed),
+    }
+}
+
+// 由 LIST / ls -l 的一行生成文件信息，SCP 后端解析 ls 输出时也使用
+pub(crate) fn file_info(dir: &str, file: &File) -> FileInfo {
+    let file_type = if file.is_symlink() {
+        FileType::Symlink
+    } else if file.is_directory() {
+        FileType::Dir
+    } else {
+        FileType::File
+    };
+
+    let mut perm = 0;
+    for (who, shift) in [(PosixPexQuery::Owner, 6), (PosixPexQuery::Group, 3), (PosixPexQuery::Others, 0)] {
+        let bits = (file.can_read(who) as u32) << 2
+            | (file.can_write(who) as u32) << 1
+            | file.can_execute(who) as u32;
+        perm |= bits << shift;
+    }
+
+    let modified = file.modified()
+        .duration_since(UNIX_EPOCH)
+        .ok()
+        .map(|duration| duration.as_secs());
+
+    FileInfo {
+        name: file.name().to_string(),
+        path: join_remote_path(dir, file.name()),
+        size: file.size() as u64,
+        is_dir: file.is_directory(),
+        file_type,
+        modified: crate::format_timestamp(modified),
+        accessed: None,
+        permissions: format!("{:o}", perm),
+        mode: crate::format_mode(file_type, perm),
+        uid: file.uid(),
+        gid: file.gid(),
+        is_symlink: file.is_symlink(),
+        link_target: file.symlink().map(|target| target.to_string_lossy().to_string()),
+        target_is_dir: false,
+    }
+}
+
+// 拆分为 (父目录, 名称)
+fn split_path(path: &str) -> (&str, &str) {
+    let trimmed = path.trim_end_matches('/');
+    match trimmed.rsplit_once('/') {
+        Some(("", name)) => ("/", name),
+        Some((parent, name)) => (parent, name),
+        None => (".", trimmed),
+    }
+}
+
+impl RemoteFs for FtpFs {
+    fn list(&self, path: &str) -> Result<Vec<FileInfo>, SftpError> {
+        let path = if path.is_empty() { "/" } else { path };
+        Ok(self.list_entries(path)?
+            .iter()
+            .map(|file| file_info(path, file))
+            .collect())
+    }
+
+    fn stat(&self, path: &str) -> Result<FileInfo, SftpError> {
+        let (parent, name) = split_path(path);
+        if name.is_empty() {
+            let root = FileInfo {
+                name: "/".to_string(),
+                path: "/".to_string(),
+                size: 0,
+                is_dir: true,
+                file_type: FileType::Dir,
+                modified: None,
+                accessed: None,
+                permissions: "755".to_string(),
+                mode: crate::format_mode(FileType::Dir, 0o755),
+                uid: None,
+                gid: None,
+                is_symlink: false,
+                link_target: None,
+                target_is_dir: false,
+            };
+            return Ok(root);
+        }
+
+        self.list_entries(parent)?
+            .iter()
+            .find(|file| file.name() == name)
+            .map(|file| {
+                let mut info = file_info(parent, file);
+                info.path = path.to_string();
+                info
+            })
+            .ok_or_else(|| SftpError::new(ErrorCode::NotFound, i18n::format("error.path_not_found", &[&path])))
+    }
+
+    fn open_read(&self, path: &str) -> Result<Box<dyn Read + '_>, SftpError> {
+        let data = self.stream.borrow_mut().retr_as_stream(path)
+            .map_err(|e| SftpError::ftp_at("action.open_remote_file", path, e))?;
+        Ok(Box::new(FtpReader { fs: self, data: Some(Box::new(data)) }))
+    }
+
+    // REST 设置下一次 RETR 的起始位置
+    fn open_read_from(&self, path: &str, offset: u64) -> Result<Box<dyn Read + '_>, SftpError> {
+        if offset > 0 {
+            self.stream.borrow_mut().resume_transfer(offset as usize)
+                .map_err(|e| SftpError::ftp_at("action.open_remote_file", path, e))?;
+        }
+        self.open_read(path)
+    }
+
+    fn open_write(&self, path: &str) -> Result<Box<dyn Write + '_>, SftpError> {
+        let data = self.stream.borrow_mut().put_with_stream(path)
+            .map_err(|e| SftpError::ftp_at("action.create_remote_file", path, e))?;
+        Ok(Box::new(FtpWriter { fs: self, data: Some(Box::new(data)) }))
+    }
+
+    fn open_append(&self, path: &str) -> Result<Box<dyn Write + '_>, SftpError> {
+        let data = self.stream.borrow_mut().append_with_stream(path)
+            .map_err(|e| SftpError::ftp_at("action.open_remote_file", path, e))?;
+        Ok(Box::new(FtpWriter { fs: self, data: Some(Box::new(data)) }))
+    }
+
+    fn mkdir(&self, path: &str) -> Result<(), SftpError> {
+        self.stream.borrow_mut().mkdir(path)
+            .map_err(|e| SftpError::ftp_at("action.create_dir", path, e))
+    }
+
+    fn remove_file(&self, path: &str) -> Result<(), SftpError> {
+        self.stream.borrow_mut().rm(path)
+            .map_err(|e| SftpError::ftp_at("action.remove_file", path, e))
+    }
+
+    fn remove_dir(&self, path: &str) -> Result<(), SftpError> {
+        self.stream.borrow_mut().rmdir(path)
+            .map_err(|e| SftpError::ftp_at("action.remove_dir", path, e))
+    }
+
+    // 不同服务器对 RNTO 目标已存在的处理不一致，这里统一为与 SFTP 相同的语义；
+    // 先确认源存在，避免源不存在时已经删掉了目标
+    fn rename(&self, source: &str, destination: &str, overwrite: bool) -> Result<(), SftpError> {
+        self.stat(source)?;
+
+        let existing = match self.stat(destination) {
+            Ok(existing) => Some(existing),
+            Err(e) if e.code == ErrorCode::NotFound => None,
+            Err(e) => return Err(e),
+        };
+
+        if let Some(existing) = existing {
+            if !overwrite {
+                return Err(SftpError::new(ErrorCode::AlreadyExists, i18n::format("error.destination_exists", &[&destination])));
+            }
+            if existing.is_dir {
+                self.remove_dir(destination)?;
+            } else {
+                self.remove_file(destination)?;
+            }
+        }
+
+        self.stream.borrow_mut().rename(source, destination)
+            .map_err(|e| SftpError::ftp_at("action.rename", source, e))
+    }
+}
+
+// Read / Write 接口只能返回 io::Error，把 SftpError 包装进去，由 SftpError::io 取回
+fn to_io_error(e: SftpError) -> io::Error {
+    io::Error::other(e)
+}
+
+// 数据连接上的读写失败属于连接问题，不是本地磁盘错误
+fn data_error(action: &'static str, e: io::Error) -> io::Error {
+    let error = SftpError::io(action, e);
+    to_io_error(match error.code {
+        ErrorCode::Timeout => error,
+        _ => error.with_code(ErrorCode::ConnectionLost),
+    })
+}
+
+// 下载数据流；读到末尾时读取服务器的传输完成应答
+struct FtpReader<'a> {
+    fs: &'a FtpFs,
+    data: Option<Box<dyn Read>>,
+}
+
+impl FtpReader<'_> {
+    fn finish(&mut self) -> Result<(), SftpError> {
+        match self.data.take() {
+            Some(data) => self.fs.stream.borrow_mut().finalize_retr_stream(data)
+                .map_err(|e| SftpError::ftp("action.read_remote_file", e)),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Read for FtpReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let Some(data) = self.data.as_mut() else {
+            return Ok(0);
+        };
+
+        let n = match data.read(buf) {
+            Ok(n) => n,
+            // 服务器中止传输时会关闭数据连接，控制连接上的应答说明了原因
+            Err(e) => {
+                let error = data_error("action.read_remote_file", e);
+                self.finish().map_err(to_io_error)?;
+                return Err(error);
+            }
+        };
+        if n == 0 && !buf.is_empty() {
+            self.finish().map_err(to_io_error)?;
+        }
+        Ok(n)
+    }
+}
+
+// 提前结束（如取消）时服务器通常返回 426，这里只需要把应答读掉
+impl Drop for FtpReader<'_> {
+    fn drop(&mut self) {
+        if let Err(e) = self.finish() {
+            tracing::debug!("结束 FTP 下载: {}", e);
+        }
+    }
+}
+
+// 上传数据流；flush 时关闭数据连接并确认服务器已保存文件，之后不能再写入
+struct FtpWriter<'a> {
+    fs: &'a FtpFs,
+    data: Option<Box<dyn Write>>,
+}
+
+impl FtpWriter<'_> {
+    fn finish(&mut self) -> Result<(), SftpError> {
+        match self.data.take() {
+            Some(data) => self.fs.stream.borrow_mut().finalize_put_stream(data)
+                .map_err(|e| SftpError::ftp("action.write_remote_file", e)),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Write for FtpWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let Some(data) = self.data.as_mut() else {
+            return Err(io::Error::new(io::ErrorKind::BrokenPipe, i18n::text("error.stream_closed")));
+        };
+
+        match data.write(buf) {
+            Ok(n) => Ok(n),
+            // 服务器拒绝上传时会关闭数据连接，控制连接上的应答说明了原因
+            Err(e) => {
+                let error = data_error("action.write_remote_file", e);
+                self.finish().map_err(to_io_error)?;
+                Err(error)
+            }
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if let Some(data) = self.data.as_mut() {
+            data.flush()
+                .map_err(|e| data_error("action.write_remote_file", e))?;
+        }
+        self.finish().map_err(to_io_error)
+    }
+}
+
+impl Drop for FtpWriter<'_> {
+    fn drop(&mut self) {
+        if let Err(e) = self.finish() {
+            tracing::debug!("结束 FTP 上传: {}", e);
+        }
+    }
+}
@@ -0,0 +1,207 @@
+// 命令返回给前端的文本（成功提示和错误信息）的多语言目录
+// 文本按 key 登记，{} 为按顺序替换的参数；日志输出不经过这里
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Locale {
+    Zh,
+    En,
+}
+
+static LOCALE: AtomicU8 = AtomicU8::new(Locale::Zh as u8);
+
+pub fn set_locale(locale: Locale) {
+    LOCALE.store(locale as u8, Ordering::SeqCst);
+}
+
+pub fn locale() -> Locale {
+    if LOCALE.load(Ordering::SeqCst) == Locale::En as u8 {
+        Locale::En
+    } else {
+        Locale::Zh
+    }
+}
+
+// (key, 中文, English)
+const MESSAGES: &[(&str, &str, &str)] = &[
+    // 操作名称，与 error.failed / error.timed_out 组合成错误信息
+    ("action.auth", "认证", "Authentication"),
+    ("action.check_host_key", "校验主机密钥", "Check host key"),
+    ("action.close_channel", "关闭通道", "Close channel"),
+    ("action.connect", "连接", "Connect"),
+    ("action.connect_proxy", "连接代理服务器", "Connect to proxy"),
+    ("action.copy", "复制", "Copy"),
+    ("action.create_destination", "创建目标文件", "Create destination file"),
+    ("action.create_dir", "创建目录", "Create directory"),
+    ("action.create_downloads_dir", "创建下载目录", "Create downloads directory"),
+    ("action.create_local_dir", "创建本地目录", "Create local directory"),
+    ("action.create_local_file", "创建本地文件", "Create local file"),
+    ("action.create_log_dir", "创建日志目录", "Create log directory"),
+    ("action.create_remote_file", "创建远程文件", "Create remote file"),
+    ("action.create_session", "创建会话", "Create session"),
+    ("action.create_sftp", "创建 SFTP 会话", "Create SFTP session"),
+    ("action.create_symlink", "创建符号链接", "Create symlink"),
+    ("action.current_dir", "获取当前目录", "Get current directory"),
+    ("action.disconnect", "断开连接", "Disconnect"),
+    ("action.exec_command", "执行命令", "Execute command"),
+    ("action.exit_status", "获取退出状态", "Get exit status"),
+    ("action.export_logs", "导出日志", "Export logs"),
+    ("action.handshake", "握手", "Handshake"),
+    ("action.http_proxy", "HTTP 代理通信", "HTTP proxy communication"),
+    ("action.listen_local", "监听本地端口", "Listen on local port"),
+    ("action.listen_remote", "远程端口监听", "Listen on remote port"),
+    ("action.open_channel", "打开通道", "Open channel"),
+    ("action.open_folder", "打开文件夹", "Open folder"),
+    ("action.open_local_file", "打开本地文件", "Open local file"),
+    ("action.open_remote_file", "打开远程文件", "Open remote file"),
+    ("action.open_source", "打开源文件", "Open source file"),
+    ("action.proxy_timeout", "设置代理超时", "Set proxy timeout"),
+    ("action.read_command_output", "读取命令输出", "Read command output"),
+    ("action.read_dir", "读取目录", "Read directory"),
+    ("action.read_local_file", "读取本地文件", "Read local file"),
+    ("action.read_logs", "读取日志", "Read logs"),
+    ("action.read_remote_file", "读取远程文件", "Read remote file"),
+    ("action.read_sftp_response", "读取 SFTP 响应", "Read SFTP response"),
+    ("action.read_source", "读取源文件", "Read source file"),
+    ("action.read_symlink", "读取符号链接", "Read symlink"),
+    ("action.remove_dir", "删除目录", "Delete directory"),
+    ("action.remove_file", "删除文件", "Delete file"),
+    ("action.rename", "重命名", "Rename"),
+    ("action.replace_dir", "替换目标目录", "Replace destination directory"),
+    ("action.replace_file", "替换目标文件", "Replace destination file"),
+    ("action.request_pty", "请求终端", "Request terminal"),
+    ("action.send_sftp_request", "发送 SFTP 请求", "Send SFTP request"),
+    ("action.set_attributes", "修改文件属性", "Set file attributes"),
+    ("action.set_modified", "设置修改时间", "Set modification time"),
+    ("action.socks5_proxy", "SOCKS5 代理通信", "SOCKS5 proxy communication"),
+    ("action.source_stat", "获取源文件信息", "Get source file info"),
+    ("action.start_sftp_subsystem", "启动 SFTP 子系统", "Start SFTP subsystem"),
+    ("action.start_shell", "启动 shell", "Start shell"),
+    ("action.stat", "获取文件信息", "Get file info"),
+    ("action.tls_setup", "建立 TLS 加密", "TLS setup"),
+    ("action.transfer", "文件传输", "File transfer"),
+    ("action.write_destination", "写入目标文件", "Write destination file"),
+    ("action.write_file_data", "写入文件数据", "Write file data"),
+    ("action.write_local_file", "写入本地文件", "Write local file"),
+    ("action.write_remote_file", "写入远程文件", "Write remote file"),
+
+    // 错误信息
+    ("error.append_unsupported", "该连接不支持续传写入", "This connection does not support appending to files"),
+    ("error.auth_failed", "认证失败", "Authentication failed"),
+    ("error.cancelled", "传输已取消", "Transfer cancelled"),
+    ("error.connection_lost", "连接已断开", "Connection lost"),
+    ("error.copy_data_timeout", "服务器端复制超时", "Server-side copy timed out"),
+    ("error.decode_file_data", "解码文件数据失败: {}", "Failed to decode file data: {}"),
+    ("error.destination_exists", "目标已存在: {}", "Destination already exists: {}"),
+    ("error.dir_not_empty", "目录不为空: {}", "Directory is not empty: {}"),
+    ("error.exec_timeout", "命令执行超时", "Command timed out"),
+    ("error.failed", "{}失败: {}", "{} failed: {}"),
+    ("error.forward_not_found", "转发不存在: {}", "Port forward not found: {}"),
+    ("error.ftp_copy_unsupported", "FTP 连接不支持在服务器上复制文件", "FTP connections do not support copying files on the server"),
+    ("error.ftp_same_connection", "FTP 连接不能同时作为源和目标", "An FTP connection cannot be both the source and the destination"),
+    ("error.ftp_implicit_proxy", "隐式 FTPS 不支持通过代理连接", "Implicit FTPS cannot be used through a proxy"),
+    ("error.host_key_mismatch", "主机 {} 的密钥与 {} 中的记录不一致，可能存在中间人攻击", "Host key for {} does not match the entry in {}; the connection may be intercepted"),
+    ("error.host_too_long", "目标主机名过长", "Target host name is too long"),
+    ("error.http_proxy_header_too_long", "HTTP 代理响应头过长", "HTTP proxy response header is too long"),
+    ("error.http_proxy_rejected", "HTTP 代理拒绝连接: {}", "HTTP proxy refused the connection: {}"),
+    ("error.invalid_glob", "无效的排除规则 {}: {}", "Invalid exclude pattern {}: {}"),
+    ("error.invalid_url", "无效的服务器地址: {}", "Invalid server address: {}"),
+    ("error.invalid_proxy_address", "无效的代理地址: {}", "Invalid proxy address: {}"),
+    ("error.invalid_source", "无效的源路径", "Invalid source path"),
+    ("error.is_directory", "路径是目录: {}", "Path is a directory: {}"),
+    ("error.logging_init", "初始化日志失败: {}", "Failed to initialize logging: {}"),
+    ("error.logging_unavailable", "日志未启用", "Logging is not available"),
+    ("error.missing_argument", "缺少参数 {}", "Missing argument {}"),
+    ("error.not_connected", "连接不存在: {}", "Connection not found: {}"),
+    ("error.not_directory", "路径不是目录: {}", "Path is not a directory: {}"),
+    ("error.path_not_found", "路径不存在: {}", "Path not found: {}"),
+    ("error.reserved_connection_id", "连接 ID {} 为本地文件系统保留", "Connection id {} is reserved for the local file system"),
+    ("error.resolve_host", "无法解析主机地址", "Unable to resolve host address"),
+    ("error.scp_size_required", "SCP 上传需要预先知道文件大小", "SCP uploads require the file size in advance"),
+    ("error.set_attributes_unsupported", "该连接不支持修改文件属性", "This connection does not support changing file attributes"),
+    ("error.set_modified_unsupported", "该连接不支持设置修改时间", "This connection does not support setting modification times"),
+    ("error.sftp_bad_length", "SFTP 响应长度无效", "Invalid SFTP response length"),
+    ("error.sftp_bad_reply", "SFTP 响应类型无效", "Unexpected SFTP response type"),
+    ("error.sftp_bad_version", "SFTP 握手响应无效", "Invalid SFTP version response"),
+    ("error.sftp_id_mismatch", "SFTP 响应序号不匹配", "SFTP response id mismatch"),
+    ("error.sftp_status", "SFTP 错误 {}: {}", "SFTP error {}: {}"),
+    ("error.sftp_truncated", "SFTP 数据包被截断", "Truncated SFTP packet"),
+    ("error.shell_copy_failed", "远程复制失败: {}", "Remote copy failed: {}"),
+    ("error.shell_copy_no_output", "远程复制未生成目标文件", "Remote copy did not create the destination"),
+    ("error.socks5_auth_failed", "SOCKS5 代理认证失败", "SOCKS5 proxy authentication failed"),
+    ("error.socks5_bad_reply", "SOCKS5 代理响应无效", "Invalid SOCKS5 proxy response"),
+    ("error.socks5_credentials_too_long", "SOCKS5 用户名或密码过长", "SOCKS5 username or password is too long"),
+    ("error.socks5_no_auth_method", "SOCKS5 代理不支持所需的认证方式", "SOCKS5 proxy does not support the required authentication method"),
+    ("error.socks5_rejected", "SOCKS5 代理拒绝连接，错误码: {}", "SOCKS5 proxy refused the connection, code: {}"),
+    ("error.stream_closed", "数据流已关闭", "Data stream already closed"),
+    ("error.symlink_unsupported", "该连接不支持符号链接", "This connection does not support symbolic links"),
+    ("error.task", "任务执行失败: {}", "Task failed: {}"),
+    ("error.terminal_closed", "终端已关闭: {}", "Terminal already closed: {}"),
+    ("error.terminal_not_found", "终端不存在: {}", "Terminal not found: {}"),
+    ("error.timed_out", "{}超时: {}", "{} timed out: {}"),
+    ("error.transfer_not_found", "传输任务 {} 不存在或已完成", "Transfer {} does not exist or has already finished"),
+    ("error.unsupported_os", "不支持的操作系统", "Unsupported operating system"),
+
+    // 成功提示
+    ("msg.connect_ok", "连接成功", "Connected successfully"),
+    ("msg.connection_active", "连接活跃，当前目录: {}", "Connection active, current directory: {}"),
+    ("msg.connection_active_no_cwd", "连接活跃，但无法获取当前目录: {}", "Connection active, but the current directory is unavailable: {}"),
+    ("msg.connection_active_scp", "连接活跃（SCP 兼容模式）", "Connection active (SCP compatibility mode)"),
+    ("msg.copy_done", "复制完成（{}），共 {} 字节", "Copy complete ({}), {} bytes"),
+    ("msg.default_proxy_updated", "默认代理已更新", "Default proxy updated"),
+    ("msg.deleted", "删除成功", "Deleted successfully"),
+    ("msg.dir_created", "目录创建成功", "Directory created"),
+    ("msg.disconnected", "连接已断开", "Disconnected"),
+    ("msg.download_done", "下载完成，文件大小: {} 字节", "Download complete, {} bytes"),
+    ("msg.drive_name", "{}盘", "Drive {}"),
+    ("msg.file_uploaded", "文件 {} 上传完成", "File {} uploaded"),
+    ("msg.folder_opened", "已打开文件夹: {}", "Opened folder: {}"),
+    ("msg.forward_stopped", "端口转发已关闭", "Port forward stopped"),
+    ("msg.locale_updated", "语言已切换", "Language updated"),
+    ("msg.logs_exported", "日志已导出到: {}", "Logs exported to: {}"),
+    ("msg.renamed", "重命名成功", "Renamed successfully"),
+    ("msg.symlink_created", "符号链接创建成功", "Symlink created"),
+    ("msg.sync_done", "同步完成：上传 {} 个，下载 {} 个，删除 {} 个，冲突 {} 个", "Sync complete: {} uploaded, {} downloaded, {} deleted, {} conflicts"),
+    ("msg.terminal_closed", "终端已关闭", "Terminal closed"),
+    ("msg.transfer_cancelled", "传输任务 {} 已取消", "Transfer {} cancelled"),
+    ("msg.transfer_done", "传输完成，共 {} 字节", "Transfer complete, {} bytes"),
+    ("msg.upload_done", "上传完成", "Upload complete"),
+];
+
+static CATALOG: std::sync::LazyLock<HashMap<&'static str, (&'static str, &'static str)>> =
+    std::sync::LazyLock::new(|| {
+        MESSAGES.iter()
+            .map(|&(key, zh, en)| (key, (zh, en)))
+            .collect()
+    });
+
+/// 当前语言下的文本，未登记的 key 原样返回
+pub fn text(key: &'static str) -> &'static str {
+    match CATALOG.get(key) {
+        Some(&(zh, en)) => match locale() {
+            Locale::Zh => zh,
+            Locale::En => en,
+        },
+        None => key,
+    }
+}
+
+/// 按顺序用参数替换文本中的 {}
+pub fn format(key: &'static str, args: &[&dyn Display]) -> String {
+    let mut parts = text(key).split("{}");
+    let mut result = parts.next().unwrap_or_default().to_string();
+
+    for (index, part) in parts.enumerate() {
+        if let Some(arg) = args.get(index) {
+            result.push_str(&arg.to_string());
+        }
+        result.push_str(part);
+    }
+
+    result
+}
@@ -1,14 +1,82 @@
-use std::collections::HashMap;
-use std::path::Path;
-use std::sync::{Mutex, Arc};
-use std::sync::atomic::AtomicBool;
+//! SFTP 客户端后端
+//!
+//! 除了桌面端的 `run`（需要默认启用的 `gui` 特性）之外，也可以作为普通的 Rust 库使用，
+//! 此时可以用 `default-features = false` 去掉 tauri 依赖。
+//! [`SftpClient`] 提供列目录、上传下载和常用文件操作，进度通过 [`ProgressListener`] 回调报告。
+//!
+//! ```no_run
+//! use sftp_web_lib::{ConnectOptions, SftpClient};
+//!
+//! let client = SftpClient::connect(&ConnectOptions::new("example.com", "user", "password"))?;
+//! for file in client.list_dir("/home/user")? {
+//!     println!("{} {}", file.mode, file.name);
+//! }
+//! client.download("/home/user/data.bin", "data.bin", &mut |done, total| {
+//!     eprintln!("{}/{}", done, total);
+//! })?;
+//! # Ok::<(), sftp_web_lib::SftpError>(())
+//! ```
+
 use serde::{Deserialize, Serialize};
-use ssh2::Session;
-use std::net::TcpStream;
-use std::io::{Write, Read};
 use std::fs;
-use base64::{Engine as _, engine::general_purpose};
-use tauri::Emitter;
+use std::path::Path;
+
+mod client;
+#[cfg(feature = "gui")]
+mod commands;
+mod error;
+mod exec;
+#[cfg(feature = "gui")]
+mod forward;
+mod ftp_fs;
+pub mod i18n;
+mod local_fs;
+#[cfg(feature = "gui")]
+mod logging;
+pub mod ops;
+mod proxy;
+#[cfg(feature = "gui")]
+mod remote_copy;
+mod remote_fs;
+mod s3_fs;
+mod scp_fs;
+mod sftp_ext;
+mod sftp_fs;
+#[cfg(feature = "gui")]
+mod socks;
+pub mod sync;
+#[cfg(feature = "gui")]
+mod terminal;
+mod webdav_fs;
+
+#[cfg(feature = "gui")]
+pub use commands::run;
+
+pub use client::{ConnectOptions, SftpClient};
+pub use error::{ErrorCode, SftpError};
+pub use ftp_fs::{FtpFs, FtpMode, FtpOptions, FtpTls};
+pub use i18n::Locale;
+pub use local_fs::LocalFs;
+pub use ops::{NoProgress, PrivateKey, ProgressListener};
+pub use proxy::{ProxyConfig, ProxyType};
+pub use remote_fs::RemoteFs;
+pub use s3_fs::{S3Fs, S3Options};
+pub use scp_fs::ScpFs;
+pub use sftp_fs::SftpFs;
+pub use sync::{SyncAction, SyncDirection, SyncOptions, SyncPlan};
+pub use webdav_fs::{WebDavFs, WebDavOptions};
+
+// 连接使用的协议
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Protocol {
+    #[default]
+    Sftp,
+    Ftp,
+    #[serde(rename = "webdav")]
+    WebDav,
+    S3,
+}
 
 // SFTP 连接信息
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -20,656 +88,339 @@ pub struct SftpConnectionInfo {
     pub username: String,
     pub password: String,
     pub connected: bool,
+    #[serde(default)]
+    pub protocol: Protocol,
+    // 仅 FTP 连接使用
+    #[serde(default)]
+    pub ftp: FtpOptions,
+    // 仅 WebDAV 连接使用
+    #[serde(default)]
+    pub webdav: WebDavOptions,
+    // 仅 S3 连接使用，用户名和密码为访问密钥和私有密钥
+    #[serde(default)]
+    pub s3: S3Options,
+    // 未设置时使用全局默认代理
+    #[serde(default)]
+    pub proxy: Option<proxy::ProxyConfig>,
+    #[serde(default)]
+    pub timeouts: TimeoutConfig,
 }
 
-// 文件信息
+// 超时设置（秒），operation_secs 为 0 表示单次操作不限时
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct FileInfo {
-    pub name: String,
-    pub path: String,
-    pub size: u64,
-    pub is_dir: bool,
-    pub modified: Option<String>,
-    pub permissions: String,
+#[serde(default)]
+pub struct TimeoutConfig {
+    pub connect_secs: u64,
+    pub handshake_secs: u64,
+    pub operation_secs: u64,
 }
 
-// 传输进度信息
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct TransferProgress {
-    pub id: String,
-    pub filename: String,
-    pub total_size: u64,
-    pub transferred: u64,
-    pub speed: f64,
-    pub status: String,
-}
-
-// 全局连接管理器
-type ConnectionManager = Mutex<HashMap<String, Session>>;
-static CONNECTIONS: std::sync::LazyLock<ConnectionManager> =
-    std::sync::LazyLock::new(|| Mutex::new(HashMap::new()));
-
-// 存储活跃的传输任务
-type TransferTasks = Mutex<HashMap<String, Arc<AtomicBool>>>;
-static TRANSFER_TASKS: std::sync::LazyLock<TransferTasks> = 
-    std::sync::LazyLock::new(|| Mutex::new(HashMap::new()));
-
-// 测试连接命令
-#[tauri::command]
-async fn test_sftp_connection(
-    host: String,
-    port: u16,
-    username: String,
-    password: String,
-) -> Result<String, String> {
-    tokio::task::spawn_blocking(move || {
-        let tcp = TcpStream::connect(format!("{}:{}", host, port))
-            .map_err(|e| format!("连接失败: {}", e))?;
-
-        let mut session = Session::new()
-            .map_err(|e| format!("创建会话失败: {}", e))?;
-
-        session.set_tcp_stream(tcp);
-        session.handshake()
-            .map_err(|e| format!("握手失败: {}", e))?;
-
-        session.userauth_password(&username, &password)
-            .map_err(|e| format!("认证失败: {}", e))?;
-
-        if !session.authenticated() {
-            return Err("认证失败".to_string());
+impl Default for TimeoutConfig {
+    fn default() -> Self {
+        TimeoutConfig {
+            connect_secs: 10,
+            handshake_secs: 15,
+            operation_secs: 60,
         }
-
-        Ok("连接成功".to_string())
-    }).await.map_err(|e| format!("任务执行失败: {}", e))?
+    }
 }
 
-// 建立 SFTP 连接
-#[tauri::command]
-async fn connect_sftp(
-    connection_info: SftpConnectionInfo,
-) -> Result<String, String> {
-    let connection_id = connection_info.id.clone();
-
-    tokio::task::spawn_blocking(move || {
-        let tcp = TcpStream::connect(format!("{}:{}", connection_info.host, connection_info.port))
-            .map_err(|e| format!("连接失败: {}", e))?;
-
-        let mut session = Session::new()
-            .map_err(|e| format!("创建会话失败: {}", e))?;
-
-        session.set_tcp_stream(tcp);
-        session.handshake()
-            .map_err(|e| format!("握手失败: {}", e))?;
-
-        session.userauth_password(&connection_info.username, &connection_info.password)
-            .map_err(|e| format!("认证失败: {}", e))?;
-
-        if !session.authenticated() {
-            return Err("认证失败".to_string());
-        }
+impl TimeoutConfig {
+    fn connect_timeout(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.connect_secs.max(1))
+    }
 
-        // 存储连接
-        let mut connections = CONNECTIONS.lock().unwrap();
-        connections.insert(connection_id.clone(), session);
+    // Session::set_timeout 以毫秒为单位，0 表示不限时
+    fn handshake_millis(&self) -> u32 {
+        self.handshake_secs.saturating_mul(1000).min(u32::MAX as u64) as u32
+    }
 
-        Ok(connection_id)
-    }).await.map_err(|e| format!("任务执行失败: {}", e))?
+    fn operation_millis(&self) -> u32 {
+        self.operation_secs.saturating_mul(1000).min(u32::MAX as u64) as u32
+    }
 }
 
-// 列出目录内容
-#[tauri::command]
-async fn list_directory(
-    connection_id: String,
-    path: String,
-) -> Result<Vec<FileInfo>, String> {
-    println!("=== 开始列出目录 ===");
-    println!("请求路径: '{}' (连接ID: {})", path, connection_id);
-
-    tokio::task::spawn_blocking(move || {
-        let connections = CONNECTIONS.lock().unwrap();
-        println!("当前活跃连接数: {}", connections.len());
-
-        let session = connections.get(&connection_id)
-            .ok_or_else(|| {
-                println!("错误: 连接不存在，连接ID: {}", connection_id);
-                println!("可用连接ID: {:?}", connections.keys().collect::<Vec<_>>());
-                format!("连接不存在: {}", connection_id)
-            })?;
-
-        println!("会话已找到，创建 SFTP 连接...");
-        let sftp = session.sftp()
-            .map_err(|e| {
-                println!("创建 SFTP 会话失败: {}", e);
-                format!("创建 SFTP 会话失败: {}", e)
-            })?;
-
-        // 验证路径格式
-        let normalized_path = if path.is_empty() || path == "/" {
-            "/"
-        } else {
-            &path
-        };
-
-        println!("标准化路径: '{}' -> '{}'", path, normalized_path);
+// 文件类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FileType {
+    File,
+    Dir,
+    Symlink,
+    Socket,
+    Fifo,
+    Char,
+    Block,
+    Other,
+}
 
-        // 尝试获取当前工作目录
-        match sftp.realpath(Path::new(".")) {
-            Ok(real_path) => println!("当前工作目录: {}", real_path.display()),
-            Err(e) => println!("无法获取当前工作目录: {}", e),
+impl FileType {
+    fn from_stat(stat: &ssh2::FileStat) -> Self {
+        match stat.file_type() {
+            ssh2::FileType::RegularFile => FileType::File,
+            ssh2::FileType::Directory => FileType::Dir,
+            ssh2::FileType::Symlink => FileType::Symlink,
+            ssh2::FileType::Socket => FileType::Socket,
+            ssh2::FileType::NamedPipe => FileType::Fifo,
+            ssh2::FileType::CharDevice => FileType::Char,
+            ssh2::FileType::BlockDevice => FileType::Block,
+            ssh2::FileType::Other(_) => FileType::Other,
         }
+    }
 
-        println!("尝试读取目录: '{}'", normalized_path);
-        let entries = sftp.readdir(Path::new(normalized_path))
-            .map_err(|e| {
-                let error_msg = format!("读取目录失败 [{}]: {}", normalized_path, e);
-                println!("{}", error_msg);
-
-                // 尝试列出根目录作为备选
-                if normalized_path != "/" {
-                    println!("尝试读取根目录作为备选...");
-                    match sftp.readdir(Path::new("/")) {
-                        Ok(root_entries) => {
-                            println!("根目录包含 {} 个条目", root_entries.len());
-                        }
-                        Err(root_err) => {
-                            println!("根目录也无法读取: {}", root_err);
-                        }
-                    }
-                }
-
-                error_msg
-            })?;
-
-        println!("原始条目数量: {}", entries.len());
-
-        let mut files = Vec::new();
-        for (index, (entry_path, stat)) in entries.iter().enumerate() {
-            println!("处理条目 {}: {:?}", index + 1, entry_path);
-
-            // 获取文件名，支持 Windows 路径格式
-            let name = if let Some(file_name) = entry_path.file_name().and_then(|n| n.to_str()) {
-                println!("  标准文件名: '{}'", file_name);
-                file_name.to_string()
-            } else {
-                // 处理 Windows 盘符或特殊路径
-                let path_str = entry_path.to_string_lossy();
-                println!("  原始路径: '{}'", path_str);
-
-                // 检查是否是 Windows 盘符 (如 "C:", "D:" 等)
-                if path_str.len() == 2 && path_str.ends_with(':') {
-                    let drive_name = format!("{}盘", &path_str[0..1]);
-                    println!("  识别为 Windows 盘符: '{}' -> '{}'", path_str, drive_name);
-                    drive_name
-                } else if path_str.contains('\\') {
-                    // Windows 路径，提取最后一部分
-                    let parts: Vec<&str> = path_str.split('\\').collect();
-                    let last_part = parts.last().map(|s| s.to_string()).unwrap_or_else(|| path_str.to_string());
-                    println!("  Windows 路径，提取: '{}'", last_part);
-                    if last_part.is_empty() {
-                        path_str.to_string()
-                    } else {
-                        last_part
-                    }
-                } else {
-                    // 使用完整路径作为名称
-                    println!("  使用完整路径作为名称: '{}'", path_str);
-                    path_str.to_string()
-                }
-            };
-
-            let file_info = FileInfo {
-                name: name.clone(),
-                path: entry_path.to_string_lossy().to_string(),
-                size: stat.size.unwrap_or(0),
-                is_dir: stat.is_dir(),
-                modified: None, // 可以后续添加时间解析
-                permissions: format!("{:o}", stat.perm.unwrap_or(0)),
-            };
-
-            println!("  -> 添加文件: {} (路径: {}, 目录: {}, 大小: {})",
-                file_info.name, file_info.path, file_info.is_dir, file_info.size);
-            files.push(file_info);
+    fn from_metadata(metadata: &fs::Metadata) -> Self {
+        let file_type = metadata.file_type();
+        if file_type.is_symlink() {
+            return FileType::Symlink;
         }
-
-        println!("=== 目录读取完成 ===");
-        println!("最终文件数量: {} (原始: {})", files.len(), entries.len());
-
-        if files.is_empty() {
-            println!("警告: 目录为空！可能的原因:");
-            println!("1. 目录确实为空");
-            println!("2. 权限不足");
-            println!("3. 路径不正确");
-            println!("4. 文件名编码问题");
+        if file_type.is_dir() {
+            return FileType::Dir;
         }
-
-        Ok(files)
-    }).await.map_err(|e| format!("任务执行失败: {}", e))?
-}
-
-// 下载文件（带进度更新）
-#[tauri::command]
-async fn download_file_with_progress(
-    app_handle: tauri::AppHandle,
-    connection_id: String,
-    remote_path: String,
-    local_path: String,
-    transfer_id: String,
-) -> Result<String, String> {
-    println!("开始下载文件: {} -> {}", remote_path, local_path);
-
-    // 创建取消标志
-    let cancel_flag = Arc::new(AtomicBool::new(false));
-    {
-        let mut tasks = TRANSFER_TASKS.lock().unwrap();
-        tasks.insert(transfer_id.clone(), cancel_flag.clone());
-    }
-
-    tokio::task::spawn_blocking(move || {
-        let connections = CONNECTIONS.lock().unwrap();
-        let session = connections.get(&connection_id)
-            .ok_or("连接不存在")?;
-
-        let sftp = session.sftp()
-            .map_err(|e| format!("创建 SFTP 会话失败: {}", e))?;
-
-        // 确保本地目录存在
-        let local_path_obj = Path::new(&local_path);
-        if let Some(parent_dir) = local_path_obj.parent() {
-            println!("创建目录: {:?}", parent_dir);
-            fs::create_dir_all(parent_dir)
-                .map_err(|e| format!("创建本地目录失败: {}", e))?;
+        if file_type.is_file() {
+            return FileType::File;
         }
 
-        println!("打开远程文件: {}", remote_path);
-        let mut remote_file = sftp.open(Path::new(&remote_path))
-            .map_err(|e| format!("打开远程文件失败: {}", e))?;
-
-        // 获取文件大小
-        let file_stat = sftp.stat(Path::new(&remote_path))
-            .map_err(|e| format!("获取文件信息失败: {}", e))?;
-        let total_size = file_stat.size.unwrap_or(0);
-
-        println!("创建本地文件: {}", local_path);
-        let mut local_file = std::fs::File::create(&local_path)
-            .map_err(|e| format!("创建本地文件失败: {}", e))?;
-
-        println!("开始传输文件，总大小: {} 字节", total_size);
-
-        // 分块传输并更新进度
-        let mut buffer = [0u8; 8192]; // 8KB 缓冲区
-        let mut bytes_copied = 0u64;
-        let mut last_progress_update = std::time::Instant::now();
-
-        loop {
-            // 检查是否取消
-            if cancel_flag.load(std::sync::atomic::Ordering::SeqCst) {
-                // 计算当前进度
-                let progress = if total_size > 0 {
-                    (bytes_copied as f64 / total_size as f64 * 100.0) as u32
-                } else {
-                    0
-                };
-
-                // 发送取消事件
-                let _ = app_handle.emit("download_progress", serde_json::json!({
-                    "transfer_id": transfer_id,
-                    "bytes_copied": bytes_copied,
-                    "total_size": total_size,
-                    "progress": progress,
-                    "cancelled": true
-                }));
-                return Err("传输已取消".to_string());
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::FileTypeExt;
+            if file_type.is_socket() {
+                return FileType::Socket;
+            }
+            if file_type.is_fifo() {
+                return FileType::Fifo;
             }
-            
-            match remote_file.read(&mut buffer) {
-                Ok(0) => break, // EOF
-                Ok(n) => {
-                    local_file.write_all(&buffer[..n])
-                        .map_err(|e| format!("写入本地文件失败: {}", e))?;
-
-                    bytes_copied += n as u64;
-
-                    // 每100ms或每1MB更新一次进度
-                    if last_progress_update.elapsed().as_millis() >= 100 || bytes_copied % (1024 * 1024) == 0 {
-                        let progress = if total_size > 0 {
-                            (bytes_copied as f64 / total_size as f64 * 100.0) as u32
-                        } else {
-                            0
-                        };
-
-                        // 发送进度更新事件
-                        let _ = app_handle.emit("download_progress", serde_json::json!({
-                            "transfer_id": transfer_id,
-                            "bytes_copied": bytes_copied,
-                            "total_size": total_size,
-                            "progress": progress
-                        }));
-
-                        last_progress_update = std::time::Instant::now();
-                        println!("下载进度: {}/{} 字节 ({}%)", bytes_copied, total_size, progress);
-                    }
-                }
-                Err(e) => return Err(format!("读取远程文件失败: {}", e)),
+            if file_type.is_char_device() {
+                return FileType::Char;
+            }
+            if file_type.is_block_device() {
+                return FileType::Block;
             }
         }
 
-        println!("文件传输完成，传输字节数: {}", bytes_copied);
-
-        // 发送完成事件
-        let _ = app_handle.emit("download_progress", serde_json::json!({
-            "transfer_id": transfer_id,
-            "bytes_copied": bytes_copied,
-            "total_size": total_size,
-            "progress": 100,
-            "completed": true
-        }));
-
-        Ok(format!("下载完成，文件大小: {} 字节", bytes_copied))
-    }).await.map_err(|e| format!("任务执行失败: {}", e))?
-}
+        FileType::Other
+    }
 
-// 保留原有的下载函数作为备用
-#[tauri::command]
-async fn download_file(
-    connection_id: String,
-    remote_path: String,
-    local_path: String,
-) -> Result<String, String> {
-    println!("开始下载文件: {} -> {}", remote_path, local_path);
-
-    tokio::task::spawn_blocking(move || {
-        let connections = CONNECTIONS.lock().unwrap();
-        let session = connections.get(&connection_id)
-            .ok_or("连接不存在")?;
-
-        let sftp = session.sftp()
-            .map_err(|e| format!("创建 SFTP 会话失败: {}", e))?;
-
-        // 确保本地目录存在
-        let local_path_obj = Path::new(&local_path);
-        if let Some(parent_dir) = local_path_obj.parent() {
-            println!("创建目录: {:?}", parent_dir);
-            fs::create_dir_all(parent_dir)
-                .map_err(|e| format!("创建本地目录失败: {}", e))?;
+    // ls -l 中的类型字符
+    fn mode_char(self) -> char {
+        match self {
+            FileType::File => '-',
+            FileType::Dir => 'd',
+            FileType::Symlink => 'l',
+            FileType::Socket => 's',
+            FileType::Fifo => 'p',
+            FileType::Char => 'c',
+            FileType::Block => 'b',
+            FileType::Other => '?',
         }
+    }
+}
 
-        println!("打开远程文件: {}", remote_path);
-        let mut remote_file = sftp.open(Path::new(&remote_path))
-            .map_err(|e| format!("打开远程文件失败: {}", e))?;
-
-        println!("创建本地文件: {}", local_path);
-        let mut local_file = std::fs::File::create(&local_path)
-            .map_err(|e| format!("创建本地文件失败: {}", e))?;
-
-        println!("开始传输文件...");
-        let bytes_copied = std::io::copy(&mut remote_file, &mut local_file)
-            .map_err(|e| format!("文件传输失败: {}", e))?;
-
-        println!("文件传输完成，传输字节数: {}", bytes_copied);
-        Ok(format!("下载完成，文件大小: {} 字节", bytes_copied))
-    }).await.map_err(|e| format!("任务执行失败: {}", e))?
+// 文件信息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileInfo {
+    pub name: String,
+    pub path: String,
+    pub size: u64,
+    pub is_dir: bool,
+    pub file_type: FileType,
+    pub modified: Option<String>,
+    pub accessed: Option<String>,
+    pub permissions: String,
+    pub mode: String,
+    pub uid: Option<u32>,
+    pub gid: Option<u32>,
+    pub is_symlink: bool,
+    pub link_target: Option<String>,
+    pub target_is_dir: bool,
 }
 
-// 上传文件
-#[tauri::command]
-async fn upload_file(
-    connection_id: String,
-    local_path: String,
-    remote_path: String,
-) -> Result<String, String> {
-    tokio::task::spawn_blocking(move || {
-        let connections = CONNECTIONS.lock().unwrap();
-        let session = connections.get(&connection_id)
-            .ok_or("连接不存在")?;
+impl FileInfo {
+    fn from_stat(name: String, path: String, stat: &ssh2::FileStat) -> Self {
+        let file_type = FileType::from_stat(stat);
+        let perm = stat.perm.unwrap_or(0);
+
+        FileInfo {
+            name,
+            path,
+            size: stat.size.unwrap_or(0),
+            is_dir: stat.is_dir(),
+            file_type,
+            modified: format_timestamp(stat.mtime),
+            accessed: format_timestamp(stat.atime),
+            permissions: format!("{:o}", perm),
+            mode: format_mode(file_type, perm),
+            uid: stat.uid,
+            gid: stat.gid,
+            is_symlink: file_type == FileType::Symlink,
+            link_target: None,
+            target_is_dir: false,
+        }
+    }
 
-        let sftp = session.sftp()
-            .map_err(|e| format!("创建 SFTP 会话失败: {}", e))?;
+    // 本地文件；非 Unix 平台没有权限位和所有者，按是否只读给出近似值
+    fn from_metadata(name: String, path: String, metadata: &fs::Metadata) -> Self {
+        let file_type = FileType::from_metadata(metadata);
 
-        let mut local_file = std::fs::File::open(&local_path)
-            .map_err(|e| format!("打开本地文件失败: {}", e))?;
+        #[cfg(unix)]
+        let (perm, uid, gid) = {
+            use std::os::unix::fs::MetadataExt;
+            (metadata.mode(), Some(metadata.uid()), Some(metadata.gid()))
+        };
+        #[cfg(not(unix))]
+        let (perm, uid, gid) = (if metadata.permissions().readonly() { 0o555 } else { 0o755 }, None, None);
 
-        let mut remote_file = sftp.create(Path::new(&remote_path))
-            .map_err(|e| format!("创建远程文件失败: {}", e))?;
+        let unix_secs = |time: std::io::Result<std::time::SystemTime>| {
+            time.ok()
+                .and_then(|time| time.duration_since(std::time::UNIX_EPOCH).ok())
+                .map(|duration| duration.as_secs())
+        };
 
-        std::io::copy(&mut local_file, &mut remote_file)
-            .map_err(|e| format!("文件传输失败: {}", e))?;
+        FileInfo {
+            name,
+            path,
+            size: metadata.len(),
+            is_dir: metadata.is_dir(),
+            file_type,
+            modified: format_timestamp(unix_secs(metadata.modified())),
+            accessed: format_timestamp(unix_secs(metadata.accessed())),
+            permissions: format!("{:o}", perm),
+            mode: format_mode(file_type, perm),
+            uid,
+            gid,
+            is_symlink: file_type == FileType::Symlink,
+            link_target: None,
+            target_is_dir: false,
+        }
+    }
 
-        Ok("上传完成".to_string())
-    }).await.map_err(|e| format!("任务执行失败: {}", e))?
-}
+    // 解析符号链接的目标；目标不存在（悬空链接）时只保留链接本身的信息
+    fn resolve_symlink(&mut self, sftp: &ssh2::Sftp, path: &Path) {
+        self.link_target = sftp.readlink(path)
+            .ok()
+            .map(|target| target.to_string_lossy().to_string());
 
-// 上传文件数据（从前端传来的 base64 数据）
-#[tauri::command]
-async fn upload_file_data(
-    connection_id: String,
-    remote_path: String,
-    file_data: String,
-    file_name: String,
-) -> Result<String, String> {
-    tokio::task::spawn_blocking(move || {
-        let connections = CONNECTIONS.lock().unwrap();
-        let session = connections.get(&connection_id)
-            .ok_or("连接不存在")?;
-
-        let sftp = session.sftp()
-            .map_err(|e| format!("创建 SFTP 会话失败: {}", e))?;
-
-        // 解码 base64 数据
-        let decoded_data = general_purpose::STANDARD.decode(&file_data)
-            .map_err(|e| format!("解码文件数据失败: {}", e))?;
-
-        let mut remote_file = sftp.create(Path::new(&remote_path))
-            .map_err(|e| format!("创建远程文件失败: {}", e))?;
-
-        remote_file.write_all(&decoded_data)
-            .map_err(|e| format!("写入文件数据失败: {}", e))?;
-
-        Ok(format!("文件 {} 上传完成", file_name))
-    }).await.map_err(|e| format!("任务执行失败: {}", e))?
+        if let Ok(target_stat) = sftp.stat(path) {
+            self.target_is_dir = target_stat.is_dir();
+        }
+    }
 }
 
-// 创建目录
-#[tauri::command]
-async fn create_directory(
-    connection_id: String,
-    path: String,
-) -> Result<String, String> {
-    tokio::task::spawn_blocking(move || {
-        let connections = CONNECTIONS.lock().unwrap();
-        let session = connections.get(&connection_id)
-            .ok_or("连接不存在")?;
-
-        let sftp = session.sftp()
-            .map_err(|e| format!("创建 SFTP 会话失败: {}", e))?;
-
-        sftp.mkdir(Path::new(&path), 0o755)
-            .map_err(|e| format!("创建目录失败: {}", e))?;
-
-        Ok("目录创建成功".to_string())
-    }).await.map_err(|e| format!("任务执行失败: {}", e))?
+// 将 Unix 时间戳转换为 RFC 3339 字符串
+fn format_timestamp(secs: Option<u64>) -> Option<String> {
+    secs.and_then(|secs| chrono::DateTime::from_timestamp(secs as i64, 0))
+        .map(|time| time.to_rfc3339())
 }
 
-// 删除文件或目录
-#[tauri::command]
-async fn delete_file(
-    connection_id: String,
-    path: String,
-    is_dir: bool,
-) -> Result<String, String> {
-    tokio::task::spawn_blocking(move || {
-        let connections = CONNECTIONS.lock().unwrap();
-        let session = connections.get(&connection_id)
-            .ok_or("连接不存在")?;
-
-        let sftp = session.sftp()
-            .map_err(|e| format!("创建 SFTP 会话失败: {}", e))?;
-
-        if is_dir {
-            sftp.rmdir(Path::new(&path))
-                .map_err(|e| format!("删除目录失败: {}", e))?;
-        } else {
-            sftp.unlink(Path::new(&path))
-                .map_err(|e| format!("删除文件失败: {}", e))?;
-        }
+// 生成 ls -l 风格的权限字符串，如 drwxr-xr-x
+fn format_mode(file_type: FileType, perm: u32) -> String {
+    let mut mode = String::with_capacity(10);
+    mode.push(file_type.mode_char());
+
+    // (读, 写, 执行, 特殊位, 特殊位字符)
+    let triplets = [
+        (0o400, 0o200, 0o100, 0o4000, 's'),
+        (0o040, 0o020, 0o010, 0o2000, 's'),
+        (0o004, 0o002, 0o001, 0o1000, 't'),
+    ];
+    for (read, write, exec, special, special_char) in triplets {
+        mode.push(if perm & read != 0 { 'r' } else { '-' });
+        mode.push(if perm & write != 0 { 'w' } else { '-' });
+        mode.push(match (perm & exec != 0, perm & special != 0) {
+            (true, true) => special_char,
+            (false, true) => special_char.to_ascii_uppercase(),
+            (true, false) => 'x',
+            (false, false) => '-',
+        });
+    }
 
-        Ok("删除成功".to_string())
-    }).await.map_err(|e| format!("任务执行失败: {}", e))?
+    mode
 }
 
-// 获取连接状态和信息
-#[tauri::command]
-async fn get_connection_info(connection_id: String) -> Result<String, String> {
-    tokio::task::spawn_blocking(move || {
-        let connections = CONNECTIONS.lock().unwrap();
-
-        if let Some(session) = connections.get(&connection_id) {
-            let sftp = session.sftp()
-                .map_err(|e| format!("创建 SFTP 会话失败: {}", e))?;
-
-            // 尝试获取当前工作目录
-            match sftp.realpath(Path::new(".")) {
-                Ok(cwd) => Ok(format!("连接活跃，当前目录: {}", cwd.display())),
-                Err(e) => Ok(format!("连接活跃，但无法获取当前目录: {}", e)),
-            }
-        } else {
-            Err(format!("连接不存在: {}", connection_id))
-        }
-    }).await.map_err(|e| format!("任务执行失败: {}", e))?
+// 传输进度信息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransferProgress {
+    pub id: String,
+    pub filename: String,
+    pub total_size: u64,
+    pub transferred: u64,
+    pub speed: f64,
+    pub status: String,
 }
 
-// 获取默认下载目录
-#[tauri::command]
-async fn get_downloads_directory() -> Result<String, String> {
-    // 获取用户的下载目录
-    let downloads_dir = if cfg!(target_os = "windows") {
-        // Windows: 使用用户目录下的 Downloads 文件夹
-        match std::env::var("USERPROFILE") {
-            Ok(user_profile) => format!("{}\\Downloads", user_profile),
-            Err(_) => {
-                // 备选方案：使用当前目录下的 downloads 文件夹
-                let current_dir = std::env::current_dir()
-                    .map_err(|e| format!("无法获取当前目录: {}", e))?;
-                format!("{}\\downloads", current_dir.display())
-            }
-        }
-    } else {
-        // Unix/Linux/macOS: 使用用户目录下的 Downloads 文件夹
-        match std::env::var("HOME") {
-            Ok(home) => format!("{}/Downloads", home),
-            Err(_) => {
-                // 备选方案：使用当前目录下的 downloads 文件夹
-                let current_dir = std::env::current_dir()
-                    .map_err(|e| format!("无法获取当前目录: {}", e))?;
-                format!("{}/downloads", current_dir.display())
-            }
-        }
-    };
-
-    println!("默认下载目录: {}", downloads_dir);
-
-    // 确保目录存在
-    fs::create_dir_all(&downloads_dir)
-        .map_err(|e| format!("创建下载目录失败: {}", e))?;
-
-    Ok(downloads_dir)
+// 批量移动中单个条目的结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MoveResult {
+    pub source: String,
+    pub destination: String,
+    pub success: bool,
+    pub error: Option<SftpError>,
 }
 
-// 断开连接
-#[tauri::command]
-async fn disconnect_sftp(connection_id: String) -> Result<String, String> {
-    let mut connections = CONNECTIONS.lock().unwrap();
-    connections.remove(&connection_id);
-    Ok("连接已断开".to_string())
+// 修改文件属性（chmod / chown / 时间戳）的参数
+// 递归时目录使用 dir_mode、文件使用 file_mode，未指定时回退到 mode；
+// conditional_exec 对应 chmod 的 X：文件只有原本带执行位时才保留掩码中的执行位
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SetStatOptions {
+    pub mode: Option<u32>,
+    pub file_mode: Option<u32>,
+    pub dir_mode: Option<u32>,
+    pub conditional_exec: bool,
+    pub uid: Option<u32>,
+    pub gid: Option<u32>,
+    pub atime: Option<u64>,
+    pub mtime: Option<u64>,
+    pub recursive: bool,
 }
 
-// 打开文件夹
-#[tauri::command]
-async fn open_file_folder(path: String) -> Result<String, String> {
-    println!("打开文件夹: {}", path);
-
-    tokio::task::spawn_blocking(move || {
-        #[cfg(target_os = "windows")]
-        {
-            use std::process::Command;
-            let result = Command::new("explorer")
-                .arg(&path)
-                .spawn();
-
-            match result {
-                Ok(_) => Ok(format!("已打开文件夹: {}", path)),
-                Err(e) => Err(format!("打开文件夹失败: {}", e))
-            }
-        }
+impl SetStatOptions {
+    // 按条目类型和当前权限得出要设置的权限，未指定权限时返回 None
+    fn perm_for(&self, is_dir: bool, current_perm: u32) -> Option<u32> {
+        let mask = if is_dir {
+            self.dir_mode.or(self.mode)
+        } else {
+            self.file_mode.or(self.mode)
+        }? & 0o7777;
 
-        #[cfg(target_os = "macos")]
-        {
-            use std::process::Command;
-            let result = Command::new("open")
-                .arg(&path)
-                .spawn();
-
-            match result {
-                Ok(_) => Ok(format!("已打开文件夹: {}", path)),
-                Err(e) => Err(format!("打开文件夹失败: {}", e))
-            }
+        let has_exec = current_perm & 0o111 != 0;
+        if self.conditional_exec && !is_dir && !has_exec {
+            Some(mask & !0o111)
+        } else {
+            Some(mask)
         }
+    }
 
-        #[cfg(target_os = "linux")]
-        {
-            use std::process::Command;
-            let result = Command::new("xdg-open")
-                .arg(&path)
-                .spawn();
-
-            match result {
-                Ok(_) => Ok(format!("已打开文件夹: {}", path)),
-                Err(e) => Err(format!("打开文件夹失败: {}", e))
-            }
-        }
+    // SFTP 要求 uid/gid、atime/mtime 成对设置，缺少的一项沿用当前值
+    fn to_stat(&self, current: &ssh2::FileStat) -> ssh2::FileStat {
+        let (uid, gid) = if self.uid.is_some() || self.gid.is_some() {
+            (self.uid.or(current.uid), self.gid.or(current.gid))
+        } else {
+            (None, None)
+        };
+        let (atime, mtime) = if self.atime.is_some() || self.mtime.is_some() {
+            (self.atime.or(current.atime), self.mtime.or(current.mtime))
+        } else {
+            (None, None)
+        };
 
-        #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
-        {
-            Err("不支持的操作系统".to_string())
+        ssh2::FileStat {
+            size: None,
+            uid,
+            gid,
+            perm: self.perm_for(current.is_dir(), current.perm.unwrap_or(0)),
+            atime,
+            mtime,
         }
-    }).await.map_err(|e| format!("任务执行失败: {}", e))?
-}
-
-// 取消传输任务
-#[tauri::command]
-async fn cancel_transfer(transfer_id: String) -> Result<String, String> {
-    println!("取消传输: {}", transfer_id);
-
-    let mut tasks = TRANSFER_TASKS.lock().unwrap();
-    if let Some(cancel_flag) = tasks.get(&transfer_id) {
-        cancel_flag.store(true, std::sync::atomic::Ordering::SeqCst);
-        tasks.remove(&transfer_id);
-        Ok(format!("传输任务 {} 已取消", transfer_id))
-    } else {
-        Err(format!("传输任务 {} 不存在或已完成", transfer_id))
     }
 }
 
-#[cfg_attr(mobile, tauri::mobile_entry_point)]
-pub fn run() {
-    tauri::Builder::default()
-        .plugin(tauri_plugin_opener::init())
-        .invoke_handler(tauri::generate_handler![
-            test_sftp_connection,
-            connect_sftp,
-            list_directory,
-            download_file,
-            download_file_with_progress,
-            upload_file,
-            upload_file_data,
-            create_directory,
-            delete_file,
-            get_connection_info,
-            get_downloads_directory,
-            disconnect_sftp,
-            open_file_folder,
-            cancel_transfer
-        ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+// 属性修改结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SetStatSummary {
+    pub updated: u64,
+    // 各路径的失败原因，消息中带有出错的路径
+    pub errors: Vec<SftpError>,
 }
+
+// 本机文件系统使用的连接 ID，双栏浏览时本地面板用它调用同样的命令
+pub const LOCAL_CONNECTION_ID: &str = "local";
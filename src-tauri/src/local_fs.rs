@@ -0,0 +1,208 @@
+// 本地磁盘的 RemoteFs 实现，供双栏浏览的本地面板和测试使用
+// 路径按本机格式直接交给 std::fs
+
+use crate::error::{ErrorCode, SftpError};
+use crate::remote_fs::RemoteFs;
+use crate::{i18n, FileInfo, SetStatOptions, SetStatSummary};
+use std::fs;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::time::{Duration, UNIX_EPOCH};
+
+/// 本机文件系统
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LocalFs;
+
+fn display_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.to_string_lossy().to_string())
+}
+
+// 读取条目信息，符号链接同时记录目标
+fn file_info(path: &Path) -> Result<FileInfo, SftpError> {
+    let path_str = path.to_string_lossy().to_string();
+    let metadata = fs::symlink_metadata(path)
+        .map_err(|e| SftpError::io_at("action.stat", &path_str, e))?;
+
+    let mut file_info = FileInfo::from_metadata(display_name(path), path_str, &metadata);
+    if file_info.is_symlink {
+        file_info.link_target = fs::read_link(path)
+            .ok()
+            .map(|target| target.to_string_lossy().to_string());
+        file_info.target_is_dir = path.is_dir();
+    }
+
+    Ok(file_info)
+}
+
+// 与 SFTP 的实现一致：递归时不跟随子目录中的符号链接，单个路径的失败记入 summary
+#[cfg(unix)]
+fn apply_attributes(path: &Path, options: &SetStatOptions, follow_links: bool, summary: &mut SetStatSummary) {
+    let path_str = path.to_string_lossy();
+    let metadata = if follow_links { fs::metadata(path) } else { fs::symlink_metadata(path) };
+    let metadata = match metadata {
+        Ok(metadata) => metadata,
+        Err(e) => {
+            summary.errors.push(SftpError::io_at("action.stat", &path_str, e));
+            return;
+        }
+    };
+
+    if metadata.file_type().is_symlink() {
+        return;
+    }
+
+    match set_attributes(path, options, &metadata) {
+        Ok(()) => summary.updated += 1,
+        Err(e) => summary.errors.push(SftpError::io_at("action.set_attributes", &path_str, e)),
+    }
+
+    if options.recursive && metadata.is_dir() {
+        match fs::read_dir(path) {
+            Ok(entries) => {
+                for entry in entries.flatten() {
+                    apply_attributes(&entry.path(), options, false, summary);
+                }
+            }
+            Err(e) => summary.errors.push(SftpError::io_at("action.read_dir", &path_str, e)),
+        }
+    }
+}
+
+#[cfg(unix)]
+fn set_attributes(path: &Path, options: &SetStatOptions, metadata: &fs::Metadata) -> std::io::Result<()> {
+    use std::os::unix::fs::{MetadataExt, PermissionsExt};
+
+    if let Some(perm) = options.perm_for(metadata.is_dir(), metadata.mode()) {
+        fs::set_permissions(path, fs::Permissions::from_mode(perm))?;
+    }
+    if options.uid.is_some() || options.gid.is_some() {
+        std::os::unix::fs::chown(path, options.uid, options.gid)?;
+    }
+
+    let mut times = fs::FileTimes::new();
+    if let Some(atime) = options.atime {
+        times = times.set_accessed(UNIX_EPOCH + Duration::from_secs(atime));
+    }
+    if let Some(mtime) = options.mtime {
+        times = times.set_modified(UNIX_EPOCH + Duration::from_secs(mtime));
+    }
+    if options.atime.is_some() || options.mtime.is_some() {
+        fs::File::open(path)?.set_times(times)?;
+    }
+
+    Ok(())
+}
+
+impl RemoteFs for LocalFs {
+    fn list(&self, path: &str) -> Result<Vec<FileInfo>, SftpError> {
+        let path = if path.is_empty() { "/" } else { path };
+        let entries = fs::read_dir(path)
+            .map_err(|e| SftpError::io_at("action.read_dir", path, e))?;
+
+        let mut files = Vec::new();
+        for entry in entries {
+            let entry = entry.map_err(|e| SftpError::io_at("action.read_dir", path, e))?;
+
+            // 列目录期间被删除的条目直接跳过
+            match file_info(&entry.path()) {
+                Ok(file_info) => files.push(file_info),
+                Err(e) if e.code == ErrorCode::NotFound => continue,
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(files)
+    }
+
+    fn stat(&self, path: &str) -> Result<FileInfo, SftpError> {
+        let metadata = fs::metadata(path)
+            .map_err(|e| SftpError::io_at("action.stat", path, e))?;
+
+        Ok(FileInfo::from_metadata(display_name(Path::new(path)), path.to_string(), &metadata))
+    }
+
+    fn open_read(&self, path: &str) -> Result<Box<dyn Read + '_>, SftpError> {
+        let file = fs::File::open(path)
+            .map_err(|e| SftpError::io_at("action.open_local_file", path, e))?;
+        Ok(Box::new(file))
+    }
+
+    fn open_read_from(&self, path: &str, offset: u64) -> Result<Box<dyn Read + '_>, SftpError> {
+        let mut file = fs::File::open(path)
+            .map_err(|e| SftpError::io_at("action.open_local_file", path, e))?;
+        file.seek(SeekFrom::Start(offset))
+            .map_err(|e| SftpError::io_at("action.read_local_file", path, e))?;
+        Ok(Box::new(file))
+    }
+
+    fn open_write(&self, path: &str) -> Result<Box<dyn Write + '_>, SftpError> {
+        let file = fs::File::create(path)
+            .map_err(|e| SftpError::io_at("action.create_local_file", path, e))?;
+        Ok(Box::new(file))
+    }
+
+    fn open_append(&self, path: &str) -> Result<Box<dyn Write + '_>, SftpError> {
+        let file = fs::OpenOptions::new()
+            .append(true)
+            .open(path)
+            .map_err(|e| SftpError::io_at("action.open_local_file", path, e))?;
+        Ok(Box::new(file))
+    }
+
+    fn mkdir(&self, path: &str) -> Result<(), SftpError> {
+        fs::create_dir(path)
+            .map_err(|e| SftpError::io_at("action.create_dir", path, e))
+    }
+
+    fn remove_file(&self, path: &str) -> Result<(), SftpError> {
+        fs::remove_file(path)
+            .map_err(|e| SftpError::io_at("action.remove_file", path, e))
+    }
+
+    fn remove_dir(&self, path: &str) -> Result<(), SftpError> {
+        fs::remove_dir(path)
+            .map_err(|e| SftpError::io_at("action.remove_dir", path, e))
+    }
+
+    // std::fs::rename 总是替换目标，不覆盖时需要先检查
+    fn rename(&self, source: &str, destination: &str, overwrite: bool) -> Result<(), SftpError> {
+        if !overwrite && fs::symlink_metadata(destination).is_ok() {
+            return Err(SftpError::new(ErrorCode::AlreadyExists, i18n::format("error.destination_exists", &[&destination])));
+        }
+
+        fs::rename(source, destination)
+            .map_err(|e| SftpError::io_at("action.rename", source, e))
+    }
+
+    fn set_modified(&self, path: &str, modified: u64) -> Result<(), SftpError> {
+        let file = fs::OpenOptions::new()
+            .write(true)
+            .open(path)
+            .map_err(|e| SftpError::io_at("action.set_modified", path, e))?;
+        file.set_modified(UNIX_EPOCH + Duration::from_secs(modified))
+            .map_err(|e| SftpError::io_at("action.set_modified", path, e))
+    }
+
+    // 权限和所有者只对 Unix 有意义，其他平台使用默认实现
+    #[cfg(unix)]
+    fn set_attributes(&self, path: &str, options: &SetStatOptions) -> Result<SetStatSummary, SftpError> {
+        let mut summary = SetStatSummary::default();
+        apply_attributes(Path::new(path), options, true, &mut summary);
+        Ok(summary)
+    }
+
+    fn read_link(&self, path: &str) -> Result<String, SftpError> {
+        fs::read_link(path)
+            .map(|target| target.to_string_lossy().to_string())
+            .map_err(|e| SftpError::io_at("action.read_symlink", path, e))
+    }
+
+    // Windows 创建符号链接需要额外权限，只在 Unix 上支持
+    #[cfg(unix)]
+    fn symlink(&self, target: &str, link_path: &str) -> Result<(), SftpError> {
+        std::os::unix::fs::symlink(target, link_path)
+            .map_err(|e| SftpError::io_at("action.create_symlink", link_path, e))
+    }
+}
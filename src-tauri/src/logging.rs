@@ -0,0 +1,119 @@
+// 日志：按天滚动写入应用数据目录下的 logs 目录，只保留最近几天的文件
+// 调试构建同时输出到终端；级别默认为 info，可通过 RUST_LOG 调整
+
+use crate::error::{ErrorCode, SftpError};
+use crate::i18n;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::prelude::*;
+use tracing_subscriber::{fmt, EnvFilter};
+
+const LOG_FILE_PREFIX: &str = "sftp-web";
+const LOG_FILE_SUFFIX: &str = "log";
+const MAX_LOG_FILES: usize = 7;
+pub(crate) const DEFAULT_READ_LINES: usize = 500;
+
+static LOG_DIR: OnceLock<PathBuf> = OnceLock::new();
+// 后台写入线程的句柄，释放后缓冲中的日志会丢失，因此在进程生命周期内保留
+static WRITER_GUARD: OnceLock<WorkerGuard> = OnceLock::new();
+
+pub(crate) fn init(log_dir: PathBuf) -> Result<(), SftpError> {
+    fs::create_dir_all(&log_dir)
+        .map_err(|e| SftpError::io("action.create_log_dir", e))?;
+
+    let appender = RollingFileAppender::builder()
+        .rotation(Rotation::DAILY)
+        .filename_prefix(LOG_FILE_PREFIX)
+        .filename_suffix(LOG_FILE_SUFFIX)
+        .max_log_files(MAX_LOG_FILES)
+        .build(&log_dir)
+        .map_err(|e| SftpError::new(ErrorCode::LocalIo, i18n::format("error.logging_init", &[&e])))?;
+    let (writer, guard) = tracing_appender::non_blocking(appender);
+
+    let filter = EnvFilter::try_from_default_env()
+        .unwrap_or_else(|_| EnvFilter::new("info"));
+    let file_layer = fmt::layer()
+        .with_writer(writer)
+        .with_ansi(false)
+        .with_target(false);
+    let console_layer = cfg!(debug_assertions).then(|| fmt::layer().with_target(false));
+
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(file_layer)
+        .with(console_layer)
+        .try_init()
+        .map_err(|e| SftpError::new(ErrorCode::Internal, i18n::format("error.logging_init", &[&e])))?;
+
+    let _ = WRITER_GUARD.set(guard);
+    let _ = LOG_DIR.set(log_dir);
+    Ok(())
+}
+
+pub(crate) fn log_dir() -> Result<&'static Path, SftpError> {
+    LOG_DIR.get()
+        .map(PathBuf::as_path)
+        .ok_or_else(|| SftpError::new(ErrorCode::Unsupported, i18n::text("error.logging_unavailable")))
+}
+
+// 按时间顺序排列的日志文件（文件名中的日期可直接按字符串排序）
+fn log_files() -> Result<Vec<PathBuf>, SftpError> {
+    let entries = fs::read_dir(log_dir()?)
+        .map_err(|e| SftpError::io("action.read_logs", e))?;
+
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with(LOG_FILE_PREFIX) && name.ends_with(LOG_FILE_SUFFIX))
+        })
+        .collect();
+    files.sort();
+
+    Ok(files)
+}
+
+// 读取最近的 max_lines 行日志，从最新的文件往前读，直到行数足够
+pub(crate) fn read_recent(max_lines: usize) -> Result<Vec<String>, SftpError> {
+    let mut lines = Vec::new();
+
+    for file in log_files()?.iter().rev() {
+        let content = fs::read_to_string(file)
+            .map_err(|e| SftpError::io_at("action.read_logs", &file.to_string_lossy(), e))?;
+
+        let mut file_lines: Vec<String> = content.lines().map(str::to_string).collect();
+        let skip = file_lines.len().saturating_sub(max_lines - lines.len());
+        file_lines.drain(..skip);
+        file_lines.append(&mut lines);
+        lines = file_lines;
+
+        if lines.len() >= max_lines {
+            break;
+        }
+    }
+
+    Ok(lines)
+}
+
+// 将全部日志按时间顺序合并导出到一个文件，返回写入的字节数
+pub(crate) fn export(destination: &Path) -> Result<u64, SftpError> {
+    let mut output = fs::File::create(destination)
+        .map_err(|e| SftpError::io("action.export_logs", e))?;
+    let mut written = 0u64;
+
+    for file in log_files()? {
+        let mut input = fs::File::open(&file)
+            .map_err(|e| SftpError::io_at("action.read_logs", &file.to_string_lossy(), e))?;
+        written += std::io::copy(&mut input, &mut output)
+            .map_err(|e| SftpError::io("action.export_logs", e))?;
+    }
+
+    output.flush()
+        .map_err(|e| SftpError::io("action.export_logs", e))?;
+    Ok(written)
+}
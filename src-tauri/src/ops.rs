@@ -0,0 +1,468 @@
+// 与界面无关的连接和传输操作，Tauri 命令、命令行工具和 SftpClient 共用
+// 这里的函数都是阻塞的，直接作用于传入的会话或文件系统，不涉及全局连接表
+
+use crate::error::{ErrorCode, SftpError};
+use crate::proxy::{self, ProxyConfig};
+use crate::remote_fs::RemoteFs;
+use crate::{i18n, TimeoutConfig};
+use ssh2::{CheckResult, KnownHostFileKind, Session};
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+// 进度回调的最小间隔
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+const PROGRESS_BYTES: u64 = 1024 * 1024;
+
+/// 传输进度的接收方
+///
+/// 参数为 (已传输字节数, 总字节数)，总大小未知时为 0。
+/// 闭包 `FnMut(u64, u64)` 已实现该 trait，可以直接传入。
+pub trait ProgressListener {
+    fn on_progress(&mut self, transferred: u64, total: u64);
+}
+
+impl<F: FnMut(u64, u64)> ProgressListener for F {
+    fn on_progress(&mut self, transferred: u64, total: u64) {
+        self(transferred, total)
+    }
+}
+
+/// 不关心进度时使用
+pub struct NoProgress;
+
+impl ProgressListener for NoProgress {
+    fn on_progress(&mut self, _transferred: u64, _total: u64) {}
+}
+
+/// 公钥认证使用的私钥文件
+#[derive(Debug, Clone)]
+pub struct PrivateKey {
+    pub path: PathBuf,
+    /// 私钥有口令保护时需要
+    pub passphrase: Option<String>,
+}
+
+// 建立 SSH 会话并完成认证，认证完成后切换为单次操作超时
+// 指定私钥时使用公钥认证，否则使用密码认证
+pub fn connect(
+    host: &str,
+    port: u16,
+    username: &str,
+    password: &str,
+    private_key: Option<&PrivateKey>,
+    proxy: Option<&ProxyConfig>,
+    timeouts: &TimeoutConfig,
+) -> Result<Session, SftpError> {
+    let tcp = proxy::connect(host, port, proxy, timeouts.connect_timeout())?;
+
+    let mut session = Session::new()
+        .map_err(|e| SftpError::ssh("action.create_session", e))?;
+
+    session.set_tcp_stream(tcp);
+    session.set_timeout(timeouts.handshake_millis());
+    session.handshake()
+        .map_err(|e| {
+            let error = SftpError::ssh("action.handshake", e);
+            match error.code {
+                ErrorCode::Timeout => error,
+                _ => error.with_code(ErrorCode::ConnectionFailed),
+            }
+        })?;
+
+    // 认证前校验主机密钥，避免把密码发给冒充的服务器
+    if let Some(known_hosts_path) = default_known_hosts_path() {
+        check_host_key(&session, host, port, &known_hosts_path)?;
+    }
+
+    match private_key {
+        Some(key) => {
+            // 与 ssh 一样，公钥默认放在私钥旁边的 .pub 文件中；没有时由 libssh2 从私钥推导
+            let mut public_key = key.path.clone().into_os_string();
+            public_key.push(".pub");
+            let public_key = PathBuf::from(public_key);
+            session.userauth_pubkey_file(
+                username,
+                public_key.exists().then_some(public_key.as_path()),
+                &key.path,
+                key.passphrase.as_deref(),
+            )
+        }
+        None => session.userauth_password(username, password),
+    }
+    .map_err(|e| SftpError::ssh("action.auth", e).with_code(ErrorCode::AuthFailed))?;
+
+    if !session.authenticated() {
+        return Err(SftpError::new(ErrorCode::AuthFailed, i18n::text("error.auth_failed")));
+    }
+
+    session.set_timeout(timeouts.operation_millis());
+    Ok(session)
+}
+
+// 与 ssh 相同的 known_hosts 位置
+fn default_known_hosts_path() -> Option<PathBuf> {
+    std::env::var_os("HOME")
+        .or_else(|| std::env::var_os("USERPROFILE"))
+        .map(|home| Path::new(&home).join(".ssh").join("known_hosts"))
+}
+
+// 用 known_hosts 校验握手后的服务器主机密钥，密钥与已有记录不一致时返回 HostKeyMismatch。
+// 文件不存在、无法解析或没有该主机的记录时接受连接，不写回文件
+pub fn check_host_key(session: &Session, host: &str, port: u16, known_hosts_path: &Path) -> Result<(), SftpError> {
+    if !known_hosts_path.exists() {
+        return Ok(());
+    }
+
+    let Some((key, _)) = session.host_key() else {
+        return Ok(());
+    };
+    let mut known_hosts = session.known_hosts()
+        .map_err(|e| SftpError::ssh("action.check_host_key", e))?;
+    if let Err(e) = known_hosts.read_file(known_hosts_path, KnownHostFileKind::OpenSSH) {
+        tracing::warn!(path = %known_hosts_path.display(), "读取 known_hosts 失败，跳过主机密钥校验: {}", e);
+        return Ok(());
+    }
+
+    match known_hosts.check_port(host, port, key) {
+        CheckResult::Mismatch => {
+            let host = if port == 22 { host.to_string() } else { format!("[{}]:{}", host, port) };
+            Err(SftpError::new(
+                ErrorCode::HostKeyMismatch,
+                i18n::format("error.host_key_mismatch", &[&host, &known_hosts_path.display()]),
+            ))
+        }
+        CheckResult::Match | CheckResult::NotFound | CheckResult::Failure => Ok(()),
+    }
+}
+
+// 下载远程文件，返回传输的字节数
+// 进度最多每 100ms 或每 1MB 报告一次，开始和结束时各报告一次
+pub fn download(
+    remote: &dyn RemoteFs,
+    remote_path: &str,
+    local_path: &str,
+    cancel_flag: &AtomicBool,
+    progress: &mut dyn ProgressListener,
+) -> Result<u64, SftpError> {
+    // 确保本地目录存在
+    if let Some(parent_dir) = Path::new(local_path).parent() {
+        fs::create_dir_all(parent_dir)
+            .map_err(|e| SftpError::io("action.create_local_dir", e))?;
+    }
+
+    // 先取大小再打开：FTP 在数据流打开期间不能发送其他命令
+    let total_size = remote.stat(remote_path)?.size;
+    let mut remote_file = remote.open_read(remote_path)?;
+
+    let mut local_file = fs::File::create(local_path)
+        .map_err(|e| SftpError::io("action.create_local_file", e))?;
+
+    copy_with_progress(
+        &mut remote_file,
+        &mut local_file,
+        total_size,
+        cancel_flag,
+        progress,
+        ("action.read_remote_file", "action.write_local_file"),
+    )
+}
+
+// 续传下载：本地已有部分内容时从其末尾继续，返回本次传输的字节数
+// 进度按整个文件计算；本地文件比远程文件大时视为不同的文件，重新下载
+pub fn download_resume(
+    remote: &dyn RemoteFs,
+    remote_path: &str,
+    local_path: &str,
+    cancel_flag: &AtomicBool,
+    progress: &mut dyn ProgressListener,
+) -> Result<u64, SftpError> {
+    let total_size = remote.stat(remote_path)?.size;
+    let offset = match fs::metadata(local_path) {
+        Ok(metadata) if metadata.is_file() && metadata.len() <= total_size => metadata.len(),
+        _ => return download(remote, remote_path, local_path, cancel_flag, progress),
+    };
+
+    if offset == total_size {
+        progress.on_progress(total_size, total_size);
+        return Ok(0);
+    }
+
+    let mut remote_file = remote.open_read_from(remote_path, offset)?;
+    let mut local_file = fs::OpenOptions::new()
+        .append(true)
+        .open(local_path)
+        .map_err(|e| SftpError::io("action.open_local_file", e))?;
+
+    let mut progress = |transferred: u64, _total: u64| progress.on_progress(offset + transferred, total_size);
+    copy_with_progress(
+        &mut remote_file,
+        &mut local_file,
+        total_size - offset,
+        cancel_flag,
+        &mut progress,
+        ("action.read_remote_file", "action.write_local_file"),
+    )
+}
+
+// 上传本地文件，返回传输的字节数；progress 与 download 相同
+pub fn upload(
+    remote: &dyn RemoteFs,
+    local_path: &str,
+    remote_path: &str,
+    cancel_flag: &AtomicBool,
+    progress: &mut dyn ProgressListener,
+) -> Result<u64, SftpError> {
+    let mut local_file = fs::File::open(local_path)
+        .map_err(|e| SftpError::io("action.open_local_file", e))?;
+    let total_size = local_file.metadata()
+        .map(|metadata| metadata.len())
+        .unwrap_or(0);
+
+    let mut remote_file = remote.open_write_sized(remote_path, total_size)?;
+
+    copy_with_progress(
+        &mut local_file,
+        &mut remote_file,
+        total_size,
+        cancel_flag,
+        progress,
+        ("action.read_local_file", "action.write_remote_file"),
+    )
+}
+
+/// 连接之间传输的参数
+pub struct TransferRequest<'a> {
+    pub source: &'a str,
+    pub destination: &'a str,
+    /// 目标文件已存在时覆盖
+    pub overwrite: bool,
+    /// 目标文件不大于源文件时从其末尾继续，优先于 overwrite
+    pub resume: bool,
+}
+
+enum TransferEntry {
+    Dir { destination: String },
+    File { source: String, destination: String, size: u64 },
+}
+
+/// 在两个文件系统之间流式传输文件或目录，数据不落本地磁盘
+///
+/// 目录递归复制，已存在的目标目录直接沿用；指向目录的符号链接会被跳过，避免循环。
+/// 进度按全部文件的总大小计算，续传时已存在的部分计为已完成。
+/// 返回本次实际传输的字节数。
+pub fn transfer(
+    source_fs: &dyn RemoteFs,
+    destination_fs: &dyn RemoteFs,
+    request: &TransferRequest,
+    cancel_flag: &AtomicBool,
+    progress: &mut dyn ProgressListener,
+) -> Result<u64, SftpError> {
+    let source = source_fs.stat(request.source)?;
+    let mut entries = Vec::new();
+    if source.is_dir {
+        collect_transfer_entries(source_fs, request.source, request.destination, &mut entries)?;
+    } else {
+        entries.push(TransferEntry::File {
+            source: request.source.to_string(),
+            destination: request.destination.to_string(),
+            size: source.size,
+        });
+    }
+
+    let total_size: u64 = entries.iter()
+        .map(|entry| match entry {
+            TransferEntry::File { size, .. } => *size,
+            TransferEntry::Dir { .. } => 0,
+        })
+        .sum();
+    progress.on_progress(0, total_size);
+
+    let mut done = 0u64;
+    let mut bytes_copied = 0u64;
+
+    for entry in &entries {
+        if cancel_flag.load(Ordering::SeqCst) {
+            return Err(SftpError::cancelled());
+        }
+
+        match entry {
+            TransferEntry::Dir { destination } => ensure_dir(destination_fs, destination)?,
+            TransferEntry::File { source, destination, size } => {
+                let offset = transfer_offset(destination_fs, destination, *size, request)?;
+                let base = done;
+                let mut progress = |transferred: u64, _total: u64| progress.on_progress(base + transferred, total_size);
+                bytes_copied += transfer_file(source_fs, destination_fs, source, destination, *size, offset, cancel_flag, &mut progress)?;
+                done += size;
+            }
+        }
+    }
+
+    progress.on_progress(done, total_size);
+    Ok(bytes_copied)
+}
+
+// 确保目录存在，已存在的目录直接沿用；同名文件视为冲突
+// 部分服务器创建已存在的目录时只返回笼统的失败，因此先查询再创建
+pub(crate) fn ensure_dir(fs: &dyn RemoteFs, path: &str) -> Result<(), SftpError> {
+    match fs.stat(path) {
+        Ok(existing) if existing.is_dir => Ok(()),
+        Ok(_) => Err(SftpError::new(ErrorCode::AlreadyExists, i18n::format("error.destination_exists", &[&path]))),
+        Err(e) if e.code == ErrorCode::NotFound => fs.mkdir(path),
+        Err(e) => Err(e),
+    }
+}
+
+// 展开需要传输的条目，目录在其内容之前
+fn collect_transfer_entries(
+    fs: &dyn RemoteFs,
+    source: &str,
+    destination: &str,
+    entries: &mut Vec<TransferEntry>,
+) -> Result<(), SftpError> {
+    entries.push(TransferEntry::Dir { destination: destination.to_string() });
+
+    for child in fs.list(source)? {
+        let child_destination = join_remote_path(destination, &child.name);
+        if child.is_symlink && child.target_is_dir {
+            tracing::warn!(path = %child.path, "跳过指向目录的符号链接");
+        } else if child.is_dir {
+            collect_transfer_entries(fs, &child.path, &child_destination, entries)?;
+        } else {
+            entries.push(TransferEntry::File {
+                source: child.path,
+                destination: child_destination,
+                size: child.size,
+            });
+        }
+    }
+
+    Ok(())
+}
+
+// 根据目标文件的现状决定起始位置
+fn transfer_offset(
+    destination_fs: &dyn RemoteFs,
+    destination: &str,
+    size: u64,
+    request: &TransferRequest,
+) -> Result<u64, SftpError> {
+    let existing = match destination_fs.stat(destination) {
+        Ok(existing) => existing,
+        Err(e) if e.code == ErrorCode::NotFound => return Ok(0),
+        Err(e) => return Err(e),
+    };
+
+    if existing.is_dir {
+        Err(SftpError::new(ErrorCode::AlreadyExists, i18n::format("error.is_directory", &[&destination])))
+    } else if request.resume && existing.size <= size {
+        Ok(existing.size)
+    } else if request.overwrite {
+        Ok(0)
+    } else {
+        Err(SftpError::new(ErrorCode::AlreadyExists, i18n::format("error.destination_exists", &[&destination])))
+    }
+}
+
+// 传输单个文件，从 offset 处继续；目标不支持追加时改为完整传输
+#[allow(clippy::too_many_arguments)]
+fn transfer_file(
+    source_fs: &dyn RemoteFs,
+    destination_fs: &dyn RemoteFs,
+    source: &str,
+    destination: &str,
+    size: u64,
+    offset: u64,
+    cancel_flag: &AtomicBool,
+    progress: &mut dyn ProgressListener,
+) -> Result<u64, SftpError> {
+    if offset == size && offset > 0 {
+        progress.on_progress(size, size);
+        return Ok(0);
+    }
+
+    let writer = if offset > 0 {
+        match destination_fs.open_append(destination) {
+            Ok(writer) => Some(writer),
+            Err(e) if e.code == ErrorCode::Unsupported => {
+                tracing::info!(path = %destination, "目标不支持追加写入，重新传输整个文件");
+                None
+            }
+            Err(e) => return Err(e),
+        }
+    } else {
+        None
+    };
+
+    let (mut reader, mut writer, offset) = match writer {
+        Some(writer) => (source_fs.open_read_from(source, offset)?, writer, offset),
+        None => (source_fs.open_read(source)?, destination_fs.open_write_sized(destination, size)?, 0),
+    };
+
+    let mut progress = |transferred: u64, _total: u64| progress.on_progress(offset + transferred, size);
+    copy_with_progress(
+        &mut reader,
+        &mut writer,
+        size - offset,
+        cancel_flag,
+        &mut progress,
+        ("action.read_remote_file", "action.write_remote_file"),
+    )
+}
+
+// 分块复制并报告进度；actions 为读、写失败时使用的操作名称
+fn copy_with_progress(
+    reader: &mut dyn Read,
+    writer: &mut dyn Write,
+    total_size: u64,
+    cancel_flag: &AtomicBool,
+    progress: &mut dyn ProgressListener,
+    actions: (&'static str, &'static str),
+) -> Result<u64, SftpError> {
+    let mut buffer = [0u8; 32 * 1024];
+    let mut bytes_copied = 0u64;
+    let mut last_reported = 0u64;
+    let mut last_progress_update = Instant::now();
+
+    progress.on_progress(0, total_size);
+
+    loop {
+        if cancel_flag.load(Ordering::SeqCst) {
+            progress.on_progress(bytes_copied, total_size);
+            return Err(SftpError::cancelled());
+        }
+
+        let n = reader.read(&mut buffer)
+            .map_err(|e| SftpError::io(actions.0, e))?;
+        if n == 0 {
+            break;
+        }
+
+        writer.write_all(&buffer[..n])
+            .map_err(|e| SftpError::io(actions.1, e))?;
+        bytes_copied += n as u64;
+
+        if last_progress_update.elapsed() >= PROGRESS_INTERVAL || bytes_copied - last_reported >= PROGRESS_BYTES {
+            progress.on_progress(bytes_copied, total_size);
+            last_reported = bytes_copied;
+            last_progress_update = Instant::now();
+        }
+    }
+
+    writer.flush()
+        .map_err(|e| SftpError::io(actions.1, e))?;
+    progress.on_progress(bytes_copied, total_size);
+
+    Ok(bytes_copied)
+}
+
+// 拼接远程路径（远程路径统一使用 / 分隔）
+pub fn join_remote_path(dir: &str, name: &str) -> String {
+    if dir.is_empty() || dir == "/" {
+        format!("/{}", name)
+    } else {
+        format!("{}/{}", dir.trim_end_matches('/'), name)
+    }
+}
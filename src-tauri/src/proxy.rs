@@ -0,0 +1,255 @@
+// 通过 SOCKS5 或 HTTP CONNECT 代理建立到 SSH / FTP 服务器的 TCP 连接，
+// 以及 HTTP 类协议使用的带代理设置的 reqwest 客户端
+
+use crate::error::{ErrorCode, SftpError};
+use crate::{i18n, TimeoutConfig};
+use base64::{engine::general_purpose, Engine as _};
+use reqwest::blocking::Client;
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::Mutex;
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProxyType {
+    // 显式直连，不使用全局默认代理
+    Direct,
+    Socks5,
+    Http,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProxyConfig {
+    pub proxy_type: ProxyType,
+    #[serde(default)]
+    pub host: String,
+    #[serde(default)]
+    pub port: u16,
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub password: Option<String>,
+}
+
+// 连接未单独配置代理时使用的全局默认代理
+static DEFAULT_PROXY: std::sync::LazyLock<Mutex<Option<ProxyConfig>>> =
+    std::sync::LazyLock::new(|| Mutex::new(None));
+
+#[cfg(feature = "gui")]
+pub(crate) fn set_default(proxy: Option<ProxyConfig>) {
+    *DEFAULT_PROXY.lock().unwrap() = proxy;
+}
+
+pub(crate) fn get_default() -> Option<ProxyConfig> {
+    DEFAULT_PROXY.lock().unwrap().clone()
+}
+
+// 连接到目标地址，优先使用连接自身的代理配置，其次是全局默认代理
+// timeout 同时用于建立 TCP 连接和与代理的协商
+pub(crate) fn connect(host: &str, port: u16, proxy: Option<&ProxyConfig>, timeout: Duration) -> Result<TcpStream, SftpError> {
+    let proxy = match proxy {
+        Some(proxy) => Some(proxy.clone()),
+        None => get_default(),
+    };
+
+    let Some(proxy) = proxy.filter(|proxy| proxy.proxy_type != ProxyType::Direct) else {
+        return connect_with_timeout(host, port, timeout)
+            .map_err(|e| connect_error("action.connect", e, ErrorCode::ConnectionFailed));
+    };
+
+    let mut stream = connect_with_timeout(&proxy.host, proxy.port, timeout)
+        .map_err(|e| connect_error("action.connect_proxy", e, ErrorCode::ProxyFailed))?;
+
+    let set_timeouts = |stream: &TcpStream, timeout: Option<Duration>| {
+        stream.set_read_timeout(timeout)
+            .and_then(|_| stream.set_write_timeout(timeout))
+            .map_err(|e| SftpError::io("action.proxy_timeout", e))
+    };
+
+    set_timeouts(&stream, Some(timeout))?;
+    if proxy.proxy_type == ProxyType::Socks5 {
+        socks5_connect(&mut stream, host, port, &proxy)?;
+    } else {
+        http_connect(&mut stream, host, port, &proxy)?;
+    }
+    set_timeouts(&stream, None)?;
+
+    Ok(stream)
+}
+
+// HTTP 类协议（WebDAV、S3）共用的 reqwest 客户端，代理的选择与 connect 相同
+// 整体超时会中断大文件传输，这里只设置连接超时，元数据请求由调用方单独设置超时
+pub(crate) fn http_client(proxy: Option<&ProxyConfig>, timeouts: &TimeoutConfig, accept_invalid_certs: bool) -> Result<Client, SftpError> {
+    let mut builder = Client::builder()
+        .timeout(None)
+        .connect_timeout(timeouts.connect_timeout())
+        .danger_accept_invalid_certs(accept_invalid_certs)
+        .danger_accept_invalid_hostnames(accept_invalid_certs);
+    builder = match reqwest_proxy(proxy)? {
+        Some(proxy) => builder.proxy(proxy),
+        None => builder.no_proxy(),
+    };
+
+    builder.build()
+        .map_err(|e| SftpError::new(ErrorCode::Internal, i18n::format("error.failed", &[&i18n::text("action.tls_setup"), &e])))
+}
+
+// 把代理配置转换为 reqwest 的格式
+// 返回 None 表示直连；SOCKS5 使用 socks5h，由代理解析目标主机名，与 socks5_connect 一致
+fn reqwest_proxy(proxy: Option<&ProxyConfig>) -> Result<Option<reqwest::Proxy>, SftpError> {
+    let proxy = match proxy {
+        Some(proxy) => Some(proxy.clone()),
+        None => get_default(),
+    };
+    let Some(proxy) = proxy.filter(|proxy| proxy.proxy_type != ProxyType::Direct) else {
+        return Ok(None);
+    };
+
+    let scheme = if proxy.proxy_type == ProxyType::Socks5 { "socks5h" } else { "http" };
+    let mut url = reqwest::Url::parse(&format!("{}://{}:{}", scheme, proxy.host, proxy.port))
+        .map_err(|e| proxy_failed(i18n::format("error.failed", &[&i18n::text("action.connect_proxy"), &e])))?;
+    if let Some(username) = &proxy.username {
+        let _ = url.set_username(username);
+        let _ = url.set_password(proxy.password.as_deref());
+    }
+
+    reqwest::Proxy::all(url)
+        .map(Some)
+        .map_err(|e| proxy_failed(i18n::format("error.failed", &[&i18n::text("action.connect_proxy"), &e])))
+}
+
+// 依次尝试解析出的每个地址，避免不可达主机等待系统默认超时
+fn connect_with_timeout(host: &str, port: u16, timeout: Duration) -> std::io::Result<TcpStream> {
+    let mut last_error = std::io::Error::new(std::io::ErrorKind::NotFound, i18n::text("error.resolve_host"));
+
+    for addr in (host, port).to_socket_addrs()? {
+        match TcpStream::connect_timeout(&addr, timeout) {
+            Ok(stream) => return Ok(stream),
+            Err(e) => last_error = e,
+        }
+    }
+
+    Err(last_error)
+}
+
+// 超时保留 Timeout 分类，其余错误归入指定的分类
+fn connect_error(action: &'static str, e: std::io::Error, code: ErrorCode) -> SftpError {
+    let error = SftpError::io(action, e);
+    if error.code == ErrorCode::Timeout {
+        error
+    } else {
+        error.with_code(code)
+    }
+}
+
+fn proxy_failed(message: impl Into<String>) -> SftpError {
+    SftpError::new(ErrorCode::ProxyFailed, message)
+}
+
+fn socks5_connect(stream: &mut TcpStream, host: &str, port: u16, proxy: &ProxyConfig) -> Result<(), SftpError> {
+    let io_err = |e: std::io::Error| connect_error("action.socks5_proxy", e, ErrorCode::ProxyFailed);
+
+    let use_password = proxy.username.is_some();
+    let greeting: &[u8] = if use_password { &[0x05, 0x02, 0x00, 0x02] } else { &[0x05, 0x01, 0x00] };
+    stream.write_all(greeting).map_err(io_err)?;
+
+    let mut choice = [0u8; 2];
+    stream.read_exact(&mut choice).map_err(io_err)?;
+    match choice[1] {
+        0x00 => {}
+        0x02 if use_password => {
+            let username = proxy.username.as_deref().unwrap_or_default().as_bytes();
+            let password = proxy.password.as_deref().unwrap_or_default().as_bytes();
+            if username.len() > 255 || password.len() > 255 {
+                return Err(proxy_failed(i18n::text("error.socks5_credentials_too_long")));
+            }
+
+            let mut auth = vec![0x01, username.len() as u8];
+            auth.extend_from_slice(username);
+            auth.push(password.len() as u8);
+            auth.extend_from_slice(password);
+            stream.write_all(&auth).map_err(io_err)?;
+
+            let mut status = [0u8; 2];
+            stream.read_exact(&mut status).map_err(io_err)?;
+            if status[1] != 0x00 {
+                return Err(SftpError::new(ErrorCode::AuthFailed, i18n::text("error.socks5_auth_failed")));
+            }
+        }
+        _ => return Err(proxy_failed(i18n::text("error.socks5_no_auth_method"))),
+    }
+
+    if host.len() > 255 {
+        return Err(SftpError::new(ErrorCode::InvalidInput, i18n::text("error.host_too_long")));
+    }
+    // 使用域名形式，由代理负责解析
+    let mut request = vec![0x05, 0x01, 0x00, 0x03, host.len() as u8];
+    request.extend_from_slice(host.as_bytes());
+    request.extend_from_slice(&port.to_be_bytes());
+    stream.write_all(&request).map_err(io_err)?;
+
+    let mut reply = [0u8; 4];
+    stream.read_exact(&mut reply).map_err(io_err)?;
+    if reply[1] != 0x00 {
+        return Err(proxy_failed(i18n::format("error.socks5_rejected", &[&reply[1]])));
+    }
+
+    // 跳过代理返回的绑定地址
+    let address_len = match reply[3] {
+        0x01 => 4,
+        0x04 => 16,
+        0x03 => {
+            let mut len = [0u8; 1];
+            stream.read_exact(&mut len).map_err(io_err)?;
+            len[0] as usize
+        }
+        _ => return Err(proxy_failed(i18n::text("error.socks5_bad_reply"))),
+    };
+    let mut bound = vec![0u8; address_len + 2];
+    stream.read_exact(&mut bound).map_err(io_err)?;
+
+    Ok(())
+}
+
+fn http_connect(stream: &mut TcpStream, host: &str, port: u16, proxy: &ProxyConfig) -> Result<(), SftpError> {
+    let io_err = |e: std::io::Error| connect_error("action.http_proxy", e, ErrorCode::ProxyFailed);
+
+    let authority = if host.contains(':') {
+        format!("[{}]:{}", host, port)
+    } else {
+        format!("{}:{}", host, port)
+    };
+
+    let mut request = format!("CONNECT {0} HTTP/1.1\r\nHost: {0}\r\n", authority);
+    if let Some(username) = &proxy.username {
+        let credentials = format!("{}:{}", username, proxy.password.as_deref().unwrap_or_default());
+        request.push_str(&format!(
+            "Proxy-Authorization: Basic {}\r\n",
+            general_purpose::STANDARD.encode(credentials)
+        ));
+    }
+    request.push_str("\r\n");
+    stream.write_all(request.as_bytes()).map_err(io_err)?;
+
+    // 逐字节读取响应头，避免多读走属于 SSH 的数据
+    let mut response = Vec::new();
+    let mut byte = [0u8; 1];
+    while !response.ends_with(b"\r\n\r\n") {
+        if response.len() > 16 * 1024 {
+            return Err(proxy_failed(i18n::text("error.http_proxy_header_too_long")));
+        }
+        stream.read_exact(&mut byte).map_err(io_err)?;
+        response.push(byte[0]);
+    }
+
+    let response = String::from_utf8_lossy(&response);
+    let status_line = response.lines().next().unwrap_or_default();
+    let status = status_line.split_whitespace().nth(1).unwrap_or_default();
+    if status != "200" {
+        return Err(proxy_failed(i18n::format("error.http_proxy_rejected", &[&status_line])));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,267 @@
+// 服务器端复制文件和目录
+// 依次尝试：copy-data 扩展 -> 远程 cp -a -> 经客户端中转的流式读写
+
+use crate::error::{ErrorCode, SftpError};
+use crate::exec;
+use crate::i18n;
+use crate::ops::{join_remote_path, ProgressListener};
+use crate::sftp_ext::ExtSftp;
+use ssh2::{OpenFlags, OpenType, Session, Sftp};
+use std::io::{Read, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum CopyMethod {
+    CopyData,
+    Shell,
+    Stream,
+    // 部分文件由 copy-data 复制，其余 copy-data 失败的文件经客户端中转
+    Mixed,
+}
+
+impl CopyMethod {
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            CopyMethod::CopyData => "copy-data",
+            CopyMethod::Shell => "cp -a",
+            CopyMethod::Stream => "stream",
+            CopyMethod::Mixed => "copy-data + stream",
+        }
+    }
+}
+
+enum EntryKind {
+    Dir,
+    File,
+    Symlink,
+}
+
+struct CopyEntry {
+    source: String,
+    destination: String,
+    kind: EntryKind,
+    size: u64,
+    perm: Option<u32>,
+}
+
+pub(crate) struct CopyRequest<'a> {
+    pub source: &'a str,
+    pub destination: &'a str,
+    pub overwrite: bool,
+    pub allow_shell: bool,
+}
+
+// 返回使用的复制方式和复制的字节数
+pub(crate) fn copy_remote(
+    session: &Session,
+    request: &CopyRequest,
+    cancel_flag: &AtomicBool,
+    progress: &mut dyn ProgressListener,
+) -> Result<(CopyMethod, u64), SftpError> {
+    let sftp = session.sftp()
+        .map_err(|e| SftpError::ssh("action.create_sftp", e))?;
+
+    let source_stat = sftp.stat(Path::new(request.source))
+        .map_err(|e| SftpError::ssh("action.source_stat", e))?;
+
+    let destination_exists = sftp.lstat(Path::new(request.destination)).is_ok();
+    if destination_exists && !request.overwrite {
+        return Err(SftpError::new(ErrorCode::AlreadyExists, i18n::format("error.destination_exists", &[&request.destination])));
+    }
+
+    let mut entries = Vec::new();
+    collect_entries(&sftp, request.source, request.destination, &source_stat, &mut entries)?;
+    let total_size: u64 = entries.iter().map(|entry| entry.size).sum();
+    progress.on_progress(0, total_size);
+
+    let mut ext = ExtSftp::open(session)
+        .ok()
+        .filter(|ext| ext.supports("copy-data"));
+
+    // cp -a 在目标为已存在目录时会复制到其内部，因此只在目标不存在时使用
+    // 超时后远程 cp 可能仍在运行，此时不能再改用流式复制写同一个目标
+    if ext.is_none() && request.allow_shell && !destination_exists {
+        match shell_copy(session, &sftp, request.source, request.destination) {
+            Ok(()) => {
+                progress.on_progress(total_size, total_size);
+                return Ok((CopyMethod::Shell, total_size));
+            }
+            Err(e) if e.code == ErrorCode::Timeout => return Err(e),
+            Err(_) => {}
+        }
+    }
+
+    let mut server_copied_any = false;
+    let mut streamed_any = false;
+    let mut copied = 0u64;
+
+    for entry in &entries {
+        if cancel_flag.load(Ordering::SeqCst) {
+            return Err(SftpError::cancelled());
+        }
+
+        match entry.kind {
+            EntryKind::Dir => {
+                let path = Path::new(&entry.destination);
+                if sftp.stat(path).map(|stat| stat.is_dir()).unwrap_or(false) {
+                    continue;
+                }
+                sftp.mkdir(path, entry.perm.unwrap_or(0o755) as i32 & 0o7777)
+                    .map_err(|e| SftpError::ssh_at("action.create_dir", &entry.destination, e))?;
+            }
+            EntryKind::Symlink => {
+                let target = sftp.readlink(Path::new(&entry.source))
+                    .map_err(|e| SftpError::ssh_at("action.read_symlink", &entry.source, e))?;
+                if request.overwrite {
+                    let _ = sftp.unlink(Path::new(&entry.destination));
+                }
+                sftp.symlink(&target, Path::new(&entry.destination))
+                    .map_err(|e| SftpError::ssh_at("action.create_symlink", &entry.destination, e))?;
+            }
+            EntryKind::File => {
+                let result = ext.as_mut()
+                    .map(|ext| ext.copy_data(&entry.source, &entry.destination, entry.perm, entry.size, cancel_flag));
+                let server_copied = match result {
+                    Some(Ok(())) => true,
+                    Some(Err(e)) if e.code == ErrorCode::Cancelled => return Err(e),
+                    // 超时的请求仍在服务器上执行，其余文件改为经客户端中转
+                    Some(Err(e)) if e.code == ErrorCode::Timeout => {
+                        ext = None;
+                        false
+                    }
+                    _ => false,
+                };
+
+                if server_copied {
+                    server_copied_any = true;
+                    copied += entry.size;
+                    progress.on_progress(copied, total_size);
+                } else {
+                    streamed_any = true;
+                    copied = stream_copy(&sftp, entry, copied, total_size, cancel_flag, progress)?;
+                }
+            }
+        }
+    }
+
+    let method = match (server_copied_any, streamed_any) {
+        (true, true) => CopyMethod::Mixed,
+        (true, false) => CopyMethod::CopyData,
+        (false, true) => CopyMethod::Stream,
+        // 只有目录和符号链接，按可用的方式报告
+        (false, false) if ext.is_some() => CopyMethod::CopyData,
+        (false, false) => CopyMethod::Stream,
+    };
+
+    progress.on_progress(copied, total_size);
+    Ok((method, copied))
+}
+
+// 展开需要复制的条目，目录在其内容之前
+fn collect_entries(
+    sftp: &Sftp,
+    source: &str,
+    destination: &str,
+    stat: &ssh2::FileStat,
+    entries: &mut Vec<CopyEntry>,
+) -> Result<(), SftpError> {
+    let file_type = stat.file_type();
+    let kind = if file_type.is_dir() {
+        EntryKind::Dir
+    } else if file_type.is_symlink() {
+        EntryKind::Symlink
+    } else {
+        EntryKind::File
+    };
+
+    entries.push(CopyEntry {
+        source: source.to_string(),
+        destination: destination.to_string(),
+        size: if matches!(kind, EntryKind::File) { stat.size.unwrap_or(0) } else { 0 },
+        perm: stat.perm,
+        kind,
+    });
+
+    if file_type.is_dir() {
+        let children = sftp.readdir(Path::new(source))
+            .map_err(|e| SftpError::ssh_at("action.read_dir", source, e))?;
+
+        for (child_path, child_stat) in children {
+            let Some(name) = child_path.file_name().map(|n| n.to_string_lossy().to_string()) else {
+                continue;
+            };
+            collect_entries(
+                sftp,
+                &child_path.to_string_lossy(),
+                &join_remote_path(destination, &name),
+                &child_stat,
+                entries,
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+fn shell_copy(session: &Session, sftp: &Sftp, source: &str, destination: &str) -> Result<(), SftpError> {
+    let command = format!("cp -a -- {} {}", exec::shell_quote(source), exec::shell_quote(destination));
+    let output = exec::run(session, &command)?;
+
+    if output.exit_status != 0 {
+        return Err(SftpError::new(ErrorCode::Failure, i18n::format("error.shell_copy_failed", &[&output.stderr.trim()])));
+    }
+
+    // 只允许 SFTP 的账号执行命令时可能没有任何效果，需要确认目标确实已创建
+    sftp.lstat(Path::new(destination))
+        .map(|_| ())
+        .map_err(|_| SftpError::new(ErrorCode::Unsupported, i18n::text("error.shell_copy_no_output")))
+}
+
+fn stream_copy(
+    sftp: &Sftp,
+    entry: &CopyEntry,
+    mut copied: u64,
+    total_size: u64,
+    cancel_flag: &AtomicBool,
+    progress: &mut dyn ProgressListener,
+) -> Result<u64, SftpError> {
+    let mut source = sftp.open(Path::new(&entry.source))
+        .map_err(|e| SftpError::ssh_at("action.open_source", &entry.source, e))?;
+
+    let mut destination = sftp.open_mode(
+        Path::new(&entry.destination),
+        OpenFlags::WRITE | OpenFlags::CREATE | OpenFlags::TRUNCATE,
+        entry.perm.unwrap_or(0o644) as i32 & 0o7777,
+        OpenType::File,
+    ).map_err(|e| SftpError::ssh_at("action.create_destination", &entry.destination, e))?;
+
+    let mut buffer = vec![0u8; 32 * 1024];
+    let mut last_progress_update = Instant::now();
+
+    loop {
+        if cancel_flag.load(Ordering::SeqCst) {
+            return Err(SftpError::cancelled());
+        }
+
+        let n = source.read(&mut buffer)
+            .map_err(|e| SftpError::io_at("action.read_source", &entry.source, e))?;
+        if n == 0 {
+            break;
+        }
+
+        destination.write_all(&buffer[..n])
+            .map_err(|e| SftpError::io_at("action.write_destination", &entry.destination, e))?;
+        copied += n as u64;
+
+        if last_progress_update.elapsed() >= PROGRESS_INTERVAL {
+            progress.on_progress(copied, total_size);
+            last_progress_update = Instant::now();
+        }
+    }
+
+    Ok(copied)
+}
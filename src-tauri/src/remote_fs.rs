@@ -0,0 +1,81 @@
+// 文件浏览和传输所需的存储后端接口
+// 路径统一使用 / 分隔的字符串，由各实现自行解释
+
+use crate::error::{ErrorCode, SftpError};
+use crate::{i18n, FileInfo, SetStatOptions, SetStatSummary};
+use std::io::{self, Read, Write};
+
+/// 可浏览、可读写的文件系统
+///
+/// SFTP、FTP、SCP、WebDAV、S3 连接和本地磁盘都实现了该 trait，界面命令和传输逻辑只依赖这里的方法。
+pub trait RemoteFs {
+    /// 列出目录内容（不包含 . 和 ..），符号链接会解析目标
+    fn list(&self, path: &str) -> Result<Vec<FileInfo>, SftpError>;
+
+    /// 获取路径信息，跟随符号链接
+    fn stat(&self, path: &str) -> Result<FileInfo, SftpError>;
+
+    /// 打开文件用于读取
+    fn open_read(&self, path: &str) -> Result<Box<dyn Read + '_>, SftpError>;
+
+    /// 从指定偏移开始读取，用于断点续传；默认实现读取并丢弃前面的内容，支持定位或范围请求的后端会覆盖此方法
+    fn open_read_from(&self, path: &str, offset: u64) -> Result<Box<dyn Read + '_>, SftpError> {
+        let mut reader = self.open_read(path)?;
+        io::copy(&mut reader.by_ref().take(offset), &mut io::sink())
+            .map_err(|e| SftpError::io_at("action.read_remote_file", path, e))?;
+        Ok(reader)
+    }
+
+    /// 创建文件用于写入，已存在时截断
+    fn open_write(&self, path: &str) -> Result<Box<dyn Write + '_>, SftpError>;
+
+    /// 与 `open_write` 相同，但预先给出要写入的字节数；SCP 等需要提前声明大小的后端会覆盖此方法
+    fn open_write_sized(&self, path: &str, size: u64) -> Result<Box<dyn Write + '_>, SftpError> {
+        let _ = size;
+        self.open_write(path)
+    }
+
+    /// 打开已有文件，在末尾继续写入，用于断点续传；不支持追加的后端返回 `ErrorCode::Unsupported`
+    fn open_append(&self, path: &str) -> Result<Box<dyn Write + '_>, SftpError> {
+        let _ = path;
+        Err(SftpError::new(ErrorCode::Unsupported, i18n::text("error.append_unsupported")))
+    }
+
+    /// 创建目录，父目录必须已存在
+    fn mkdir(&self, path: &str) -> Result<(), SftpError>;
+
+    /// 删除文件或符号链接
+    fn remove_file(&self, path: &str) -> Result<(), SftpError>;
+
+    /// 删除空目录
+    fn remove_dir(&self, path: &str) -> Result<(), SftpError>;
+
+    /// 重命名或移动；overwrite 为 true 时替换已存在的目标（目录只有为空时才会被替换）
+    fn rename(&self, source: &str, destination: &str, overwrite: bool) -> Result<(), SftpError>;
+
+    /// 设置文件的修改时间（Unix 秒），同步后保持两侧一致；不支持的后端返回 `ErrorCode::Unsupported`
+    fn set_modified(&self, path: &str, modified: u64) -> Result<(), SftpError> {
+        let _ = (path, modified);
+        Err(SftpError::new(ErrorCode::Unsupported, i18n::text("error.set_modified_unsupported")))
+    }
+
+    /// 修改权限、所有者和时间戳，递归时不跟随子目录中的符号链接；
+    /// 单个路径的失败记录在返回的 `SetStatSummary` 中，不支持的后端返回 `ErrorCode::Unsupported`
+    fn set_attributes(&self, path: &str, options: &SetStatOptions) -> Result<SetStatSummary, SftpError> {
+        let _ = (path, options);
+        Err(SftpError::new(ErrorCode::Unsupported, i18n::text("error.set_attributes_unsupported")))
+    }
+
+    /// 读取符号链接指向的路径；不支持的后端返回 `ErrorCode::Unsupported`
+    fn read_link(&self, path: &str) -> Result<String, SftpError> {
+        let _ = path;
+        Err(SftpError::new(ErrorCode::Unsupported, i18n::text("error.symlink_unsupported")))
+    }
+
+    /// 创建符号链接 link_path -> target；不支持的后端返回 `ErrorCode::Unsupported`
+    fn symlink(&self, target: &str, link_path: &str) -> Result<(), SftpError> {
+        let _ = (target, link_path);
+        Err(SftpError::new(ErrorCode::Unsupported, i18n::text("error.symlink_unsupported")))
+    }
+}
+
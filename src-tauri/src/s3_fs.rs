@@ -0,0 +1,690 @@
+// S3 兼容对象存储的 RemoteFs 实现（AWS S3、MinIO 等）
+// 对象键按 / 划分目录：列目录使用 ListObjectsV2 的 prefix + delimiter，空目录以 "键/" 形式的空对象表示；
+// 请求使用 AWS Signature Version 4 签名，大文件分段上传
+
+use crate::error::{ErrorCode, SftpError};
+use crate::ops::join_remote_path;
+use crate::proxy::{self, ProxyConfig};
+use crate::remote_fs::RemoteFs;
+use crate::{i18n, FileInfo, FileType, TimeoutConfig};
+use hmac::{Hmac, Mac};
+use quick_xml::events::Event;
+use reqwest::blocking::{Client, Response};
+use reqwest::header::{AUTHORIZATION, ETAG};
+use reqwest::{Method, StatusCode, Url};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::{self, Read, Write};
+use std::time::Duration;
+
+// 分段上传的段大小；S3 要求除最后一段外不小于 5 MiB
+const PART_SIZE: usize = 8 * 1024 * 1024;
+
+// S3 连接的附加设置，访问密钥和私有密钥使用连接的用户名和密码
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct S3Options {
+    pub bucket: String,
+    pub region: String,
+    pub https: bool,
+    // 使用 bucket.host 形式的地址（AWS 推荐）；MinIO 等自建服务通常只支持路径形式
+    pub virtual_hosted: bool,
+    pub accept_invalid_certs: bool,
+}
+
+impl Default for S3Options {
+    fn default() -> Self {
+        S3Options {
+            bucket: String::new(),
+            region: "us-east-1".to_string(),
+            https: false,
+            virtual_hosted: false,
+            accept_invalid_certs: false,
+        }
+    }
+}
+
+/// 一个 S3 存储桶，目录由对象键的 / 分隔推出
+pub struct S3Fs {
+    client: Client,
+    // 存储桶的地址，路径以 / 结尾
+    base: Url,
+    bucket: String,
+    region: String,
+    access_key: String,
+    secret_key: String,
+    timeout: Option<Duration>,
+}
+
+// ListObjectsV2 结果中的一个对象
+struct S3Object {
+    key: String,
+    size: u64,
+    modified: Option<u64>,
+}
+
+#[derive(Default)]
+struct Listing {
+    objects: Vec<S3Object>,
+    prefixes: Vec<String>,
+}
+
+impl S3Fs {
+    /// 连接并验证密钥：列出存储桶中的一个对象
+    pub fn connect(
+        host: &str,
+        port: u16,
+        access_key: &str,
+        secret_key: &str,
+        options: &S3Options,
+        proxy: Option<&ProxyConfig>,
+        timeouts: &TimeoutConfig,
+    ) -> Result<Self, SftpError> {
+        let scheme = if options.https { "https" } else { "http" };
+        let base = if options.virtual_hosted {
+            format!("{}://{}.{}:{}/", scheme, options.bucket, host, port)
+        } else {
+            format!("{}://{}:{}/{}/", scheme, host, port, options.bucket)
+        };
+        let base = Url::parse(&base)
+            .map_err(|e| SftpError::new(ErrorCode::InvalidInput, i18n::format("error.invalid_url", &[&e])))?;
+
+        let client = proxy::http_client(proxy, timeouts, options.accept_invalid_certs)?;
+
+        let region = match options.region.trim() {
+            "" => "us-east-1".to_string(),
+            region => region.to_string(),
+        };
+        let fs = S3Fs {
+            client,
+            base,
+            bucket: options.bucket.clone(),
+            region,
+            access_key: access_key.to_string(),
+            secret_key: secret_key.to_string(),
+            timeout: (timeouts.operation_secs > 0).then(|| Duration::from_secs(timeouts.operation_secs)),
+        };
+
+        fs.send(Method::GET, "", &[("list-type", "2"), ("max-keys", "1")], &[], Vec::new(), "action.read_dir")
+            .map_err(|e| match e.code {
+                ErrorCode::AuthFailed | ErrorCode::Timeout | ErrorCode::NotFound | ErrorCode::PermissionDenied => e,
+                _ => e.with_code(ErrorCode::ConnectionFailed),
+            })?;
+
+        Ok(fs)
+    }
+
+    /// 存储桶名称
+    pub fn bucket(&self) -> &str {
+        &self.bucket
+    }
+
+    /// 服务器端复制文件或目录（逐个复制前缀下的对象），返回复制的字节数
+    ///
+    /// 单个对象最大 5 GiB，这是 CopyObject 的限制。
+    pub fn copy(&self, source: &str, destination: &str, overwrite: bool) -> Result<u64, SftpError> {
+        self.transfer(source, destination, overwrite, false)
+    }
+
+    // 复制 source 到 destination，remove_source 为 true 时复制完成后删除源对象（即重命名）
+    fn transfer(&self, source: &str, destination: &str, overwrite: bool, remove_source: bool) -> Result<u64, SftpError> {
+        let info = self.stat(source)?;
+        match self.stat(destination) {
+            Ok(_) if !overwrite => {
+                return Err(SftpError::new(ErrorCode::AlreadyExists, i18n::format("error.destination_exists", &[&destination])));
+            }
+            Ok(existing) if existing.is_dir => self.remove_dir(destination)?,
+            Ok(_) => {}
+            Err(e) if e.code == ErrorCode::NotFound => {}
+            Err(e) => return Err(e),
+        }
+
+        let (source_key, destination_key) = (object_key(source), object_key(destination));
+        if !info.is_dir {
+            self.copy_object(&source_key, &destination_key)?;
+            if remove_source {
+                self.send(Method::DELETE, &source_key, &[], &[], Vec::new(), "action.remove_file")?;
+            }
+            return Ok(info.size);
+        }
+
+        let source_prefix = format!("{}/", source_key);
+        let destination_prefix = format!("{}/", destination_key);
+        let objects = self.list_objects(&source_prefix, false, "action.read_dir")?.objects;
+
+        self.send(Method::PUT, &destination_prefix, &[], &[], Vec::new(), "action.create_dir")?;
+        let mut bytes_copied = 0;
+        for object in objects.iter().filter(|object| object.key != source_prefix) {
+            let relative = &object.key[source_prefix.len()..];
+            self.copy_object(&object.key, &format!("{}{}", destination_prefix, relative))?;
+            bytes_copied += object.size;
+        }
+
+        if remove_source {
+            for object in objects.iter().filter(|object| object.key != source_prefix) {
+                self.send(Method::DELETE, &object.key, &[], &[], Vec::new(), "action.remove_file")?;
+            }
+            self.send(Method::DELETE, &source_prefix, &[], &[], Vec::new(), "action.remove_dir")?;
+        }
+        Ok(bytes_copied)
+    }
+
+    fn copy_object(&self, source_key: &str, destination_key: &str) -> Result<(), SftpError> {
+        let copy_source = format!("/{}/{}", self.bucket, uri_encode(source_key, false));
+        let response = self.send(Method::PUT, destination_key, &[], &[("x-amz-copy-source", &copy_source)], Vec::new(), "action.copy")?;
+        // CopyObject 可能在 200 响应中返回错误
+        check_body(response, "action.copy", destination_key).map(|_| ())
+    }
+
+    // 对象键对应的地址，query 为已编码并排序的查询字符串
+    fn object_url(&self, key: &str, query: &str) -> Url {
+        let mut url = self.base.clone();
+        url.set_path(&format!("{}{}", self.base.path(), uri_encode(key, false)));
+        url.set_query((!query.is_empty()).then_some(query));
+        url
+    }
+
+    // 签名并发送请求，非 2xx 响应转换为错误
+    // 不带查询参数的 GET 是对象下载，不设置单次操作超时
+    fn send(
+        &self,
+        method: Method,
+        key: &str,
+        query: &[(&str, &str)],
+        headers: &[(&str, &str)],
+        body: Vec<u8>,
+        action: &'static str,
+    ) -> Result<Response, SftpError> {
+        let mut query: Vec<(String, String)> = query.iter()
+            .map(|(name, value)| (uri_encode(name, true), uri_encode(value, true)))
+            .collect();
+        query.sort();
+        let query = query.iter()
+            .map(|(name, value)| format!("{}={}", name, value))
+            .collect::<Vec<_>>()
+            .join("&");
+        let url = self.object_url(key, &query);
+
+        let payload_hash = hex(&Sha256::digest(&body));
+        let amz_date = chrono::Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+        let host = match url.port() {
+            Some(port) => format!("{}:{}", url.host_str().unwrap_or_default(), port),
+            None => url.host_str().unwrap_or_default().to_string(),
+        };
+
+        let mut signed: Vec<(String, String)> = headers.iter()
+            .map(|(name, value)| (name.to_ascii_lowercase(), value.trim().to_string()))
+            .collect();
+        signed.push(("host".to_string(), host));
+        signed.push(("x-amz-content-sha256".to_string(), payload_hash.clone()));
+        signed.push(("x-amz-date".to_string(), amz_date.clone()));
+        signed.sort();
+
+        let authorization = self.authorization(&method, &url, &query, &signed, &payload_hash, &amz_date);
+        let streaming = method == Method::GET && query.is_empty();
+
+        let mut request = self.client.request(method, url);
+        for (name, value) in signed.iter().filter(|(name, _)| name != "host") {
+            request = request.header(name.as_str(), value.as_str());
+        }
+        request = request.header(AUTHORIZATION, authorization).body(body);
+        if let (Some(timeout), false) = (self.timeout, streaming) {
+            request = request.timeout(timeout);
+        }
+
+        let path = format!("/{}", key);
+        let response = request.send()
+            .map_err(|e| SftpError::http(action, &path, e))?;
+        if !response.status().is_success() {
+            return Err(error_response(response, action, &path));
+        }
+        Ok(response)
+    }
+
+    // AWS Signature Version 4
+    fn authorization(&self, method: &Method, url: &Url, query: &str, headers: &[(String, String)], payload_hash: &str, amz_date: &str) -> String {
+        let canonical_headers: String = headers.iter()
+            .map(|(name, value)| format!("{}:{}\n", name, value))
+            .collect();
+        let signed_headers = headers.iter()
+            .map(|(name, _)| name.as_str())
+            .collect::<Vec<_>>()
+            .join(";");
+        let canonical_request = format!(
+            "{}\n{}\n{}\n{}\n{}\n{}",
+            method.as_str(), url.path(), query, canonical_headers, signed_headers, payload_hash
+        );
+
+        let date = &amz_date[..8];
+        let scope = format!("{}/{}/s3/aws4_request", date, self.region);
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            amz_date, scope, hex(&Sha256::digest(canonical_request.as_bytes()))
+        );
+
+        let signing_key = [date, self.region.as_str(), "s3", "aws4_request"]
+            .iter()
+            .fold(format!("AWS4{}", self.secret_key).into_bytes(), |key, part| hmac_sha256(&key, part.as_bytes()));
+        let signature = hex(&hmac_sha256(&signing_key, string_to_sign.as_bytes()));
+
+        format!(
+            "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+            self.access_key, scope, signed_headers, signature
+        )
+    }
+
+    // 列出前缀下的对象，自动翻页；delimiter 为 true 时按 / 归并为公共前缀
+    fn list_objects(&self, prefix: &str, delimiter: bool, action: &'static str) -> Result<Listing, SftpError> {
+        let mut listing = Listing::default();
+        let mut token: Option<String> = None;
+
+        loop {
+            let mut query = vec![("list-type", "2"), ("prefix", prefix)];
+            if delimiter {
+                query.push(("delimiter", "/"));
+            }
+            if let Some(token) = token.as_deref() {
+                query.push(("continuation-token", token));
+            }
+
+            let response = self.send(Method::GET, "", &query, &[], Vec::new(), action)?;
+            let body = response.text()
+                .map_err(|e| SftpError::http(action, prefix, e))?;
+
+            token = None;
+            let mut truncated = false;
+            let mut current: Option<S3Object> = None;
+            parse_xml(&body, |path, text| match (path, text) {
+                ([.., b"Contents"], None) => listing.objects.extend(current.take()),
+                ([.., b"Contents", b"Key"], Some(text)) => {
+                    current.get_or_insert_with(|| S3Object { key: String::new(), size: 0, modified: None }).key = text.to_string();
+                }
+                ([.., b"Contents", b"Size"], Some(text)) => {
+                    current.get_or_insert_with(|| S3Object { key: String::new(), size: 0, modified: None }).size = text.parse().unwrap_or(0);
+                }
+                ([.., b"Contents", b"LastModified"], Some(text)) => {
+                    current.get_or_insert_with(|| S3Object { key: String::new(), size: 0, modified: None }).modified =
+                        chrono::DateTime::parse_from_rfc3339(text).ok().map(|time| time.timestamp().max(0) as u64);
+                }
+                ([.., b"CommonPrefixes", b"Prefix"], Some(text)) => listing.prefixes.push(text.to_string()),
+                ([.., b"IsTruncated"], Some(text)) => truncated = text == "true",
+                ([.., b"NextContinuationToken"], Some(text)) => token = Some(text.to_string()),
+                _ => {}
+            }).map_err(|e| xml_error(action, e))?;
+
+            if !truncated || token.is_none() {
+                return Ok(listing);
+            }
+        }
+    }
+
+    // dir 下名称以 name_prefix 开头的条目；服务器忽略 delimiter 时由更深层的键推出子目录
+    fn entries(&self, dir: &str, name_prefix: &str, action: &'static str) -> Result<Vec<FileInfo>, SftpError> {
+        let dir_key = object_key(dir);
+        let prefix = if dir_key.is_empty() { String::new() } else { format!("{}/", dir_key) };
+        let listing = self.list_objects(&format!("{}{}", prefix, name_prefix), true, action)?;
+
+        let mut files = BTreeMap::new();
+        let mut dirs = BTreeSet::new();
+        for object in &listing.objects {
+            let Some(relative) = object.key.strip_prefix(&prefix) else { continue };
+            match relative.split_once('/') {
+                Some((name, _)) => {
+                    dirs.insert(name.to_string());
+                }
+                // 目录自身的标记对象
+                None if relative.is_empty() => {}
+                None => {
+                    files.insert(relative.to_string(), file_info(dir, relative, false, object.size, object.modified));
+                }
+            }
+        }
+        for common_prefix in &listing.prefixes {
+            if let Some((name, _)) = common_prefix.strip_prefix(&prefix).and_then(|relative| relative.split_once('/')) {
+                dirs.insert(name.to_string());
+            }
+        }
+
+        Ok(dirs.iter()
+            .filter(|name| !name.is_empty())
+            .map(|name| file_info(dir, name, true, 0, None))
+            .chain(files.into_values())
+            .collect())
+    }
+}
+
+// 浏览路径对应的对象键，根目录为空字符串
+fn object_key(path: &str) -> String {
+    path.trim_matches('/').to_string()
+}
+
+fn file_info(dir: &str, name: &str, is_dir: bool, size: u64, modified: Option<u64>) -> FileInfo {
+    let (file_type, perm) = if is_dir { (FileType::Dir, 0o755) } else { (FileType::File, 0o644) };
+
+    FileInfo {
+        name: name.to_string(),
+        path: join_remote_path(dir, name),
+        size,
+        is_dir,
+        file_type,
+        modified: crate::format_timestamp(modified),
+        accessed: None,
+        permissions: format!("{:o}", perm),
+        mode: crate::format_mode(file_type, perm),
+        uid: None,
+        gid: None,
+        is_symlink: false,
+        link_target: None,
+        target_is_dir: false,
+    }
+}
+
+// SigV4 的 URI 编码：只保留非保留字符，路径中的 / 按 encode_slash 决定
+fn uri_encode(text: &str, encode_slash: bool) -> String {
+    let mut encoded = String::with_capacity(text.len());
+    for byte in text.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => encoded.push(byte as char),
+            b'/' if !encode_slash => encoded.push('/'),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC 接受任意长度的密钥");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+// 遍历 XML，对每段文本以 (元素路径, Some(文本)) 回调，元素结束时以 (元素路径, None) 回调；元素名不含命名空间前缀
+fn parse_xml(body: &str, mut on_event: impl FnMut(&[&[u8]], Option<&str>)) -> Result<(), quick_xml::Error> {
+    let mut reader = quick_xml::Reader::from_str(body);
+    reader.config_mut().trim_text(true);
+    let mut stack: Vec<Vec<u8>> = Vec::new();
+
+    loop {
+        match reader.read_event()? {
+            Event::Start(start) => stack.push(start.local_name().as_ref().to_vec()),
+            Event::Text(text) => {
+                let text = text.unescape()?;
+                let path: Vec<&[u8]> = stack.iter().map(Vec::as_slice).collect();
+                on_event(&path, Some(&text));
+            }
+            Event::End(_) => {
+                let path: Vec<&[u8]> = stack.iter().map(Vec::as_slice).collect();
+                on_event(&path, None);
+                stack.pop();
+            }
+            Event::Eof => return Ok(()),
+            _ => {}
+        }
+    }
+}
+
+fn xml_error(action: &'static str, e: quick_xml::Error) -> SftpError {
+    SftpError::new(ErrorCode::Failure, i18n::format("error.failed", &[&i18n::text(action), &e]))
+}
+
+// 从错误响应体中取出 <Code> 和 <Message>；HEAD 等没有响应体的请求只能按状态码分类
+fn error_response(response: Response, action: &'static str, path: &str) -> SftpError {
+    let status = response.status();
+    let body = response.text().unwrap_or_default();
+    s3_error(&body, status, action, path)
+}
+
+fn s3_error(body: &str, status: StatusCode, action: &'static str, path: &str) -> SftpError {
+    let (mut code, mut message) = (String::new(), String::new());
+    let _ = parse_xml(body, |element, text| match (element, text) {
+        ([b"Error", b"Code"], Some(text)) => code = text.to_string(),
+        ([b"Error", b"Message"], Some(text)) => message = text.to_string(),
+        _ => {}
+    });
+    SftpError::s3(action, path, status, &code, &message)
+}
+
+// CompleteMultipartUpload 和 CopyObject 在 200 响应中也可能返回 <Error>
+fn check_body(response: Response, action: &'static str, key: &str) -> Result<String, SftpError> {
+    let status = response.status();
+    let body = response.text()
+        .map_err(|e| SftpError::http(action, key, e))?;
+    if body.contains("<Error>") {
+        return Err(s3_error(&body, status, action, &format!("/{}", key)));
+    }
+    Ok(body)
+}
+
+impl RemoteFs for S3Fs {
+    fn list(&self, path: &str) -> Result<Vec<FileInfo>, SftpError> {
+        let path = if path.is_empty() { "/" } else { path };
+        let files = self.entries(path, "", "action.read_dir")?;
+
+        // 前缀下没有对象时确认目录本身存在
+        if files.is_empty() && !object_key(path).is_empty() && !self.stat(path)?.is_dir {
+            return Err(SftpError::new(ErrorCode::InvalidInput, i18n::format("error.not_directory", &[&path])));
+        }
+        Ok(files)
+    }
+
+    fn stat(&self, path: &str) -> Result<FileInfo, SftpError> {
+        let key = object_key(path);
+        if key.is_empty() {
+            let mut info = file_info("/", "", true, 0, None);
+            info.name = "/".to_string();
+            info.path = "/".to_string();
+            return Ok(info);
+        }
+
+        let (parent, name) = match key.rsplit_once('/') {
+            Some((parent, name)) => (format!("/{}", parent), name),
+            None => ("/".to_string(), key.as_str()),
+        };
+        if let Some(mut info) = self.entries(&parent, name, "action.stat")?.into_iter().find(|info| info.name == name) {
+            info.path = path.to_string();
+            return Ok(info);
+        }
+
+        // 不列出目录标记的服务器上，空目录只能通过标记对象确认
+        match self.send(Method::HEAD, &format!("{}/", key), &[], &[], Vec::new(), "action.stat") {
+            Ok(_) => {
+                let mut info = file_info(&parent, name, true, 0, None);
+                info.path = path.to_string();
+                Ok(info)
+            }
+            Err(e) if e.code == ErrorCode::NotFound => {
+                Err(SftpError::new(ErrorCode::NotFound, i18n::format("error.path_not_found", &[&path])))
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    fn open_read(&self, path: &str) -> Result<Box<dyn Read + '_>, SftpError> {
+        self.open_read_from(path, 0)
+    }
+
+    // 使用范围请求从偏移处继续下载；服务器忽略 Range 时丢弃前面的内容
+    fn open_read_from(&self, path: &str, offset: u64) -> Result<Box<dyn Read + '_>, SftpError> {
+        let range = format!("bytes={}-", offset);
+        let headers: &[(&str, &str)] = if offset > 0 { &[("range", &range)] } else { &[] };
+        let mut response = self.send(Method::GET, &object_key(path), &[], headers, Vec::new(), "action.open_remote_file")?;
+
+        if offset > 0 && response.status() != StatusCode::PARTIAL_CONTENT {
+            io::copy(&mut response.by_ref().take(offset), &mut io::sink())
+                .map_err(|e| SftpError::io_at("action.read_remote_file", path, e))?;
+        }
+        Ok(Box::new(response))
+    }
+
+    fn open_write(&self, path: &str) -> Result<Box<dyn Write + '_>, SftpError> {
+        Ok(Box::new(S3Writer {
+            fs: self,
+            key: object_key(path),
+            buffer: Vec::new(),
+            upload_id: None,
+            etags: Vec::new(),
+            finished: false,
+        }))
+    }
+
+    fn mkdir(&self, path: &str) -> Result<(), SftpError> {
+        match self.stat(path) {
+            Ok(_) => return Err(SftpError::new(ErrorCode::AlreadyExists, i18n::format("error.destination_exists", &[&path]))),
+            Err(e) if e.code == ErrorCode::NotFound => {}
+            Err(e) => return Err(e),
+        }
+
+        self.send(Method::PUT, &format!("{}/", object_key(path)), &[], &[], Vec::new(), "action.create_dir")
+            .map(|_| ())
+    }
+
+    // DELETE 对不存在的键也返回成功，先确认路径存在且不是目录
+    fn remove_file(&self, path: &str) -> Result<(), SftpError> {
+        if self.stat(path)?.is_dir {
+            return Err(SftpError::new(ErrorCode::InvalidInput, i18n::format("error.is_directory", &[&path])));
+        }
+        self.send(Method::DELETE, &object_key(path), &[], &[], Vec::new(), "action.remove_file")
+            .map(|_| ())
+    }
+
+    fn remove_dir(&self, path: &str) -> Result<(), SftpError> {
+        if !self.list(path)?.is_empty() {
+            return Err(SftpError::new(ErrorCode::Failure, i18n::format("error.dir_not_empty", &[&path])));
+        }
+        self.send(Method::DELETE, &format!("{}/", object_key(path)), &[], &[], Vec::new(), "action.remove_dir")
+            .map(|_| ())
+    }
+
+    // S3 没有重命名操作，复制后删除源对象
+    fn rename(&self, source: &str, destination: &str, overwrite: bool) -> Result<(), SftpError> {
+        self.transfer(source, destination, overwrite, true)
+            .map(|_| ())
+    }
+}
+
+impl S3Fs {
+    fn create_multipart_upload(&self, key: &str) -> Result<String, SftpError> {
+        let response = self.send(Method::POST, key, &[("uploads", "")], &[], Vec::new(), "action.write_remote_file")?;
+        let body = check_body(response, "action.write_remote_file", key)?;
+
+        let mut upload_id = None;
+        parse_xml(&body, |path, text| {
+            if let ([.., b"UploadId"], Some(text)) = (path, text) {
+                upload_id = Some(text.to_string());
+            }
+        }).map_err(|e| xml_error("action.write_remote_file", e))?;
+
+        upload_id.ok_or_else(|| SftpError::new(ErrorCode::Failure, i18n::format("error.failed", &[&i18n::text("action.write_remote_file"), &body])))
+    }
+
+    fn upload_part(&self, key: &str, upload_id: &str, part_number: usize, data: Vec<u8>) -> Result<String, SftpError> {
+        let part_number = part_number.to_string();
+        let length = data.len().to_string();
+        let response = self.send(
+            Method::PUT,
+            key,
+            &[("partNumber", &part_number), ("uploadId", upload_id)],
+            &[("content-length", &length)],
+            data,
+            "action.write_remote_file",
+        )?;
+
+        Ok(response.headers()
+            .get(ETAG)
+            .and_then(|etag| etag.to_str().ok())
+            .unwrap_or_default()
+            .to_string())
+    }
+
+    fn complete_multipart_upload(&self, key: &str, upload_id: &str, etags: &[String]) -> Result<(), SftpError> {
+        let parts: String = etags.iter()
+            .enumerate()
+            .map(|(index, etag)| format!("<Part><PartNumber>{}</PartNumber><ETag>{}</ETag></Part>", index + 1, etag))
+            .collect();
+        let body = format!("<CompleteMultipartUpload>{}</CompleteMultipartUpload>", parts).into_bytes();
+
+        let response = self.send(Method::POST, key, &[("uploadId", upload_id)], &[], body, "action.write_remote_file")?;
+        check_body(response, "action.write_remote_file", key).map(|_| ())
+    }
+}
+
+// 数据先在内存中缓冲：不超过一段时 flush 用单个 PUT 上传，否则分段上传，flush 时完成
+struct S3Writer<'a> {
+    fs: &'a S3Fs,
+    key: String,
+    buffer: Vec<u8>,
+    upload_id: Option<String>,
+    etags: Vec<String>,
+    finished: bool,
+}
+
+impl S3Writer<'_> {
+    fn upload_part(&mut self, data: Vec<u8>) -> Result<(), SftpError> {
+        let upload_id = match &self.upload_id {
+            Some(upload_id) => upload_id.clone(),
+            None => self.upload_id.insert(self.fs.create_multipart_upload(&self.key)?).clone(),
+        };
+        let etag = self.fs.upload_part(&self.key, &upload_id, self.etags.len() + 1, data)?;
+        self.etags.push(etag);
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), SftpError> {
+        let data = std::mem::take(&mut self.buffer);
+        match self.upload_id.clone() {
+            None => {
+                let length = data.len().to_string();
+                self.fs.send(Method::PUT, &self.key, &[], &[("content-length", &length)], data, "action.write_remote_file")?;
+            }
+            Some(upload_id) => {
+                if !data.is_empty() {
+                    self.upload_part(data)?;
+                }
+                self.fs.complete_multipart_upload(&self.key, &upload_id, &self.etags)?;
+                self.upload_id = None;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Write for S3Writer<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.finished {
+            return Err(io::Error::new(io::ErrorKind::BrokenPipe, i18n::text("error.stream_closed")));
+        }
+
+        self.buffer.extend_from_slice(buf);
+        while self.buffer.len() >= PART_SIZE {
+            let part = self.buffer.drain(..PART_SIZE).collect();
+            self.upload_part(part)
+                .map_err(io::Error::other)?;
+        }
+        Ok(buf.len())
+    }
+
+    // flush 时上传剩余数据，之后不能再写入
+    fn flush(&mut self) -> io::Result<()> {
+        if self.finished {
+            return Ok(());
+        }
+        self.finished = true;
+        // 保留 S3 的错误分类，由 SftpError::io 取回
+        self.finish()
+            .map_err(io::Error::other)
+    }
+}
+
+// 未完成就被丢弃（如取消）时放弃分段上传，避免服务器保留已上传的段
+impl Drop for S3Writer<'_> {
+    fn drop(&mut self) {
+        if let Some(upload_id) = self.upload_id.take() {
+            let _ = self.fs.send(Method::DELETE, &self.key, &[("uploadId", &upload_id)], &[], Vec::new(), "action.write_remote_file");
+        }
+    }
+}
@@ -0,0 +1,227 @@
+// 服务器未启用 SFTP 子系统时的降级实现
+// 单个文件通过 SCP 传输，目录列表和文件操作通过 exec 执行 ls / mkdir / rm / mv 完成；
+// 账号不允许执行命令时只剩传输可用
+
+use crate::error::{ErrorCode, SftpError};
+use crate::exec::{self, shell_quote, ExecOutput};
+use crate::ftp_fs;
+use crate::remote_fs::RemoteFs;
+use crate::{i18n, FileInfo};
+use ssh2::{Channel, Session};
+use std::io::{self, Read, Write};
+use std::path::Path;
+use suppaftp::list::File;
+
+/// 基于 SCP 和远程命令的文件系统
+pub struct ScpFs<'a> {
+    session: &'a Session,
+}
+
+impl<'a> ScpFs<'a> {
+    pub fn new(session: &'a Session) -> Self {
+        ScpFs { session }
+    }
+
+    fn run(&self, action: &'static str, path: &str, command: &str) -> Result<ExecOutput, SftpError> {
+        let output = exec::run(self.session, command)?;
+        if output.exit_status != 0 {
+            return Err(command_error(action, path, &output));
+        }
+        Ok(output)
+    }
+
+    fn exists(&self, path: &str) -> Result<bool, SftpError> {
+        let command = format!("test -e {0} || test -L {0}", shell_quote(path));
+        Ok(exec::run(self.session, &command)?.exit_status == 0)
+    }
+}
+
+// 按 coreutils / busybox 的错误文本分类
+fn command_error(action: &'static str, path: &str, output: &ExecOutput) -> SftpError {
+    let stderr = output.stderr.trim();
+    let lower = stderr.to_lowercase();
+    let code = if lower.contains("no such file") {
+        ErrorCode::NotFound
+    } else if lower.contains("permission denied") || lower.contains("operation not permitted") {
+        ErrorCode::PermissionDenied
+    } else if lower.contains("file exists") {
+        ErrorCode::AlreadyExists
+    } else {
+        ErrorCode::Failure
+    };
+
+    let action = format!("{} [{}]", i18n::text(action), path);
+    SftpError::new(code, i18n::format("error.failed", &[&action, &stderr]))
+}
+
+// 解析 ls -l 的输出；GNU ls 会在权限位后附加 . 或 +（SELinux 上下文、ACL），解析前去掉
+fn parse_ls(stdout: &str) -> Vec<File> {
+    stdout.lines()
+        .filter_map(|line| {
+            let line = match line.char_indices().nth(10) {
+                Some((index, '.' | '+' | '@')) => format!("{}{}", &line[..index], &line[index + 1..]),
+                _ => line.to_string(),
+            };
+            File::try_from(line.as_str()).ok()
+        })
+        .filter(|file| file.name() != "." && file.name() != "..")
+        .collect()
+}
+
+impl RemoteFs for ScpFs<'_> {
+    fn list(&self, path: &str) -> Result<Vec<FileInfo>, SftpError> {
+        let path = if path.is_empty() { "/" } else { path };
+        let output = self.run("action.read_dir", path, &format!("LC_ALL=C ls -lan -- {}", shell_quote(path)))?;
+
+        Ok(parse_ls(&output.stdout)
+            .iter()
+            .map(|file| ftp_fs::file_info(path, file))
+            .collect())
+    }
+
+    fn stat(&self, path: &str) -> Result<FileInfo, SftpError> {
+        let output = self.run("action.stat", path, &format!("LC_ALL=C ls -ladnL -- {}", shell_quote(path)))?;
+        let file = parse_ls(&output.stdout)
+            .into_iter()
+            .next()
+            .ok_or_else(|| SftpError::new(ErrorCode::Failure, i18n::format("error.failed", &[&i18n::text("action.stat"), &output.stdout.trim()])))?;
+
+        let mut info = ftp_fs::file_info(path, &file);
+        info.name = Path::new(path)
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| path.to_string());
+        info.path = path.to_string();
+        Ok(info)
+    }
+
+    fn open_read(&self, path: &str) -> Result<Box<dyn Read + '_>, SftpError> {
+        let (channel, stat) = self.session.scp_recv(Path::new(path))
+            .map_err(|e| SftpError::ssh_at("action.open_remote_file", path, e))?;
+        Ok(Box::new(ScpReader { channel: Some(channel), remaining: stat.size() }))
+    }
+
+    // SCP 需要预先声明文件大小
+    fn open_write(&self, _path: &str) -> Result<Box<dyn Write + '_>, SftpError> {
+        Err(SftpError::new(ErrorCode::Unsupported, i18n::text("error.scp_size_required")))
+    }
+
+    fn open_write_sized(&self, path: &str, size: u64) -> Result<Box<dyn Write + '_>, SftpError> {
+        let channel = self.session.scp_send(Path::new(path), 0o644, size, None)
+            .map_err(|e| SftpError::ssh_at("action.create_remote_file", path, e))?;
+        Ok(Box::new(ScpWriter { channel: Some(channel) }))
+    }
+
+    fn mkdir(&self, path: &str) -> Result<(), SftpError> {
+        self.run("action.create_dir", path, &format!("mkdir -- {}", shell_quote(path)))
+            .map(|_| ())
+    }
+
+    fn remove_file(&self, path: &str) -> Result<(), SftpError> {
+        self.run("action.remove_file", path, &format!("rm -- {}", shell_quote(path)))
+            .map(|_| ())
+    }
+
+    fn remove_dir(&self, path: &str) -> Result<(), SftpError> {
+        self.run("action.remove_dir", path, &format!("rmdir -- {}", shell_quote(path)))
+            .map(|_| ())
+    }
+
+    // mv 遇到已存在的目录会把源移动到目录里面，因此先按 SFTP 的语义处理目标；
+    // 处理目标前先确认源存在，避免源不存在时已经删掉了目标
+    fn rename(&self, source: &str, destination: &str, overwrite: bool) -> Result<(), SftpError> {
+        if !self.exists(source)? {
+            return Err(SftpError::new(ErrorCode::NotFound, i18n::format("error.path_not_found", &[&source])));
+        }
+        if self.exists(destination)? {
+            if !overwrite {
+                return Err(SftpError::new(ErrorCode::AlreadyExists, i18n::format("error.destination_exists", &[&destination])));
+            }
+            let command = format!("test -d {0} && ! test -L {0}", shell_quote(destination));
+            if exec::run(self.session, &command)?.exit_status == 0 {
+                self.remove_dir(destination)?;
+            }
+        }
+
+        let command = format!("mv -f -- {} {}", shell_quote(source), shell_quote(destination));
+        self.run("action.rename", source, &command)
+            .map(|_| ())
+    }
+}
+
+// 正常结束 SCP 通道：发送 EOF 并等待远程 scp 退出
+fn close_channel(channel: &mut Channel) -> Result<(), ssh2::Error> {
+    channel.send_eof()?;
+    channel.wait_eof()?;
+    channel.close()?;
+    channel.wait_close()
+}
+
+// 通道中文件内容之后还有协议确认字节，只能按声明的大小读取
+struct ScpReader {
+    channel: Option<Channel>,
+    remaining: u64,
+}
+
+impl Read for ScpReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let Some(channel) = self.channel.as_mut() else {
+            return Ok(0);
+        };
+
+        if self.remaining == 0 {
+            let result = close_channel(channel);
+            self.channel = None;
+            return result.map(|_| 0).map_err(io::Error::other);
+        }
+
+        let limit = buf.len().min(self.remaining.min(usize::MAX as u64) as usize);
+        let n = channel.read(&mut buf[..limit])?;
+        if n == 0 {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, i18n::text("error.connection_lost")));
+        }
+        self.remaining -= n as u64;
+        Ok(n)
+    }
+}
+
+// 提前结束（如取消）时直接关闭通道
+impl Drop for ScpReader {
+    fn drop(&mut self) {
+        if let Some(mut channel) = self.channel.take() {
+            let _ = channel.close();
+        }
+    }
+}
+
+// flush 时结束传输并等待远程确认，之后不能再写入
+struct ScpWriter {
+    channel: Option<Channel>,
+}
+
+impl Write for ScpWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self.channel.as_mut() {
+            Some(channel) => channel.write(buf),
+            None => Err(io::Error::new(io::ErrorKind::BrokenPipe, i18n::text("error.stream_closed"))),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self.channel.take() {
+            Some(mut channel) => {
+                channel.flush()?;
+                close_channel(&mut channel).map_err(io::Error::other)
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+impl Drop for ScpWriter {
+    fn drop(&mut self) {
+        if let Some(mut channel) = self.channel.take() {
+            let _ = channel.close();
+        }
+    }
+}
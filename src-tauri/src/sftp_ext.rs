@@ -0,0 +1,378 @@
+// 在独立 SSH 通道上实现的最小 SFTP v3 客户端
+// libssh2 既不公开服务器声明的扩展列表，也不支持发送扩展请求，
+// 因此 copy-data、posix-rename 等扩展操作通过这里直接按协议收发数据包
+
+use crate::error::{ErrorCode, SftpError};
+use crate::i18n;
+use ssh2::{Channel, Session};
+use std::io::{Read, Write};
+
+const SSH_FXP_INIT: u8 = 1;
+const SSH_FXP_VERSION: u8 = 2;
+const SSH_FXP_STATUS: u8 = 101;
+const SSH_FXP_EXTENDED: u8 = 200;
+
+const SSH_FX_OK: u32 = 0;
+
+// 单个数据包的上限，防止异常长度导致分配过大内存
+const MAX_PACKET_LEN: usize = 256 * 1024;
+
+pub(crate) struct ExtSftp {
+    #[cfg(feature = "gui")]
+    session: Session,
+    channel: Channel,
+    extensions: Vec<String>,
+    next_id: u32,
+}
+
+impl ExtSftp {
+    pub(crate) fn open(session: &Session) -> Result<Self, SftpError> {
+        let mut channel = session.channel_session()
+            .map_err(|e| SftpError::ssh("action.open_channel", e))?;
+        channel.subsystem("sftp")
+            .map_err(|e| SftpError::ssh("action.start_sftp_subsystem", e))?;
+
+        let mut ext = ExtSftp {
+            #[cfg(feature = "gui")]
+            session: session.clone(),
+            channel,
+            extensions: Vec::new(),
+            next_id: 1,
+        };
+
+        let mut init = vec![SSH_FXP_INIT];
+        put_u32(&mut init, 3);
+        ext.send(&init)?;
+
+        let reply = ext.recv()?;
+        let mut reader = PacketReader::new(&reply);
+        if reader.u8()? != SSH_FXP_VERSION {
+            return Err(protocol_error("error.sftp_bad_version"));
+        }
+        reader.u32()?;
+
+        while !reader.is_empty() {
+            let name = reader.string()?;
+            reader.string()?;
+            ext.extensions.push(String::from_utf8_lossy(&name).to_string());
+        }
+
+        Ok(ext)
+    }
+
+    pub(crate) fn supports(&self, extension: &str) -> bool {
+        self.extensions.iter().any(|name| name == extension)
+    }
+
+    // 按 POSIX rename(2) 语义重命名（posix-rename@openssh.com 扩展），已存在的目标被原子替换
+    pub(crate) fn posix_rename(&mut self, source: &str, destination: &str) -> Result<(), SftpError> {
+        let id = self.next_id();
+        let mut packet = vec![SSH_FXP_EXTENDED];
+        put_u32(&mut packet, id);
+        put_string(&mut packet, b"posix-rename@openssh.com");
+        put_string(&mut packet, source.as_bytes());
+        put_string(&mut packet, destination.as_bytes());
+
+        self.request(id, &packet).and_then(|reply| expect_ok(&reply))
+    }
+
+    fn request(&mut self, id: u32, packet: &[u8]) -> Result<Vec<u8>, SftpError> {
+        self.send(packet)?;
+        let reply = self.recv()?;
+
+        let mut reader = PacketReader::new(&reply);
+        reader.u8()?;
+        if reader.u32()? != id {
+            return Err(protocol_error("error.sftp_id_mismatch"));
+        }
+
+        Ok(reply)
+    }
+
+    fn send(&mut self, packet: &[u8]) -> Result<(), SftpError> {
+        let mut frame = Vec::with_capacity(packet.len() + 4);
+        put_u32(&mut frame, packet.len() as u32);
+        frame.extend_from_slice(packet);
+
+        self.channel.write_all(&frame)
+            .and_then(|_| self.channel.flush())
+            .map_err(|e| SftpError::io("action.send_sftp_request", e))
+    }
+
+    fn recv(&mut self) -> Result<Vec<u8>, SftpError> {
+        let mut len = [0u8; 4];
+        self.channel.read_exact(&mut len)
+            .map_err(|e| SftpError::io("action.read_sftp_response", e))?;
+
+        let len = u32::from_be_bytes(len) as usize;
+        if len == 0 || len > MAX_PACKET_LEN {
+            return Err(protocol_error("error.sftp_bad_length"));
+        }
+
+        let mut packet = vec![0u8; len];
+        self.channel.read_exact(&mut packet)
+            .map_err(|e| SftpError::io("action.read_sftp_response", e))?;
+
+        Ok(packet)
+    }
+
+    fn next_id(&mut self) -> u32 {
+        let id = self.next_id;
+        self.next_id = self.next_id.wrapping_add(1);
+        id
+    }
+}
+
+impl Drop for ExtSftp {
+    fn drop(&mut self) {
+        let _ = self.channel.send_eof();
+        let _ = self.channel.close();
+    }
+}
+
+fn expect_ok(reply: &[u8]) -> Result<(), SftpError> {
+    let mut reader = PacketReader::new(reply);
+    if reader.u8()? != SSH_FXP_STATUS {
+        return Err(protocol_error("error.sftp_bad_reply"));
+    }
+    reader.u32()?;
+
+    match status_error(&mut reader)? {
+        None => Ok(()),
+        Some(e) => Err(e),
+    }
+}
+
+// 读取 STATUS 包中 id 之后的内容，状态为 OK 时返回 None
+fn status_error(reader: &mut PacketReader) -> Result<Option<SftpError>, SftpError> {
+    let code = reader.u32()?;
+    if code == SSH_FX_OK {
+        return Ok(None);
+    }
+
+    let message = reader.string()
+        .map(|m| String::from_utf8_lossy(&m).to_string())
+        .unwrap_or_default();
+    Ok(Some(SftpError::sftp_status(code as i32, i18n::format("error.sftp_status", &[&code, &message]))))
+}
+
+fn protocol_error(key: &'static str) -> SftpError {
+    SftpError::new(ErrorCode::Failure, i18n::text(key))
+}
+
+fn put_u32(buf: &mut Vec<u8>, value: u32) {
+    buf.extend_from_slice(&value.to_be_bytes());
+}
+
+fn put_string(buf: &mut Vec<u8>, value: &[u8]) {
+    put_u32(buf, value.len() as u32);
+    buf.extend_from_slice(value);
+}
+
+struct PacketReader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> PacketReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        PacketReader { data }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], SftpError> {
+        if self.data.len() < len {
+            return Err(protocol_error("error.sftp_truncated"));
+        }
+        let (head, rest) = self.data.split_at(len);
+        self.data = rest;
+        Ok(head)
+    }
+
+    fn u8(&mut self) -> Result<u8, SftpError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32, SftpError> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn string(&mut self) -> Result<Vec<u8>, SftpError> {
+        let len = self.u32()? as usize;
+        Ok(self.take(len)?.to_vec())
+    }
+}
+
+// copy-data 扩展只用于桌面端的服务器端复制（remote_copy）
+#[cfg(feature = "gui")]
+mod copy_data {
+    use super::*;
+    use std::io::ErrorKind;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::time::{Duration, Instant};
+
+    const SSH_FXP_OPEN: u8 = 3;
+    const SSH_FXP_CLOSE: u8 = 4;
+    const SSH_FXP_HANDLE: u8 = 102;
+
+    const SSH_FXF_READ: u32 = 0x01;
+    const SSH_FXF_WRITE: u32 = 0x02;
+    const SSH_FXF_CREAT: u32 = 0x08;
+    const SSH_FXF_TRUNC: u32 = 0x10;
+
+    const SSH_FILEXFER_ATTR_PERMISSIONS: u32 = 0x04;
+
+    // 等待回复时检查取消标志的间隔
+    const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+    // 估算期限时假定的最低复制速度（字节/秒）
+    const COPY_DATA_MIN_RATE: u64 = 1024 * 1024;
+
+    impl ExtSftp {
+        // 由服务器直接复制文件内容（copy-data 扩展），数据不经过客户端。
+        // 服务器复制完成后才会回复，期限为单次操作超时加上按最低速度复制 size 字节所需的时间；
+        // 超时返回 Timeout，此时请求仍在服务器上执行，这个通道不能再继续使用
+        pub(crate) fn copy_data(
+            &mut self,
+            source: &str,
+            destination: &str,
+            perm: Option<u32>,
+            size: u64,
+            cancel_flag: &AtomicBool,
+        ) -> Result<(), SftpError> {
+            let read_handle = self.open_handle(source, SSH_FXF_READ, None)?;
+            let write_handle = match self.open_handle(destination, SSH_FXF_WRITE | SSH_FXF_CREAT | SSH_FXF_TRUNC, perm) {
+                Ok(handle) => handle,
+                Err(e) => {
+                    let _ = self.close_handle(&read_handle);
+                    return Err(e);
+                }
+            };
+
+            let id = self.next_id();
+            let mut packet = vec![SSH_FXP_EXTENDED];
+            put_u32(&mut packet, id);
+            put_string(&mut packet, b"copy-data");
+            put_string(&mut packet, &read_handle);
+            put_u64(&mut packet, 0);
+            put_u64(&mut packet, 0); // 长度为 0 表示复制到文件末尾
+            put_string(&mut packet, &write_handle);
+            put_u64(&mut packet, 0);
+
+            // 操作超时为 0 表示不限时
+            let deadline = match self.session.timeout() {
+                0 => None,
+                millis => Some(Instant::now() + Duration::from_millis(millis as u64) + Duration::from_secs(size / COPY_DATA_MIN_RATE)),
+            };
+            let result = self.send(&packet)
+                .and_then(|_| self.poll_reply(id, deadline, cancel_flag))
+                .and_then(|reply| expect_ok(&reply));
+
+            let _ = self.close_handle(&read_handle);
+            let _ = self.close_handle(&write_handle);
+
+            result
+        }
+
+        fn open_handle(&mut self, path: &str, flags: u32, perm: Option<u32>) -> Result<Vec<u8>, SftpError> {
+            let id = self.next_id();
+            let mut packet = vec![SSH_FXP_OPEN];
+            put_u32(&mut packet, id);
+            put_string(&mut packet, path.as_bytes());
+            put_u32(&mut packet, flags);
+            match perm {
+                Some(perm) => {
+                    put_u32(&mut packet, SSH_FILEXFER_ATTR_PERMISSIONS);
+                    put_u32(&mut packet, perm & 0o7777);
+                }
+                None => put_u32(&mut packet, 0),
+            }
+
+            let reply = self.request(id, &packet)?;
+            let mut reader = PacketReader::new(&reply);
+            match reader.u8()? {
+                SSH_FXP_HANDLE => {
+                    reader.u32()?;
+                    reader.string()
+                }
+                SSH_FXP_STATUS => {
+                    reader.u32()?;
+                    Err(status_error(&mut reader)?
+                        .unwrap_or_else(|| protocol_error("error.sftp_bad_reply")))
+                }
+                _ => Err(protocol_error("error.sftp_bad_reply")),
+            }
+        }
+
+        fn close_handle(&mut self, handle: &[u8]) -> Result<(), SftpError> {
+            let id = self.next_id();
+            let mut packet = vec![SSH_FXP_CLOSE];
+            put_u32(&mut packet, id);
+            put_string(&mut packet, handle);
+
+            let reply = self.request(id, &packet)?;
+            expect_ok(&reply)
+        }
+
+        // 以非阻塞方式等待回复，期间检查取消标志和期限；调用方持有连接锁，阻塞模式切换不会影响其他通道
+        fn poll_reply(&mut self, id: u32, deadline: Option<Instant>, cancel_flag: &AtomicBool) -> Result<Vec<u8>, SftpError> {
+            let mut frame = Vec::new();
+            let mut buffer = [0u8; 4096];
+
+            loop {
+                // 只读取这一个回复需要的字节
+                let wanted = match frame.get(..4) {
+                    None => 4 - frame.len(),
+                    Some(len) => {
+                        let len = u32::from_be_bytes([len[0], len[1], len[2], len[3]]) as usize;
+                        if len == 0 || len > MAX_PACKET_LEN {
+                            return Err(protocol_error("error.sftp_bad_length"));
+                        }
+                        if frame.len() == 4 + len {
+                            break;
+                        }
+                        4 + len - frame.len()
+                    }
+                };
+
+                self.session.set_blocking(false);
+                let read = self.channel.read(&mut buffer[..wanted.min(4096)]);
+                self.session.set_blocking(true);
+
+                match read {
+                    Ok(0) => return Err(SftpError::io("action.read_sftp_response", ErrorKind::UnexpectedEof.into())),
+                    Ok(n) => {
+                        frame.extend_from_slice(&buffer[..n]);
+                        continue;
+                    }
+                    Err(e) if e.kind() == ErrorKind::WouldBlock => {}
+                    Err(e) => return Err(SftpError::io("action.read_sftp_response", e)),
+                }
+
+                if cancel_flag.load(Ordering::SeqCst) {
+                    return Err(SftpError::cancelled());
+                }
+                if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                    return Err(SftpError::new(ErrorCode::Timeout, i18n::text("error.copy_data_timeout")));
+                }
+                std::thread::sleep(POLL_INTERVAL);
+            }
+
+            let reply = frame.split_off(4);
+            let mut reader = PacketReader::new(&reply);
+            reader.u8()?;
+            if reader.u32()? != id {
+                return Err(protocol_error("error.sftp_id_mismatch"));
+            }
+
+            Ok(reply)
+        }
+    }
+
+    fn put_u64(buf: &mut Vec<u8>, value: u64) {
+        buf.extend_from_slice(&value.to_be_bytes());
+    }
+}
@@ -0,0 +1,257 @@
+// 基于 libssh2 SFTP 会话的 RemoteFs 实现
+
+use crate::error::{ErrorCode, SftpError};
+use crate::remote_fs::RemoteFs;
+use crate::sftp_ext::ExtSftp;
+use crate::{i18n, FileInfo, SetStatOptions, SetStatSummary};
+use ssh2::{OpenFlags, OpenType, RenameFlags, Session};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+/// 一个 SFTP 子系统通道上的文件系统
+pub struct SftpFs {
+    session: Session,
+    sftp: ssh2::Sftp,
+}
+
+impl SftpFs {
+    /// 在已认证的会话上打开 SFTP 通道
+    pub fn new(session: &Session) -> Result<Self, SftpError> {
+        let sftp = session.sftp()
+            .map_err(|e| SftpError::ssh("action.create_sftp", e))?;
+        Ok(SftpFs { session: session.clone(), sftp })
+    }
+
+    /// 底层的 SFTP 通道，用于 trait 未覆盖的操作（属性、符号链接等）
+    pub fn sftp(&self) -> &ssh2::Sftp {
+        &self.sftp
+    }
+}
+
+// SFTP v3 的 FAILURE 状态码，OpenSSH 在重命名目标已存在时返回它
+const SSH_FX_FAILURE: i32 = 4;
+
+// 目录条目的显示名称，兼容 Windows 服务器返回的盘符和 \ 分隔路径
+fn entry_name(entry_path: &Path) -> String {
+    if let Some(file_name) = entry_path.file_name().and_then(|n| n.to_str()) {
+        return file_name.to_string();
+    }
+
+    let path_str = entry_path.to_string_lossy();
+
+    // 检查是否是 Windows 盘符 (如 "C:", "D:" 等)
+    if path_str.len() == 2 && path_str.ends_with(':') {
+        i18n::format("msg.drive_name", &[&&path_str[0..1]])
+    } else if path_str.contains('\\') {
+        // Windows 路径，提取最后一部分
+        let parts: Vec<&str> = path_str.split('\\').collect();
+        let last_part = parts.last().map(|s| s.to_string()).unwrap_or_else(|| path_str.to_string());
+        if last_part.is_empty() {
+            path_str.to_string()
+        } else {
+            last_part
+        }
+    } else {
+        // 使用完整路径作为名称
+        path_str.to_string()
+    }
+}
+
+// 对路径应用属性修改；递归时与 chmod -R 一样不跟随子目录中的符号链接
+fn apply_setstat(
+    sftp: &ssh2::Sftp,
+    path: &Path,
+    options: &SetStatOptions,
+    follow_links: bool,
+    summary: &mut SetStatSummary,
+) {
+    let current = if follow_links { sftp.stat(path) } else { sftp.lstat(path) };
+    let current = match current {
+        Ok(stat) => stat,
+        Err(e) => {
+            summary.errors.push(SftpError::ssh_at("action.stat", &path.to_string_lossy(), e));
+            return;
+        }
+    };
+
+    if current.file_type().is_symlink() {
+        return;
+    }
+
+    match sftp.setstat(path, options.to_stat(&current)) {
+        Ok(()) => summary.updated += 1,
+        Err(e) => summary.errors.push(SftpError::ssh_at("action.set_attributes", &path.to_string_lossy(), e)),
+    }
+
+    if options.recursive && current.is_dir() {
+        match sftp.readdir(path) {
+            Ok(entries) => {
+                for (child, _) in entries {
+                    apply_setstat(sftp, &child, options, false, summary);
+                }
+            }
+            Err(e) => summary.errors.push(SftpError::ssh_at("action.read_dir", &path.to_string_lossy(), e)),
+        }
+    }
+}
+
+impl RemoteFs for SftpFs {
+    fn list(&self, path: &str) -> Result<Vec<FileInfo>, SftpError> {
+        // 验证路径格式
+        let normalized_path = if path.is_empty() || path == "/" {
+            "/"
+        } else {
+            path
+        };
+
+        let entries = self.sftp.readdir(Path::new(normalized_path))
+            .map_err(|e| SftpError::ssh_at("action.read_dir", normalized_path, e))?;
+
+        let mut files = Vec::new();
+        for (entry_path, stat) in entries.iter() {
+            let name = entry_name(entry_path);
+            tracing::trace!(entry = ?entry_path, name = %name, "目录条目");
+
+            let mut file_info = FileInfo::from_stat(name, entry_path.to_string_lossy().to_string(), stat);
+            if file_info.is_symlink {
+                file_info.resolve_symlink(&self.sftp, entry_path);
+            }
+            files.push(file_info);
+        }
+
+        Ok(files)
+    }
+
+    fn stat(&self, path: &str) -> Result<FileInfo, SftpError> {
+        let stat = self.sftp.stat(Path::new(path))
+            .map_err(|e| SftpError::ssh_at("action.stat", path, e))?;
+
+        Ok(FileInfo::from_stat(entry_name(Path::new(path)), path.to_string(), &stat))
+    }
+
+    fn open_read(&self, path: &str) -> Result<Box<dyn Read + '_>, SftpError> {
+        let file = self.sftp.open(Path::new(path))
+            .map_err(|e| SftpError::ssh_at("action.open_remote_file", path, e))?;
+        Ok(Box::new(file))
+    }
+
+    fn open_read_from(&self, path: &str, offset: u64) -> Result<Box<dyn Read + '_>, SftpError> {
+        let mut file = self.sftp.open(Path::new(path))
+            .map_err(|e| SftpError::ssh_at("action.open_remote_file", path, e))?;
+        file.seek(SeekFrom::Start(offset))
+            .map_err(|e| SftpError::io_at("action.read_remote_file", path, e))?;
+        Ok(Box::new(file))
+    }
+
+    fn open_write(&self, path: &str) -> Result<Box<dyn Write + '_>, SftpError> {
+        let file = self.sftp.create(Path::new(path))
+            .map_err(|e| SftpError::ssh_at("action.create_remote_file", path, e))?;
+        Ok(Box::new(file))
+    }
+
+    // 部分服务器不支持 APPEND 标志，按当前大小定位到末尾
+    fn open_append(&self, path: &str) -> Result<Box<dyn Write + '_>, SftpError> {
+        let mut file = self.sftp.open_mode(Path::new(path), OpenFlags::WRITE, 0o644, OpenType::File)
+            .map_err(|e| SftpError::ssh_at("action.open_remote_file", path, e))?;
+        let size = file.stat()
+            .map_err(|e| SftpError::ssh_at("action.stat", path, e))?
+            .size
+            .unwrap_or(0);
+        file.seek(SeekFrom::Start(size))
+            .map_err(|e| SftpError::io_at("action.write_remote_file", path, e))?;
+        Ok(Box::new(file))
+    }
+
+    fn mkdir(&self, path: &str) -> Result<(), SftpError> {
+        self.sftp.mkdir(Path::new(path), 0o755)
+            .map_err(|e| SftpError::ssh_at("action.create_dir", path, e))
+    }
+
+    fn remove_file(&self, path: &str) -> Result<(), SftpError> {
+        self.sftp.unlink(Path::new(path))
+            .map_err(|e| SftpError::ssh_at("action.remove_file", path, e))
+    }
+
+    fn remove_dir(&self, path: &str) -> Result<(), SftpError> {
+        self.sftp.rmdir(Path::new(path))
+            .map_err(|e| SftpError::ssh_at("action.remove_dir", path, e))
+    }
+
+    // SFTP v3 服务器会忽略覆盖标志，目标存在时直接失败。服务器声明 posix-rename@openssh.com
+    // 扩展（如 OpenSSH）时用它原子替换；否则只在确认失败原因是目标已存在后才移除目标重试
+    // （目录只有为空时才会被替换）
+    fn rename(&self, source: &str, destination: &str, overwrite: bool) -> Result<(), SftpError> {
+        if overwrite {
+            if let Ok(mut ext) = ExtSftp::open(&self.session) {
+                if ext.supports("posix-rename@openssh.com") {
+                    return ext.posix_rename(source, destination);
+                }
+            }
+        }
+
+        let (source_path, destination_path) = (Path::new(source), Path::new(destination));
+        let flags = if overwrite {
+            RenameFlags::OVERWRITE | RenameFlags::ATOMIC | RenameFlags::NATIVE
+        } else {
+            RenameFlags::ATOMIC | RenameFlags::NATIVE
+        };
+
+        let err = match self.sftp.rename(source_path, destination_path, Some(flags)) {
+            Ok(()) => return Ok(()),
+            Err(e) => SftpError::ssh_at("action.rename", source, e),
+        };
+
+        // 源不存在、权限不足、连接断开等失败都不能动目标
+        let destination_exists = err.code == ErrorCode::AlreadyExists || err.sftp_status == Some(SSH_FX_FAILURE);
+        if !overwrite || !destination_exists || self.sftp.lstat(source_path).is_err() {
+            return Err(err);
+        }
+
+        let existing = self.sftp.lstat(destination_path)
+            .map_err(|_| err)?;
+
+        if existing.is_dir() {
+            self.sftp.rmdir(destination_path)
+                .map_err(|e| SftpError::ssh_at("action.replace_dir", destination, e))?;
+        } else {
+            self.sftp.unlink(destination_path)
+                .map_err(|e| SftpError::ssh_at("action.replace_file", destination, e))?;
+        }
+
+        self.sftp.rename(source_path, destination_path, Some(flags))
+            .map_err(|e| SftpError::ssh_at("action.rename", source, e))
+    }
+
+    // SFTP v3 的时间属性需要同时给出访问时间，沿用原有的访问时间
+    fn set_modified(&self, path: &str, modified: u64) -> Result<(), SftpError> {
+        let stat = self.sftp.stat(Path::new(path))
+            .map_err(|e| SftpError::ssh_at("action.stat", path, e))?;
+        let attributes = ssh2::FileStat {
+            size: None,
+            uid: None,
+            gid: None,
+            perm: None,
+            atime: Some(stat.atime.unwrap_or(modified)),
+            mtime: Some(modified),
+        };
+        self.sftp.setstat(Path::new(path), attributes)
+            .map_err(|e| SftpError::ssh_at("action.set_modified", path, e))
+    }
+
+    fn set_attributes(&self, path: &str, options: &SetStatOptions) -> Result<SetStatSummary, SftpError> {
+        let mut summary = SetStatSummary::default();
+        apply_setstat(&self.sftp, Path::new(path), options, true, &mut summary);
+        Ok(summary)
+    }
+
+    fn read_link(&self, path: &str) -> Result<String, SftpError> {
+        self.sftp.readlink(Path::new(path))
+            .map(|target| target.to_string_lossy().to_string())
+            .map_err(|e| SftpError::ssh_at("action.read_symlink", path, e))
+    }
+
+    fn symlink(&self, target: &str, link_path: &str) -> Result<(), SftpError> {
+        self.sftp.symlink(Path::new(target), Path::new(link_path))
+            .map_err(|e| SftpError::ssh_at("action.create_symlink", link_path, e))
+    }
+}
@@ -0,0 +1,205 @@
+// 动态端口转发（ssh -D）：本地 SOCKS5 代理，每个请求通过 direct-tcpip 通道连到目标地址
+
+use crate::forward::{self, ForwardInfo, ForwardKind, Pipe, PipeSource};
+use crate::error::SftpError;
+use crate::commands::ssh_session;
+use ssh2::Session;
+use std::io::{Read, Write};
+use std::net::{Ipv4Addr, Ipv6Addr, TcpListener, TcpStream};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::time::Duration;
+
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+const SOCKS_VERSION: u8 = 0x05;
+const AUTH_NONE: u8 = 0x00;
+const AUTH_PASSWORD: u8 = 0x02;
+const AUTH_UNACCEPTABLE: u8 = 0xff;
+const CMD_CONNECT: u8 = 0x01;
+const ATYP_IPV4: u8 = 0x01;
+const ATYP_DOMAIN: u8 = 0x03;
+const ATYP_IPV6: u8 = 0x04;
+const REPLY_SUCCEEDED: u8 = 0x00;
+const REPLY_GENERAL_FAILURE: u8 = 0x01;
+const REPLY_COMMAND_NOT_SUPPORTED: u8 = 0x07;
+const REPLY_ADDRESS_NOT_SUPPORTED: u8 = 0x08;
+
+// SOCKS 侧的用户名/密码认证（RFC 1929）
+#[derive(Clone)]
+pub(crate) struct SocksCredentials {
+    pub username: String,
+    pub password: String,
+}
+
+// 已完成握手、等待打开通道的请求
+struct SocksRequest {
+    stream: TcpStream,
+    host: String,
+    port: u16,
+}
+
+struct SocksSource {
+    listener: TcpListener,
+    credentials: Option<SocksCredentials>,
+    ready_sender: Sender<SocksRequest>,
+    ready_receiver: Receiver<SocksRequest>,
+}
+
+impl PipeSource for SocksSource {
+    fn accept(&mut self, session: &Session) -> Vec<Pipe> {
+        // 握手需要等待客户端数据，放到独立线程中进行，避免阻塞其他转发连接
+        while let Ok((stream, _)) = self.listener.accept() {
+            let credentials = self.credentials.clone();
+            let sender = self.ready_sender.clone();
+            std::thread::spawn(move || {
+                match handshake(stream, credentials.as_ref()) {
+                    Ok(Some(request)) => {
+                        let _ = sender.send(request);
+                    }
+                    Ok(None) => {}
+                    Err(e) => tracing::debug!("SOCKS 握手失败: {}", e),
+                }
+            });
+        }
+
+        let mut pipes = Vec::new();
+        while let Ok(mut request) = self.ready_receiver.try_recv() {
+            match session.channel_direct_tcpip(&request.host, request.port, None) {
+                Ok(channel) => {
+                    if send_reply(&mut request.stream, REPLY_SUCCEEDED).is_err() {
+                        continue;
+                    }
+                    match Pipe::new(request.stream, channel) {
+                        Ok(pipe) => pipes.push(pipe),
+                        Err(e) => tracing::warn!("转发连接初始化失败: {}", e),
+                    }
+                }
+                Err(e) => {
+                    tracing::warn!(target_host = %request.host, target_port = request.port, "打开转发通道失败: {}", e);
+                    let _ = send_reply(&mut request.stream, REPLY_GENERAL_FAILURE);
+                }
+            }
+        }
+
+        pipes
+    }
+}
+
+// 启动 SOCKS5 代理，bind_port 为 0 时自动分配端口
+pub(crate) fn start(
+    app_handle: tauri::AppHandle,
+    connection_id: String,
+    bind_address: String,
+    bind_port: u16,
+    credentials: Option<SocksCredentials>,
+) -> Result<ForwardInfo, SftpError> {
+    if ssh_session(&connection_id).is_none() {
+        return Err(SftpError::not_connected(&connection_id));
+    }
+
+    let listener = TcpListener::bind((bind_address.as_str(), bind_port))
+        .map_err(|e| SftpError::io("action.listen_local", e))?;
+    listener.set_nonblocking(true)
+        .map_err(|e| SftpError::io("action.listen_local", e))?;
+    let bind_port = listener.local_addr()
+        .map(|addr| addr.port())
+        .unwrap_or(bind_port);
+
+    let info = ForwardInfo {
+        id: forward::next_forward_id(&connection_id),
+        connection_id,
+        kind: ForwardKind::Dynamic,
+        bind_address,
+        bind_port,
+        target_host: String::new(),
+        target_port: 0,
+        bytes_in: 0,
+        bytes_out: 0,
+        active_connections: 0,
+        total_connections: 0,
+    };
+
+    let (ready_sender, ready_receiver) = mpsc::channel();
+    let source = SocksSource {
+        listener,
+        credentials,
+        ready_sender,
+        ready_receiver,
+    };
+
+    Ok(forward::spawn(app_handle, info, Box::new(source)))
+}
+
+// 完成 SOCKS5 协商并读取 CONNECT 请求；请求被拒绝时返回 None
+fn handshake(mut stream: TcpStream, credentials: Option<&SocksCredentials>) -> std::io::Result<Option<SocksRequest>> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(HANDSHAKE_TIMEOUT))?;
+    stream.set_write_timeout(Some(HANDSHAKE_TIMEOUT))?;
+
+    let [version, method_count] = read_array::<2>(&mut stream)?;
+    if version != SOCKS_VERSION {
+        return Ok(None);
+    }
+    let mut methods = vec![0u8; method_count as usize];
+    stream.read_exact(&mut methods)?;
+
+    let method = if credentials.is_some() { AUTH_PASSWORD } else { AUTH_NONE };
+    if !methods.contains(&method) {
+        stream.write_all(&[SOCKS_VERSION, AUTH_UNACCEPTABLE])?;
+        return Ok(None);
+    }
+    stream.write_all(&[SOCKS_VERSION, method])?;
+
+    if let Some(credentials) = credentials {
+        let [_auth_version, username_len] = read_array::<2>(&mut stream)?;
+        let username = read_vec(&mut stream, username_len as usize)?;
+        let [password_len] = read_array::<1>(&mut stream)?;
+        let password = read_vec(&mut stream, password_len as usize)?;
+
+        let accepted = username == credentials.username.as_bytes()
+            && password == credentials.password.as_bytes();
+        stream.write_all(&[0x01, if accepted { 0x00 } else { 0x01 }])?;
+        if !accepted {
+            return Ok(None);
+        }
+    }
+
+    let [_version, command, _reserved, address_type] = read_array::<4>(&mut stream)?;
+    let host = match address_type {
+        ATYP_IPV4 => Ipv4Addr::from(read_array::<4>(&mut stream)?).to_string(),
+        ATYP_IPV6 => Ipv6Addr::from(read_array::<16>(&mut stream)?).to_string(),
+        ATYP_DOMAIN => {
+            let [len] = read_array::<1>(&mut stream)?;
+            String::from_utf8_lossy(&read_vec(&mut stream, len as usize)?).to_string()
+        }
+        _ => {
+            send_reply(&mut stream, REPLY_ADDRESS_NOT_SUPPORTED)?;
+            return Ok(None);
+        }
+    };
+    let port = u16::from_be_bytes(read_array::<2>(&mut stream)?);
+
+    if command != CMD_CONNECT {
+        send_reply(&mut stream, REPLY_COMMAND_NOT_SUPPORTED)?;
+        return Ok(None);
+    }
+
+    Ok(Some(SocksRequest { stream, host, port }))
+}
+
+// 回复请求结果，绑定地址统一填 0.0.0.0:0
+fn send_reply(stream: &mut TcpStream, reply: u8) -> std::io::Result<()> {
+    stream.write_all(&[SOCKS_VERSION, reply, 0x00, ATYP_IPV4, 0, 0, 0, 0, 0, 0])
+}
+
+fn read_array<const N: usize>(stream: &mut TcpStream) -> std::io::Result<[u8; N]> {
+    let mut buf = [0u8; N];
+    stream.read_exact(&mut buf)?;
+    Ok(buf)
+}
+
+fn read_vec(stream: &mut TcpStream, len: usize) -> std::io::Result<Vec<u8>> {
+    let mut buf = vec![0u8; len];
+    stream.read_exact(&mut buf)?;
+    Ok(buf)
+}
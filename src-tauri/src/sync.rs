@@ -0,0 +1,444 @@
+// 目录同步：比较两侧的目录树生成同步计划，确认后按计划执行
+// 两侧都是 RemoteFs，左侧通常是本机目录，也可以是另一个连接；计划中的路径相对于各自的根目录
+
+use crate::error::{ErrorCode, SftpError};
+use crate::i18n;
+use crate::ops::{self, join_remote_path, ProgressListener, TransferRequest};
+use crate::remote_fs::RemoteFs;
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::io::Read;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+// FAT 和部分 FTP 服务器只有 2 秒精度，差值在此范围内视为同一时间
+const MTIME_TOLERANCE: u64 = 2;
+
+/// 同步方向
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncDirection {
+    /// 左侧覆盖右侧
+    #[default]
+    Push,
+    /// 右侧覆盖左侧
+    Pull,
+    /// 双向，较新的一侧覆盖另一侧；不会删除文件
+    TwoWay,
+}
+
+/// 生成同步计划的选项
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SyncOptions {
+    pub direction: SyncDirection,
+    /// 排除规则（glob），同时匹配相对路径和文件名，被排除的目录不再展开
+    pub exclude: Vec<String>,
+    /// 单向同步时删除目标一侧多出的文件和目录
+    pub delete_extraneous: bool,
+    /// 大小相同时比较 SHA-256，而不是修改时间
+    pub checksum: bool,
+}
+
+/// 计划中的一项操作
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncAction {
+    /// 从左侧复制到右侧
+    Upload,
+    /// 从右侧复制到左侧
+    Download,
+    DeleteLeft,
+    DeleteRight,
+    /// 无法自动决定，执行时跳过
+    Conflict,
+}
+
+/// 生成该项操作的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncReason {
+    /// 另一侧不存在
+    Missing,
+    /// 目标一侧多出的条目
+    Extraneous,
+    SizeDiffers,
+    Modified,
+    ChecksumDiffers,
+    /// 一侧是文件，另一侧是目录
+    TypeMismatch,
+    /// 双向同步时两侧都有改动，无法判断哪一侧较新
+    BothModified,
+}
+
+/// 某一侧条目的状态；modified 为 Unix 秒
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SyncEntry {
+    pub is_dir: bool,
+    pub size: u64,
+    pub modified: Option<u64>,
+}
+
+/// 计划中的一项；path 为相对路径，以 / 分隔；目录在其内容之前
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncItem {
+    pub path: String,
+    pub action: SyncAction,
+    pub reason: SyncReason,
+    pub left: Option<SyncEntry>,
+    pub right: Option<SyncEntry>,
+}
+
+/// 同步计划，可交给界面预览，剔除不需要的项后再执行
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncPlan {
+    pub left_root: String,
+    pub right_root: String,
+    pub items: Vec<SyncItem>,
+}
+
+/// 执行结果统计
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SyncSummary {
+    pub uploaded: usize,
+    pub downloaded: usize,
+    pub deleted: usize,
+    pub conflicts: usize,
+    pub bytes: u64,
+}
+
+/// 比较两侧目录，生成同步计划；不修改任何文件
+///
+/// 根目录不存在时视为空目录。符号链接不参与同步。
+pub fn plan(
+    left_fs: &dyn RemoteFs,
+    left_root: &str,
+    right_fs: &dyn RemoteFs,
+    right_root: &str,
+    options: &SyncOptions,
+    cancel_flag: &AtomicBool,
+) -> Result<SyncPlan, SftpError> {
+    let exclude = build_exclude(&options.exclude)?;
+    let left = scan(left_fs, left_root, &exclude, cancel_flag)?;
+    let right = scan(right_fs, right_root, &exclude, cancel_flag)?;
+
+    // 按路径分段排序，目录的内容紧跟在目录之后
+    let mut paths: Vec<&String> = left.keys().chain(right.keys()).collect();
+    paths.sort_by(|a, b| a.split('/').cmp(b.split('/')));
+    paths.dedup();
+
+    let mut items = Vec::new();
+    // 整个目录被删除或冲突时，其内容不再单独列出
+    let mut skipped_dir: Option<String> = None;
+
+    for path in paths {
+        if cancel_flag.load(Ordering::SeqCst) {
+            return Err(SftpError::cancelled());
+        }
+        if skipped_dir.as_ref().is_some_and(|dir| path.starts_with(dir.as_str())) {
+            continue;
+        }
+
+        let left_entry = left.get(path).copied();
+        let right_entry = right.get(path).copied();
+        let decision = match (left_entry, right_entry) {
+            (Some(l), Some(r)) => compare(left_fs, left_root, right_fs, right_root, path, l, r, options, cancel_flag)?,
+            (Some(_), None) => match options.direction {
+                SyncDirection::Push | SyncDirection::TwoWay => Some((SyncAction::Upload, SyncReason::Missing)),
+                SyncDirection::Pull => options.delete_extraneous.then_some((SyncAction::DeleteLeft, SyncReason::Extraneous)),
+            },
+            (None, Some(_)) => match options.direction {
+                SyncDirection::Pull | SyncDirection::TwoWay => Some((SyncAction::Download, SyncReason::Missing)),
+                SyncDirection::Push => options.delete_extraneous.then_some((SyncAction::DeleteRight, SyncReason::Extraneous)),
+            },
+            (None, None) => None,
+        };
+
+        let Some((action, reason)) = decision else { continue };
+        let is_dir = [left_entry, right_entry].iter().flatten().any(|entry| entry.is_dir);
+        if is_dir && matches!(action, SyncAction::DeleteLeft | SyncAction::DeleteRight | SyncAction::Conflict) {
+            skipped_dir = Some(format!("{}/", path));
+        }
+
+        items.push(SyncItem {
+            path: path.clone(),
+            action,
+            reason,
+            left: left_entry,
+            right: right_entry,
+        });
+    }
+
+    Ok(SyncPlan {
+        left_root: left_root.to_string(),
+        right_root: right_root.to_string(),
+        items,
+    })
+}
+
+/// 按计划执行同步，返回统计结果；冲突项跳过
+///
+/// 进度按需要复制的文件总大小计算。复制后尽量把目标的修改时间设为与源相同，
+/// 后端不支持时保留服务器时间。
+pub fn apply(
+    left_fs: &dyn RemoteFs,
+    right_fs: &dyn RemoteFs,
+    plan: &SyncPlan,
+    cancel_flag: &AtomicBool,
+    progress: &mut dyn ProgressListener,
+) -> Result<SyncSummary, SftpError> {
+    let total_size: u64 = plan.items.iter()
+        .filter_map(|item| match item.action {
+            SyncAction::Upload => item.left,
+            SyncAction::Download => item.right,
+            _ => None,
+        })
+        .filter(|entry| !entry.is_dir)
+        .map(|entry| entry.size)
+        .sum();
+    progress.on_progress(0, total_size);
+
+    if plan.items.iter().any(|item| item.action == SyncAction::Upload) {
+        ops::ensure_dir(right_fs, &plan.right_root)?;
+    }
+    if plan.items.iter().any(|item| item.action == SyncAction::Download) {
+        ops::ensure_dir(left_fs, &plan.left_root)?;
+    }
+
+    let mut summary = SyncSummary::default();
+    let mut done = 0u64;
+
+    for item in &plan.items {
+        if cancel_flag.load(Ordering::SeqCst) {
+            return Err(SftpError::cancelled());
+        }
+
+        let left_path = join_remote_path(&plan.left_root, &item.path);
+        let right_path = join_remote_path(&plan.right_root, &item.path);
+
+        match item.action {
+            SyncAction::Upload | SyncAction::Download => {
+                let (source_fs, source, entry, destination_fs, destination) = if item.action == SyncAction::Upload {
+                    (left_fs, &left_path, item.left, right_fs, &right_path)
+                } else {
+                    (right_fs, &right_path, item.right, left_fs, &left_path)
+                };
+                let Some(entry) = entry else { continue };
+
+                if entry.is_dir {
+                    ops::ensure_dir(destination_fs, destination)?;
+                } else {
+                    let base = done;
+                    let mut progress = |transferred: u64, _total: u64| progress.on_progress(base + transferred, total_size);
+                    summary.bytes += copy_file(source_fs, source, destination_fs, destination, entry, cancel_flag, &mut progress)?;
+                    done += entry.size;
+                }
+
+                if item.action == SyncAction::Upload {
+                    summary.uploaded += 1;
+                } else {
+                    summary.downloaded += 1;
+                }
+            }
+            SyncAction::DeleteLeft => {
+                remove_tree(left_fs, &left_path)?;
+                summary.deleted += 1;
+            }
+            SyncAction::DeleteRight => {
+                remove_tree(right_fs, &right_path)?;
+                summary.deleted += 1;
+            }
+            SyncAction::Conflict => summary.conflicts += 1,
+        }
+    }
+
+    progress.on_progress(done, total_size);
+    Ok(summary)
+}
+
+fn build_exclude(patterns: &[String]) -> Result<GlobSet, SftpError> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns.iter().map(|pattern| pattern.trim()).filter(|pattern| !pattern.is_empty()) {
+        let glob = Glob::new(pattern)
+            .map_err(|e| SftpError::new(ErrorCode::InvalidInput, i18n::format("error.invalid_glob", &[&pattern, &e.kind()])))?;
+        builder.add(glob);
+    }
+    builder.build()
+        .map_err(|e| SftpError::new(ErrorCode::InvalidInput, i18n::format("error.invalid_glob", &[&patterns.join(", "), &e])))
+}
+
+// 递归列出目录，键为相对路径
+fn scan(
+    fs: &dyn RemoteFs,
+    root: &str,
+    exclude: &GlobSet,
+    cancel_flag: &AtomicBool,
+) -> Result<HashMap<String, SyncEntry>, SftpError> {
+    let mut entries = HashMap::new();
+    match fs.stat(root) {
+        Ok(info) if info.is_dir => {}
+        Ok(_) => return Err(SftpError::new(ErrorCode::InvalidInput, i18n::format("error.not_directory", &[&root]))),
+        Err(e) if e.code == ErrorCode::NotFound => return Ok(entries),
+        Err(e) => return Err(e),
+    }
+
+    let mut pending = vec![(root.to_string(), String::new())];
+    while let Some((dir, relative_dir)) = pending.pop() {
+        if cancel_flag.load(Ordering::SeqCst) {
+            return Err(SftpError::cancelled());
+        }
+
+        for child in fs.list(&dir)? {
+            let relative = if relative_dir.is_empty() {
+                child.name.clone()
+            } else {
+                format!("{}/{}", relative_dir, child.name)
+            };
+
+            if exclude.is_match(&relative) || exclude.is_match(&child.name) {
+                continue;
+            }
+            if child.is_symlink {
+                tracing::debug!(path = %child.path, "同步时跳过符号链接");
+                continue;
+            }
+
+            let modified = child.modified.as_deref()
+                .and_then(|modified| chrono::DateTime::parse_from_rfc3339(modified).ok())
+                .and_then(|time| u64::try_from(time.timestamp()).ok());
+            entries.insert(relative.clone(), SyncEntry {
+                is_dir: child.is_dir,
+                size: if child.is_dir { 0 } else { child.size },
+                modified,
+            });
+
+            if child.is_dir {
+                pending.push((child.path, relative));
+            }
+        }
+    }
+
+    Ok(entries)
+}
+
+// 比较两侧都存在的条目，返回需要执行的操作；相同时返回 None
+#[allow(clippy::too_many_arguments)]
+fn compare(
+    left_fs: &dyn RemoteFs,
+    left_root: &str,
+    right_fs: &dyn RemoteFs,
+    right_root: &str,
+    path: &str,
+    left: SyncEntry,
+    right: SyncEntry,
+    options: &SyncOptions,
+    cancel_flag: &AtomicBool,
+) -> Result<Option<(SyncAction, SyncReason)>, SftpError> {
+    if left.is_dir != right.is_dir {
+        return Ok(Some((SyncAction::Conflict, SyncReason::TypeMismatch)));
+    }
+    if left.is_dir {
+        return Ok(None);
+    }
+
+    let reason = if left.size != right.size {
+        SyncReason::SizeDiffers
+    } else if options.checksum {
+        let left_hash = checksum(left_fs, &join_remote_path(left_root, path), cancel_flag)?;
+        let right_hash = checksum(right_fs, &join_remote_path(right_root, path), cancel_flag)?;
+        if left_hash == right_hash {
+            return Ok(None);
+        }
+        SyncReason::ChecksumDiffers
+    } else {
+        // 不保留修改时间的后端上，复制得到的文件总是比源新，单向同步时不应再次复制
+        let unchanged = match (left.modified, right.modified, options.direction) {
+            (Some(l), Some(r), SyncDirection::Push) => r + MTIME_TOLERANCE >= l,
+            (Some(l), Some(r), SyncDirection::Pull) => l + MTIME_TOLERANCE >= r,
+            (Some(l), Some(r), SyncDirection::TwoWay) => l.abs_diff(r) <= MTIME_TOLERANCE,
+            _ => true,
+        };
+        if unchanged {
+            return Ok(None);
+        }
+        SyncReason::Modified
+    };
+
+    let action = match options.direction {
+        SyncDirection::Push => SyncAction::Upload,
+        SyncDirection::Pull => SyncAction::Download,
+        SyncDirection::TwoWay => match (left.modified, right.modified) {
+            (Some(l), Some(r)) if l > r + MTIME_TOLERANCE => SyncAction::Upload,
+            (Some(l), Some(r)) if r > l + MTIME_TOLERANCE => SyncAction::Download,
+            _ => return Ok(Some((SyncAction::Conflict, SyncReason::BothModified))),
+        },
+    };
+    Ok(Some((action, reason)))
+}
+
+fn checksum(fs: &dyn RemoteFs, path: &str, cancel_flag: &AtomicBool) -> Result<[u8; 32], SftpError> {
+    let mut reader = fs.open_read(path)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = [0u8; 32 * 1024];
+
+    loop {
+        if cancel_flag.load(Ordering::SeqCst) {
+            return Err(SftpError::cancelled());
+        }
+        let n = reader.read(&mut buffer)
+            .map_err(|e| SftpError::io_at("action.read_remote_file", path, e))?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buffer[..n]);
+    }
+
+    Ok(hasher.finalize().into())
+}
+
+// 复制单个文件并同步修改时间，返回传输的字节数
+fn copy_file(
+    source_fs: &dyn RemoteFs,
+    source: &str,
+    destination_fs: &dyn RemoteFs,
+    destination: &str,
+    entry: SyncEntry,
+    cancel_flag: &AtomicBool,
+    progress: &mut dyn ProgressListener,
+) -> Result<u64, SftpError> {
+    let request = TransferRequest {
+        source,
+        destination,
+        overwrite: true,
+        resume: false,
+    };
+    let bytes = ops::transfer(source_fs, destination_fs, &request, cancel_flag, progress)?;
+
+    if let Some(modified) = entry.modified {
+        match destination_fs.set_modified(destination, modified) {
+            Ok(()) => {}
+            Err(e) if e.code == ErrorCode::Unsupported => {}
+            Err(e) => tracing::warn!(path = %destination, code = ?e.code, "设置修改时间失败: {}", e),
+        }
+    }
+
+    Ok(bytes)
+}
+
+// 删除文件或整个目录
+fn remove_tree(fs: &dyn RemoteFs, path: &str) -> Result<(), SftpError> {
+    let info = fs.stat(path)?;
+    if !info.is_dir || info.is_symlink {
+        return fs.remove_file(path);
+    }
+
+    for child in fs.list(path)? {
+        if child.is_dir && !child.is_symlink {
+            remove_tree(fs, &child.path)?;
+        } else {
+            fs.remove_file(&child.path)?;
+        }
+    }
+    fs.remove_dir(path)
+}
@@ -0,0 +1,191 @@
+// 基于已建立 SSH 会话的交互式终端
+// 会话的阻塞模式是整个会话共享的，因此每次轮询都在持有连接锁的情况下
+// 临时切换为非阻塞模式，保证不会与其他命令的 SFTP 操作交错
+
+use crate::commands::ssh_session;
+use crate::error::{ErrorCode, SftpError};
+use crate::i18n;
+use base64::{engine::general_purpose, Engine as _};
+use ssh2::Channel;
+use std::collections::HashMap;
+use std::io::{ErrorKind, Read, Write};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::Emitter;
+
+const IDLE_POLL_INTERVAL: Duration = Duration::from_millis(20);
+const BUSY_POLL_INTERVAL: Duration = Duration::from_millis(2);
+
+pub(crate) enum TerminalCommand {
+    Input(Vec<u8>),
+    Resize(u32, u32),
+    Close,
+}
+
+struct TerminalHandle {
+    connection_id: String,
+    sender: Sender<TerminalCommand>,
+}
+
+static TERMINALS: std::sync::LazyLock<Mutex<HashMap<String, TerminalHandle>>> =
+    std::sync::LazyLock::new(|| Mutex::new(HashMap::new()));
+
+static NEXT_TERMINAL_ID: AtomicU64 = AtomicU64::new(1);
+
+// 在连接上打开一个带 PTY 的 shell，返回终端 ID
+pub(crate) fn open(
+    app_handle: tauri::AppHandle,
+    connection_id: String,
+    cols: u32,
+    rows: u32,
+) -> Result<String, SftpError> {
+    let channel = {
+        let connection = ssh_session(&connection_id)
+            .ok_or_else(|| SftpError::not_connected(&connection_id))?;
+        let session = connection.lock().unwrap();
+
+        let mut channel = session.channel_session()
+            .map_err(|e| SftpError::ssh("action.open_channel", e))?;
+        channel.request_pty("xterm-256color", None, Some((cols, rows, 0, 0)))
+            .map_err(|e| SftpError::ssh("action.request_pty", e))?;
+        channel.shell()
+            .map_err(|e| SftpError::ssh("action.start_shell", e))?;
+        channel
+    };
+
+    let terminal_id = format!("{}-term-{}", connection_id, NEXT_TERMINAL_ID.fetch_add(1, Ordering::SeqCst));
+    let (sender, receiver) = mpsc::channel();
+
+    TERMINALS.lock().unwrap().insert(terminal_id.clone(), TerminalHandle {
+        connection_id: connection_id.clone(),
+        sender,
+    });
+
+    let id = terminal_id.clone();
+    std::thread::spawn(move || {
+        tracing::info!(connection_id = %connection_id, terminal_id = %id, "终端已打开");
+        let exit_status = pump(&app_handle, &id, &connection_id, channel, receiver);
+        TERMINALS.lock().unwrap().remove(&id);
+        tracing::info!(connection_id = %connection_id, terminal_id = %id, exit_status = ?exit_status, "终端已关闭");
+
+        let _ = app_handle.emit("terminal_exit", serde_json::json!({
+            "terminal_id": id,
+            "exit_status": exit_status
+        }));
+    });
+
+    Ok(terminal_id)
+}
+
+pub(crate) fn send(terminal_id: &str, command: TerminalCommand) -> Result<(), SftpError> {
+    let terminals = TERMINALS.lock().unwrap();
+    let terminal = terminals.get(terminal_id)
+        .ok_or_else(|| SftpError::new(ErrorCode::NotFound, i18n::format("error.terminal_not_found", &[&terminal_id])))?;
+
+    terminal.sender.send(command)
+        .map_err(|_| SftpError::new(ErrorCode::NotFound, i18n::format("error.terminal_closed", &[&terminal_id])))
+}
+
+pub(crate) fn list(connection_id: &str) -> Vec<String> {
+    TERMINALS.lock().unwrap()
+        .iter()
+        .filter(|(_, terminal)| terminal.connection_id == connection_id)
+        .map(|(id, _)| id.clone())
+        .collect()
+}
+
+// 在后台线程中转发终端输入输出，返回远程 shell 的退出状态
+fn pump(
+    app_handle: &tauri::AppHandle,
+    terminal_id: &str,
+    connection_id: &str,
+    mut channel: Channel,
+    receiver: Receiver<TerminalCommand>,
+) -> Option<i32> {
+    let mut pending_input: Vec<u8> = Vec::new();
+    let mut buffer = [0u8; 16 * 1024];
+
+    loop {
+        let mut resize = None;
+        let mut closing = false;
+        loop {
+            match receiver.try_recv() {
+                Ok(TerminalCommand::Input(data)) => pending_input.extend_from_slice(&data),
+                Ok(TerminalCommand::Resize(cols, rows)) => resize = Some((cols, rows)),
+                Ok(TerminalCommand::Close) | Err(TryRecvError::Disconnected) => {
+                    closing = true;
+                    break;
+                }
+                Err(TryRecvError::Empty) => break,
+            }
+        }
+
+        let Some(connection) = ssh_session(connection_id) else {
+            // 连接已断开
+            return None;
+        };
+        let session = connection.lock().unwrap();
+
+        if closing {
+            let _ = channel.send_eof();
+            let _ = channel.close();
+            return channel.exit_status().ok();
+        }
+
+        if let Some((cols, rows)) = resize {
+            let _ = channel.request_pty_size(cols, rows, None, None);
+        }
+
+        session.set_blocking(false);
+
+        let mut output = Vec::new();
+        let mut failed = false;
+
+        if !pending_input.is_empty() {
+            match channel.write(&pending_input) {
+                Ok(n) => {
+                    pending_input.drain(..n);
+                }
+                Err(e) if e.kind() == ErrorKind::WouldBlock => {}
+                Err(_) => failed = true,
+            }
+        }
+
+        while !failed {
+            match channel.read(&mut buffer) {
+                Ok(0) => break,
+                Ok(n) => output.extend_from_slice(&buffer[..n]),
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(_) => failed = true,
+            }
+        }
+
+        let finished = failed || channel.eof();
+        session.set_blocking(true);
+
+        let exit_status = if finished {
+            let _ = channel.close();
+            let _ = channel.wait_close();
+            channel.exit_status().ok()
+        } else {
+            None
+        };
+        drop(session);
+
+        let busy = !output.is_empty();
+        if busy {
+            let _ = app_handle.emit("terminal_output", serde_json::json!({
+                "terminal_id": terminal_id,
+                "data": general_purpose::STANDARD.encode(&output)
+            }));
+        }
+
+        if finished {
+            return exit_status;
+        }
+
+        std::thread::sleep(if busy { BUSY_POLL_INTERVAL } else { IDLE_POLL_INTERVAL });
+    }
+}
@@ -0,0 +1,657 @@
+// WebDAV 的 RemoteFs 实现（如 Nextcloud 文件共享）
+// 浏览路径 / 对应服务器上的 base_path；认证方式由首次 401 响应决定，支持 Basic 和 Digest (MD5)
+
+use crate::error::{ErrorCode, SftpError};
+use crate::ops::join_remote_path;
+use crate::proxy::{self, ProxyConfig};
+use crate::remote_fs::RemoteFs;
+use crate::{i18n, FileInfo, FileType, TimeoutConfig};
+use md5::{Digest, Md5};
+use quick_xml::events::Event;
+use reqwest::blocking::{Body, Client, RequestBuilder, Response};
+use reqwest::header::{AUTHORIZATION, WWW_AUTHENTICATE};
+use reqwest::{Method, StatusCode, Url};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::io::{self, Read, Write};
+use std::sync::mpsc::{self, Receiver, SyncSender};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+const PROPFIND_BODY: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<d:propfind xmlns:d="DAV:"><d:prop><d:resourcetype/><d:getcontentlength/><d:getlastmodified/></d:prop></d:propfind>"#;
+
+// WebDAV 连接的附加设置
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WebDavOptions {
+    pub https: bool,
+    // 服务器上 WebDAV 根目录的路径，如 Nextcloud 的 /remote.php/dav/files/<用户名>
+    pub base_path: String,
+    // 接受自签名或不匹配的证书
+    pub accept_invalid_certs: bool,
+}
+
+#[derive(Debug, Clone)]
+struct DigestChallenge {
+    realm: String,
+    nonce: String,
+    opaque: Option<String>,
+    qop_auth: bool,
+    md5_sess: bool,
+    // 同一 nonce 下的请求计数
+    nc: u32,
+}
+
+#[derive(Debug, Clone)]
+enum Auth {
+    None,
+    Basic,
+    Digest(DigestChallenge),
+}
+
+/// 一个 WebDAV 服务器上的文件系统
+pub struct WebDavFs {
+    client: Client,
+    // WebDAV 根目录，路径以 / 结尾
+    root: Url,
+    username: String,
+    password: String,
+    auth: RefCell<Auth>,
+    timeout: Option<Duration>,
+}
+
+// PROPFIND 响应中的一项
+struct DavEntry {
+    path: String,
+    is_dir: bool,
+    size: u64,
+    modified: Option<u64>,
+}
+
+impl WebDavFs {
+    /// 连接并验证账号：对根目录发送一次 PROPFIND
+    pub fn connect(
+        host: &str,
+        port: u16,
+        username: &str,
+        password: &str,
+        options: &WebDavOptions,
+        proxy: Option<&ProxyConfig>,
+        timeouts: &TimeoutConfig,
+    ) -> Result<Self, SftpError> {
+        let scheme = if options.https { "https" } else { "http" };
+        let base_path = options.base_path.trim_matches('/');
+        let root = Url::parse(&format!("{}://{}:{}/{}", scheme, host, port, base_path))
+            .map_err(|e| SftpError::new(ErrorCode::InvalidInput, i18n::format("error.invalid_url", &[&e])))?;
+
+        let client = proxy::http_client(proxy, timeouts, options.accept_invalid_certs)?;
+
+        let mut fs = WebDavFs {
+            client,
+            root,
+            username: username.to_string(),
+            password: password.to_string(),
+            auth: RefCell::new(Auth::None),
+            timeout: (timeouts.operation_secs > 0).then(|| Duration::from_secs(timeouts.operation_secs)),
+        };
+        fs.root = fs.url("/", true);
+
+        fs.propfind("/", true, 0)
+            .map_err(|e| match e.code {
+                ErrorCode::AuthFailed | ErrorCode::Timeout | ErrorCode::NotFound => e,
+                _ => e.with_code(ErrorCode::ConnectionFailed),
+            })?;
+
+        Ok(fs)
+    }
+
+    /// WebDAV 根目录的地址
+    pub fn root_url(&self) -> &str {
+        self.root.as_str()
+    }
+
+    /// 服务器端复制，目录递归复制
+    pub fn copy(&self, source: &str, destination: &str, overwrite: bool) -> Result<(), SftpError> {
+        let destination_url = self.url(destination, false);
+        let response = self.send(Method::from_bytes(b"COPY").unwrap(), &self.url(source, false), "action.copy", source, |request| {
+            request
+                .header("Destination", destination_url.as_str())
+                .header("Overwrite", if overwrite { "T" } else { "F" })
+                .header("Depth", "infinity")
+        })?;
+
+        match response.status() {
+            StatusCode::PRECONDITION_FAILED => Err(SftpError::new(ErrorCode::AlreadyExists, i18n::format("error.destination_exists", &[&destination]))),
+            status if status.is_success() => Ok(()),
+            status => Err(SftpError::http_status("action.copy", source, status)),
+        }
+    }
+
+    // 浏览路径对应的地址，collection 为 true 时以 / 结尾
+    fn url(&self, path: &str, collection: bool) -> Url {
+        let mut url = self.root.clone();
+        if let Ok(mut segments) = url.path_segments_mut() {
+            segments.pop_if_empty();
+            segments.extend(path.split('/').filter(|segment| !segment.is_empty()));
+            if collection {
+                segments.push("");
+            }
+        }
+        url
+    }
+
+    // 由服务器返回的 href 得到浏览路径
+    fn browse_path(&self, href: &str) -> Option<String> {
+        let url = self.root.join(href).ok()?;
+        let path = percent_decode(url.path());
+        let root = percent_decode(self.root.path());
+        let relative = path.strip_prefix(root.trim_end_matches('/'))?;
+
+        let relative = relative.trim_end_matches('/');
+        Some(if relative.is_empty() { "/".to_string() } else { relative.to_string() })
+    }
+
+    fn authorization(&self, method: &Method, url: &Url) -> Option<String> {
+        let mut auth = self.auth.borrow_mut();
+        match &mut *auth {
+            Auth::None => None,
+            Auth::Basic => {
+                use base64::{engine::general_purpose, Engine as _};
+                let credentials = format!("{}:{}", self.username, self.password);
+                Some(format!("Basic {}", general_purpose::STANDARD.encode(credentials)))
+            }
+            Auth::Digest(challenge) => Some(digest_authorization(challenge, &self.username, &self.password, method, url)),
+        }
+    }
+
+    // 发送请求；收到 401 时按质询更新认证方式后重试一次（请求体必须可以重复发送）
+    // GET 的响应体是流式读取的，整体超时会中断大文件下载，因此不设置
+    fn send(
+        &self,
+        method: Method,
+        url: &Url,
+        action: &'static str,
+        path: &str,
+        configure: impl Fn(RequestBuilder) -> RequestBuilder,
+    ) -> Result<Response, SftpError> {
+        let streaming = method == Method::GET;
+        let mut retried = false;
+        loop {
+            let mut request = configure(self.client.request(method.clone(), url.clone()));
+            if let (Some(timeout), false) = (self.timeout, streaming) {
+                request = request.timeout(timeout);
+            }
+            if let Some(authorization) = self.authorization(&method, url) {
+                request = request.header(AUTHORIZATION, authorization);
+            }
+
+            let response = request.send()
+                .map_err(|e| SftpError::http(action, path, e))?;
+            if response.status() != StatusCode::UNAUTHORIZED || retried {
+                return match response.status() {
+                    StatusCode::UNAUTHORIZED => Err(SftpError::new(ErrorCode::AuthFailed, i18n::text("error.auth_failed"))),
+                    _ => Ok(response),
+                };
+            }
+
+            match parse_challenge(&response) {
+                Some(auth) => *self.auth.borrow_mut() = auth,
+                None => return Err(SftpError::new(ErrorCode::AuthFailed, i18n::text("error.auth_failed"))),
+            }
+            retried = true;
+        }
+    }
+
+    fn propfind(&self, path: &str, collection: bool, depth: u8) -> Result<Vec<DavEntry>, SftpError> {
+        let action = if depth == 0 { "action.stat" } else { "action.read_dir" };
+        let response = self.send(Method::from_bytes(b"PROPFIND").unwrap(), &self.url(path, collection), action, path, |request| {
+            request
+                .header("Depth", depth.to_string())
+                .header("Content-Type", "application/xml; charset=utf-8")
+                .body(PROPFIND_BODY)
+        })?;
+
+        if !response.status().is_success() {
+            return Err(SftpError::http_status(action, path, response.status()));
+        }
+
+        let body = response.text()
+            .map_err(|e| SftpError::http(action, path, e))?;
+        let entries = parse_multistatus(&body)
+            .map_err(|e| SftpError::new(ErrorCode::Failure, i18n::format("error.failed", &[&i18n::text(action), &e])))?;
+
+        Ok(entries.into_iter()
+            .filter_map(|(href, mut entry)| {
+                entry.path = self.browse_path(&href)?;
+                Some(entry)
+            })
+            .collect())
+    }
+
+    fn delete(&self, path: &str, collection: bool, action: &'static str) -> Result<(), SftpError> {
+        let response = self.send(Method::DELETE, &self.url(path, collection), action, path, |request| request)?;
+        if !response.status().is_success() {
+            return Err(SftpError::http_status(action, path, response.status()));
+        }
+        Ok(())
+    }
+}
+
+fn file_info(entry: &DavEntry) -> FileInfo {
+    let (file_type, perm) = if entry.is_dir { (FileType::Dir, 0o755) } else { (FileType::File, 0o644) };
+    let name = entry.path.rsplit('/').next().unwrap_or_default();
+
+    FileInfo {
+        name: if name.is_empty() { "/".to_string() } else { name.to_string() },
+        path: entry.path.clone(),
+        size: entry.size,
+        is_dir: entry.is_dir,
+        file_type,
+        modified: crate::format_timestamp(entry.modified),
+        accessed: None,
+        permissions: format!("{:o}", perm),
+        mode: crate::format_mode(file_type, perm),
+        uid: None,
+        gid: None,
+        is_symlink: false,
+        link_target: None,
+        target_is_dir: false,
+    }
+}
+
+// 解析 PROPFIND 的 207 响应，返回 (href, 条目)；只关心少数几个属性，按本地名匹配以忽略命名空间前缀
+fn parse_multistatus(body: &str) -> Result<Vec<(String, DavEntry)>, quick_xml::Error> {
+    let mut reader = quick_xml::Reader::from_str(body);
+    reader.config_mut().trim_text(true);
+
+    let mut entries = Vec::new();
+    let mut current: Option<(String, DavEntry)> = None;
+    let mut element = Vec::new();
+
+    loop {
+        match reader.read_event()? {
+            Event::Start(start) => {
+                element = start.local_name().as_ref().to_vec();
+                if element == b"response" {
+                    current = Some((String::new(), DavEntry { path: String::new(), is_dir: false, size: 0, modified: None }));
+                }
+                if element == b"collection" {
+                    if let Some((_, entry)) = current.as_mut() {
+                        entry.is_dir = true;
+                    }
+                }
+            }
+            Event::Empty(empty) if empty.local_name().as_ref() == b"collection" => {
+                if let Some((_, entry)) = current.as_mut() {
+                    entry.is_dir = true;
+                }
+            }
+            Event::Text(text) => {
+                let Some((href, entry)) = current.as_mut() else { continue };
+                let text = text.unescape()?;
+                match element.as_slice() {
+                    b"href" => *href = text.to_string(),
+                    b"getcontentlength" => entry.size = text.trim().parse().unwrap_or(0),
+                    b"getlastmodified" => {
+                        entry.modified = chrono::DateTime::parse_from_rfc2822(text.trim())
+                            .ok()
+                            .map(|time| time.timestamp().max(0) as u64);
+                    }
+                    _ => {}
+                }
+            }
+            Event::End(end) => {
+                if end.local_name().as_ref() == b"response" {
+                    entries.extend(current.take());
+                }
+                element.clear();
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    Ok(entries)
+}
+
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes.get(i + 1..i + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+// 从 401 响应中选择认证方式，优先 Digest
+fn parse_challenge(response: &Response) -> Option<Auth> {
+    let mut basic = false;
+    for value in response.headers().get_all(WWW_AUTHENTICATE) {
+        let value = value.to_str().ok()?;
+        let (scheme, params) = value.split_once(' ').unwrap_or((value, ""));
+
+        if scheme.eq_ignore_ascii_case("basic") {
+            basic = true;
+        } else if scheme.eq_ignore_ascii_case("digest") {
+            let params = parse_auth_params(params);
+            let param = |name: &str| params.iter()
+                .find(|(key, _)| key.eq_ignore_ascii_case(name))
+                .map(|(_, value)| value.clone());
+
+            let algorithm = param("algorithm").unwrap_or_else(|| "MD5".to_string());
+            if !algorithm.eq_ignore_ascii_case("md5") && !algorithm.eq_ignore_ascii_case("md5-sess") {
+                continue;
+            }
+
+            return Some(Auth::Digest(DigestChallenge {
+                realm: param("realm").unwrap_or_default(),
+                nonce: param("nonce")?,
+                opaque: param("opaque"),
+                qop_auth: param("qop").is_some_and(|qop| qop.split(',').any(|qop| qop.trim() == "auth")),
+                md5_sess: algorithm.eq_ignore_ascii_case("md5-sess"),
+                nc: 0,
+            }));
+        }
+    }
+
+    basic.then_some(Auth::Basic)
+}
+
+// 解析 key=value, key="quoted, value" 形式的参数列表
+fn parse_auth_params(text: &str) -> Vec<(String, String)> {
+    let mut params = Vec::new();
+    let mut rest = text.trim();
+
+    while let Some((key, after)) = rest.split_once('=') {
+        let key = key.trim().trim_start_matches(',').trim().to_string();
+        let after = after.trim_start();
+
+        let (value, remaining) = if let Some(quoted) = after.strip_prefix('"') {
+            let mut value = String::new();
+            let mut chars = quoted.char_indices();
+            let mut end = quoted.len();
+            while let Some((index, c)) = chars.next() {
+                match c {
+                    '\\' => value.extend(chars.next().map(|(_, c)| c)),
+                    '"' => {
+                        end = index + 1;
+                        break;
+                    }
+                    c => value.push(c),
+                }
+            }
+            (value, &quoted[end..])
+        } else {
+            let end = after.find(',').unwrap_or(after.len());
+            (after[..end].trim().to_string(), &after[end..])
+        };
+
+        params.push((key, value));
+        rest = remaining.trim_start().trim_start_matches(',').trim_start();
+    }
+
+    params
+}
+
+fn md5_hex(text: &str) -> String {
+    format!("{:x}", Md5::digest(text.as_bytes()))
+}
+
+// RFC 2617 Digest 认证
+fn digest_authorization(challenge: &mut DigestChallenge, username: &str, password: &str, method: &Method, url: &Url) -> String {
+    challenge.nc += 1;
+    let nc = format!("{:08x}", challenge.nc);
+    let cnonce = md5_hex(&format!("{:?}{}", std::time::SystemTime::now(), challenge.nc));
+    let uri = match url.query() {
+        Some(query) => format!("{}?{}", url.path(), query),
+        None => url.path().to_string(),
+    };
+
+    let mut ha1 = md5_hex(&format!("{}:{}:{}", username, challenge.realm, password));
+    if challenge.md5_sess {
+        ha1 = md5_hex(&format!("{}:{}:{}", ha1, challenge.nonce, cnonce));
+    }
+    let ha2 = md5_hex(&format!("{}:{}", method.as_str(), uri));
+
+    let mut header = format!(r#"Digest username="{}", realm="{}", nonce="{}", uri="{}""#, username, challenge.realm, challenge.nonce, uri);
+    if challenge.qop_auth {
+        let response = md5_hex(&format!("{}:{}:{}:{}:auth:{}", ha1, challenge.nonce, nc, cnonce, ha2));
+        header.push_str(&format!(r#", qop=auth, nc={}, cnonce="{}", response="{}""#, nc, cnonce, response));
+    } else {
+        let response = md5_hex(&format!("{}:{}:{}", ha1, challenge.nonce, ha2));
+        header.push_str(&format!(r#", response="{}""#, response));
+    }
+    header.push_str(if challenge.md5_sess { ", algorithm=MD5-sess" } else { ", algorithm=MD5" });
+    if let Some(opaque) = &challenge.opaque {
+        header.push_str(&format!(r#", opaque="{}""#, opaque));
+    }
+
+    header
+}
+
+impl RemoteFs for WebDavFs {
+    fn list(&self, path: &str) -> Result<Vec<FileInfo>, SftpError> {
+        let path = if path.is_empty() { "/" } else { path };
+        let dir = match path.trim_end_matches('/') {
+            "" => "/",
+            dir => dir,
+        };
+
+        // 响应的第一项是目录本身
+        Ok(self.propfind(path, true, 1)?
+            .iter()
+            .filter(|entry| entry.path != dir)
+            .map(|entry| {
+                let mut info = file_info(entry);
+                info.path = join_remote_path(dir, &info.name);
+                info
+            })
+            .collect())
+    }
+
+    fn stat(&self, path: &str) -> Result<FileInfo, SftpError> {
+        let entry = self.propfind(path, false, 0)?
+            .into_iter()
+            .next()
+            .ok_or_else(|| SftpError::new(ErrorCode::NotFound, i18n::format("error.path_not_found", &[&path])))?;
+
+        let mut info = file_info(&entry);
+        info.path = path.to_string();
+        Ok(info)
+    }
+
+    fn open_read(&self, path: &str) -> Result<Box<dyn Read + '_>, SftpError> {
+        let response = self.send(Method::GET, &self.url(path, false), "action.open_remote_file", path, |request| request)?;
+        if !response.status().is_success() {
+            return Err(SftpError::http_status("action.open_remote_file", path, response.status()));
+        }
+
+        Ok(Box::new(response))
+    }
+
+    fn open_write(&self, path: &str) -> Result<Box<dyn Write + '_>, SftpError> {
+        self.put(path, None)
+    }
+
+    fn open_write_sized(&self, path: &str, size: u64) -> Result<Box<dyn Write + '_>, SftpError> {
+        self.put(path, Some(size))
+    }
+
+    fn mkdir(&self, path: &str) -> Result<(), SftpError> {
+        let response = self.send(Method::from_bytes(b"MKCOL").unwrap(), &self.url(path, true), "action.create_dir", path, |request| request)?;
+        match response.status() {
+            // MKCOL 对已存在的路径返回 405
+            StatusCode::METHOD_NOT_ALLOWED => Err(SftpError::new(ErrorCode::AlreadyExists, i18n::format("error.destination_exists", &[&path]))),
+            status if status.is_success() => Ok(()),
+            status => Err(SftpError::http_status("action.create_dir", path, status)),
+        }
+    }
+
+    // DELETE 对目录是递归的，这里先确认类型，保持与其他后端相同的语义
+    fn remove_file(&self, path: &str) -> Result<(), SftpError> {
+        if self.stat(path)?.is_dir {
+            return Err(SftpError::new(ErrorCode::InvalidInput, i18n::format("error.is_directory", &[&path])));
+        }
+        self.delete(path, false, "action.remove_file")
+    }
+
+    fn remove_dir(&self, path: &str) -> Result<(), SftpError> {
+        if !self.list(path)?.is_empty() {
+            return Err(SftpError::new(ErrorCode::Failure, i18n::format("error.dir_not_empty", &[&path])));
+        }
+        self.delete(path, true, "action.remove_dir")
+    }
+
+    // MOVE 带 Overwrite: T 时会递归删除目标目录，因此非空目录需要先拒绝；
+    // 删除空的目标目录前先确认源存在
+    fn rename(&self, source: &str, destination: &str, overwrite: bool) -> Result<(), SftpError> {
+        self.stat(source)?;
+        if overwrite {
+            match self.stat(destination) {
+                Ok(existing) if existing.is_dir => self.remove_dir(destination)?,
+                Ok(_) => {}
+                Err(e) if e.code == ErrorCode::NotFound => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        let destination_url = self.url(destination, false);
+        let response = self.send(Method::from_bytes(b"MOVE").unwrap(), &self.url(source, false), "action.rename", source, |request| {
+            request
+                .header("Destination", destination_url.as_str())
+                .header("Overwrite", if overwrite { "T" } else { "F" })
+        })?;
+
+        match response.status() {
+            StatusCode::PRECONDITION_FAILED => Err(SftpError::new(ErrorCode::AlreadyExists, i18n::format("error.destination_exists", &[&destination]))),
+            status if status.is_success() => Ok(()),
+            status => Err(SftpError::http_status("action.rename", source, status)),
+        }
+    }
+}
+
+impl WebDavFs {
+    // 上传在后台线程中发送 PUT，写入的数据经通道交给请求体；大小已知时使用 Content-Length
+    // 流式请求体无法在 401 后重发，也不能设置整体超时。Digest 的 nonce 可能已经失效，
+    // 因此先经 send 发送一个不带请求体的 HEAD，必要时按新的质询重新认证
+    fn put(&self, path: &str, size: Option<u64>) -> Result<Box<dyn Write + '_>, SftpError> {
+        let url = self.url(path, false);
+        if matches!(*self.auth.borrow(), Auth::Digest(_)) {
+            self.send(Method::HEAD, &url, "action.write_remote_file", path, |request| request)?;
+        }
+        let authorization = self.authorization(&Method::PUT, &url);
+        let (sender, receiver) = mpsc::sync_channel(8);
+        let reader = PipeReader { receiver, chunk: Vec::new(), position: 0 };
+
+        let client = self.client.clone();
+        let path = path.to_string();
+        let request = std::thread::spawn(move || {
+            let body = match size {
+                Some(size) => Body::sized(reader, size),
+                None => Body::new(reader),
+            };
+            let mut request = client.put(url).body(body);
+            if let Some(authorization) = authorization {
+                request = request.header(AUTHORIZATION, authorization);
+            }
+
+            let response = request.send()
+                .map_err(|e| SftpError::http("action.write_remote_file", &path, e))?;
+            if !response.status().is_success() {
+                return Err(SftpError::http_status("action.write_remote_file", &path, response.status()));
+            }
+            Ok(())
+        });
+
+        Ok(Box::new(PutWriter { sender: Some(sender), request: Some(request) }))
+    }
+}
+
+// 请求体从通道中读取上传数据；收到 Err 表示上传被放弃
+struct PipeReader {
+    receiver: Receiver<io::Result<Vec<u8>>>,
+    chunk: Vec<u8>,
+    position: usize,
+}
+
+impl Read for PipeReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.position == self.chunk.len() {
+            match self.receiver.recv() {
+                Ok(chunk) => {
+                    self.chunk = chunk?;
+                    self.position = 0;
+                }
+                Err(_) => return Ok(0),
+            }
+        }
+
+        let n = buf.len().min(self.chunk.len() - self.position);
+        buf[..n].copy_from_slice(&self.chunk[self.position..self.position + n]);
+        self.position += n;
+        Ok(n)
+    }
+}
+
+// flush 时结束请求体并等待服务器响应，之后不能再写入
+struct PutWriter {
+    sender: Option<SyncSender<io::Result<Vec<u8>>>>,
+    request: Option<JoinHandle<Result<(), SftpError>>>,
+}
+
+impl PutWriter {
+    fn finish(&mut self) -> io::Result<()> {
+        self.sender = None;
+        match self.request.take().map(JoinHandle::join) {
+            // 保留请求的错误分类，由 SftpError::io 取回
+            Some(Ok(result)) => result.map_err(io::Error::other),
+            Some(Err(_)) => Err(io::Error::other(i18n::text("error.stream_closed"))),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Write for PutWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let Some(sender) = self.sender.as_ref() else {
+            return Err(io::Error::new(io::ErrorKind::BrokenPipe, i18n::text("error.stream_closed")));
+        };
+
+        // 请求提前结束（如服务器拒绝）时通道已关闭，返回请求的错误
+        if sender.send(Ok(buf.to_vec())).is_err() {
+            self.finish()?;
+            return Err(io::Error::new(io::ErrorKind::BrokenPipe, i18n::text("error.stream_closed")));
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.finish()
+    }
+}
+
+// 未 flush 就被丢弃（如取消）时中止请求，避免服务器保存不完整的文件
+impl Drop for PutWriter {
+    fn drop(&mut self) {
+        if let Some(sender) = self.sender.take() {
+            let _ = sender.send(Err(io::Error::new(io::ErrorKind::Interrupted, i18n::text("error.cancelled"))));
+        }
+        if let Some(request) = self.request.take() {
+            let _ = request.join();
+        }
+    }
+}
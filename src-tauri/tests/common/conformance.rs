@@ -0,0 +1,116 @@
+// 各 RemoteFs 后端共同遵守的语义
+// root 为后端浏览路径 / 对应的本地目录，用于准备数据和检查服务器上的结果
+
+use super::sample_data;
+use sftp_web_lib::{ops, ErrorCode, NoProgress, RemoteFs};
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::AtomicBool;
+
+pub fn conformance(fs: &dyn RemoteFs, root: &Path) {
+    list_and_stat(fs, root);
+    upload_download_round_trip(fs, root);
+    mkdir_and_remove(fs, root);
+    rename_respects_overwrite(fs, root);
+}
+
+fn list_and_stat(remote: &dyn RemoteFs, root: &Path) {
+    fs::write(root.join("a b.txt"), b"12345").unwrap();
+    fs::create_dir(root.join("sub")).unwrap();
+    fs::write(root.join("sub/inner.txt"), b"x").unwrap();
+
+    let mut files = remote.list("/").unwrap();
+    files.sort_by(|a, b| a.name.cmp(&b.name));
+
+    assert_eq!(files.len(), 2);
+    assert_eq!(files[0].name, "a b.txt");
+    assert_eq!(files[0].path, "/a b.txt");
+    assert_eq!(files[0].size, 5);
+    assert!(!files[0].is_dir);
+    assert!(files[0].modified.is_some());
+    assert_eq!(files[1].name, "sub");
+    assert_eq!(files[1].path, "/sub");
+    assert!(files[1].is_dir);
+
+    let sub = remote.list("/sub").unwrap();
+    assert_eq!(sub.len(), 1);
+    assert_eq!(sub[0].path, "/sub/inner.txt");
+
+    assert!(remote.stat("/").unwrap().is_dir);
+    assert!(remote.stat("/sub").unwrap().is_dir);
+    assert_eq!(remote.stat("/a b.txt").unwrap().size, 5);
+
+    let err = remote.stat("/missing.txt").unwrap_err();
+    assert_eq!(err.code, ErrorCode::NotFound);
+
+    fs::remove_file(root.join("a b.txt")).unwrap();
+    fs::remove_dir_all(root.join("sub")).unwrap();
+}
+
+fn upload_download_round_trip(remote: &dyn RemoteFs, root: &Path) {
+    let local = tempfile::tempdir().unwrap();
+    let data = sample_data(300_000);
+    let source = local.path().join("source.bin");
+    let downloaded = local.path().join("downloaded.bin");
+    fs::write(&source, &data).unwrap();
+
+    let cancel_flag = AtomicBool::new(false);
+    let bytes = ops::upload(remote, source.to_str().unwrap(), "/uploaded.bin", &cancel_flag, &mut NoProgress).unwrap();
+    assert_eq!(bytes, data.len() as u64);
+    assert_eq!(fs::read(root.join("uploaded.bin")).unwrap(), data);
+
+    let mut last = (0, 0);
+    let bytes = ops::download(remote, "/uploaded.bin", downloaded.to_str().unwrap(), &cancel_flag, &mut |done, total| {
+        last = (done, total);
+    }).unwrap();
+
+    assert_eq!(bytes, data.len() as u64);
+    assert_eq!(last, (data.len() as u64, data.len() as u64));
+    assert_eq!(fs::read(&downloaded).unwrap(), data);
+
+    // 传输结束后连接仍然可用
+    assert_eq!(remote.list("/").unwrap().len(), 1);
+    fs::remove_file(root.join("uploaded.bin")).unwrap();
+}
+
+fn mkdir_and_remove(remote: &dyn RemoteFs, root: &Path) {
+    remote.mkdir("/new").unwrap();
+    assert!(root.join("new").is_dir());
+    assert!(remote.mkdir("/new").is_err());
+    fs::write(root.join("new/file.txt"), b"x").unwrap();
+
+    assert!(remote.remove_dir("/new").is_err());
+    assert!(remote.remove_file("/new").is_err());
+    remote.remove_file("/new/file.txt").unwrap();
+    remote.remove_dir("/new").unwrap();
+    assert!(!root.join("new").exists());
+
+    let err = remote.remove_file("/new/file.txt").unwrap_err();
+    assert_eq!(err.code, ErrorCode::NotFound);
+}
+
+fn rename_respects_overwrite(remote: &dyn RemoteFs, root: &Path) {
+    fs::write(root.join("a.txt"), b"new").unwrap();
+    fs::write(root.join("b.txt"), b"old").unwrap();
+
+    let err = remote.rename("/a.txt", "/b.txt", false).unwrap_err();
+    assert_eq!(err.code, ErrorCode::AlreadyExists);
+    assert_eq!(fs::read(root.join("b.txt")).unwrap(), b"old");
+
+    remote.rename("/a.txt", "/b.txt", true).unwrap();
+    assert!(!root.join("a.txt").exists());
+    assert_eq!(fs::read(root.join("b.txt")).unwrap(), b"new");
+
+    // 源不存在时不能先删掉已有的目标
+    let err = remote.rename("/a.txt", "/b.txt", true).unwrap_err();
+    assert_eq!(err.code, ErrorCode::NotFound);
+    assert_eq!(fs::read(root.join("b.txt")).unwrap(), b"new");
+
+    remote.mkdir("/empty").unwrap();
+    let err = remote.rename("/a.txt", "/empty", true).unwrap_err();
+    assert_eq!(err.code, ErrorCode::NotFound);
+    assert!(root.join("empty").is_dir());
+
+    remote.remove_dir("/empty").unwrap();
+    fs::remove_file(root.join("b.txt")).unwrap();
+}
@@ -0,0 +1,513 @@
+// 集成测试使用的进程内 SSH/SFTP 服务器
+// 以临时目录作为 SFTP 根目录，支持密码和公钥认证；每个测试启动独立的实例
+// exec 请求直接交给本机 sh 执行（路径不做映射），用于测试 SCP 和远程命令
+// 另有各后端共用的测试数据和 RemoteFs 一致性检查；各测试文件只用到其中一部分
+
+#![allow(dead_code)]
+
+mod conformance;
+
+// 只有部分测试文件用到
+#[allow(unused_imports)]
+pub use conformance::conformance;
+
+use russh::keys::ssh_key::rand_core::OsRng;
+use russh::keys::ssh_key::LineEnding;
+use russh::keys::{Algorithm, PrivateKey, PublicKey};
+use russh::server::{Auth, Msg, Session};
+use russh::{Channel, ChannelId, ChannelMsg};
+use russh_sftp::protocol::{
+    Attrs, Data, File, FileAttributes, Handle, Name, OpenFlags, Packet, Status, StatusCode, Version,
+};
+use sftp_web_lib::ConnectOptions;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Component, Path, PathBuf};
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::{Duration, UNIX_EPOCH};
+use tempfile::TempDir;
+use tokio::io::AsyncWriteExt;
+
+pub const USERNAME: &str = "tester";
+pub const PASSWORD: &str = "secret";
+
+// 内容随位置变化的测试数据，便于发现错位
+pub fn sample_data(len: usize) -> Vec<u8> {
+    (0..len).map(|i| (i % 251) as u8).collect()
+}
+
+pub struct TestServer {
+    pub port: u16,
+    root: TempDir,
+    client_key: TempDir,
+    // 服务器运行在这个运行时上，测试结束时随之关闭
+    _runtime: tokio::runtime::Runtime,
+}
+
+impl TestServer {
+    pub fn start() -> Self {
+        Self::start_with(true, true)
+    }
+
+    // 模拟没有 SFTP 子系统的主机
+    pub fn start_without_sftp() -> Self {
+        Self::start_with(false, false)
+    }
+
+    // 模拟不声明任何扩展（如 posix-rename@openssh.com）的非 OpenSSH 服务器
+    pub fn start_without_extensions() -> Self {
+        Self::start_with(true, false)
+    }
+
+    fn start_with(sftp_enabled: bool, extensions: bool) -> Self {
+        let root = tempfile::tempdir().unwrap();
+        let client_key = tempfile::tempdir().unwrap();
+
+        // 客户端密钥写入文件，供 libssh2 的公钥认证读取
+        let key = PrivateKey::random(&mut OsRng, Algorithm::Ed25519).unwrap();
+        fs::write(
+            client_key.path().join("id_ed25519"),
+            key.to_openssh(LineEnding::LF).unwrap().as_bytes(),
+        ).unwrap();
+        fs::write(
+            client_key.path().join("id_ed25519.pub"),
+            key.public_key().to_openssh().unwrap(),
+        ).unwrap();
+
+        let config = Arc::new(russh::server::Config {
+            keys: vec![PrivateKey::random(&mut OsRng, Algorithm::Ed25519).unwrap()],
+            auth_rejection_time: std::time::Duration::from_millis(10),
+            auth_rejection_time_initial: Some(std::time::Duration::ZERO),
+            ..Default::default()
+        });
+
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(2)
+            .enable_all()
+            .build()
+            .unwrap();
+
+        let listener = runtime
+            .block_on(tokio::net::TcpListener::bind("127.0.0.1:0"))
+            .unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let sftp_root = root.path().to_path_buf();
+        let authorized_key = key.public_key().clone();
+        runtime.spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let handler = SshHandler {
+                    root: sftp_root.clone(),
+                    authorized_key: authorized_key.clone(),
+                    sftp_enabled,
+                    extensions,
+                    channels: HashMap::new(),
+                    exec_channels: HashSet::new(),
+                };
+                let config = config.clone();
+                tokio::spawn(async move {
+                    if let Ok(session) = russh::server::run_stream(config, stream, handler).await {
+                        let _ = session.await;
+                    }
+                });
+            }
+        });
+
+        TestServer {
+            port,
+            root,
+            client_key,
+            _runtime: runtime,
+        }
+    }
+
+    pub fn connect_options(&self) -> ConnectOptions {
+        let mut options = ConnectOptions::new("127.0.0.1", USERNAME, PASSWORD);
+        options.port = self.port;
+        options
+    }
+
+    // 服务器临时目录本身，exec 和 SCP 需要使用这里的真实路径
+    pub fn root(&self) -> &Path {
+        self.root.path()
+    }
+
+    // 远程路径在服务器临时目录中对应的本地路径
+    pub fn path(&self, remote_path: &str) -> PathBuf {
+        resolve(self.root.path(), remote_path)
+    }
+
+    pub fn private_key_path(&self) -> PathBuf {
+        self.client_key.path().join("id_ed25519")
+    }
+}
+
+// 将 SFTP 路径映射到根目录下，忽略 .. 以免越出根目录
+fn resolve(root: &Path, path: &str) -> PathBuf {
+    let mut resolved = root.to_path_buf();
+    for component in Path::new(path).components() {
+        if let Component::Normal(part) = component {
+            resolved.push(part);
+        }
+    }
+    resolved
+}
+
+struct SshHandler {
+    root: PathBuf,
+    authorized_key: PublicKey,
+    sftp_enabled: bool,
+    extensions: bool,
+    channels: HashMap<ChannelId, Channel<Msg>>,
+    // exec 通道在命令结束后由 run_exec 关闭，收到客户端 EOF 时不能提前关闭
+    exec_channels: HashSet<ChannelId>,
+}
+
+impl russh::server::Handler for SshHandler {
+    type Error = russh::Error;
+
+    async fn auth_password(&mut self, user: &str, password: &str) -> Result<Auth, Self::Error> {
+        if user == USERNAME && password == PASSWORD {
+            Ok(Auth::Accept)
+        } else {
+            Ok(Auth::reject())
+        }
+    }
+
+    async fn auth_publickey(&mut self, user: &str, public_key: &PublicKey) -> Result<Auth, Self::Error> {
+        if user == USERNAME && public_key.key_data() == self.authorized_key.key_data() {
+            Ok(Auth::Accept)
+        } else {
+            Ok(Auth::reject())
+        }
+    }
+
+    async fn channel_open_session(
+        &mut self,
+        channel: Channel<Msg>,
+        _session: &mut Session,
+    ) -> Result<bool, Self::Error> {
+        self.channels.insert(channel.id(), channel);
+        Ok(true)
+    }
+
+    async fn channel_eof(&mut self, channel: ChannelId, session: &mut Session) -> Result<(), Self::Error> {
+        if self.exec_channels.contains(&channel) {
+            return Ok(());
+        }
+        session.close(channel)
+    }
+
+    async fn exec_request(
+        &mut self,
+        channel_id: ChannelId,
+        data: &[u8],
+        session: &mut Session,
+    ) -> Result<(), Self::Error> {
+        match self.channels.remove(&channel_id) {
+            Some(channel) => {
+                session.channel_success(channel_id)?;
+                self.exec_channels.insert(channel_id);
+                tokio::spawn(run_exec(channel, String::from_utf8_lossy(data).to_string()));
+            }
+            None => session.channel_failure(channel_id)?,
+        }
+        Ok(())
+    }
+
+    async fn subsystem_request(
+        &mut self,
+        channel_id: ChannelId,
+        name: &str,
+        session: &mut Session,
+    ) -> Result<(), Self::Error> {
+        match self.channels.remove(&channel_id) {
+            Some(channel) if name == "sftp" && self.sftp_enabled => {
+                session.channel_success(channel_id)?;
+                russh_sftp::server::run(channel.into_stream(), SftpHandler::new(self.root.clone(), self.extensions)).await;
+            }
+            _ => session.channel_failure(channel_id)?,
+        }
+        Ok(())
+    }
+}
+
+// 用 sh 执行命令，标准输入输出与通道对接，结束后发送退出码并关闭通道
+async fn run_exec(channel: Channel<Msg>, command: String) {
+    let (mut reader, writer) = channel.split();
+    let child = tokio::process::Command::new("sh")
+        .arg("-c")
+        .arg(&command)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn();
+    let Ok(mut child) = child else {
+        let _ = writer.exit_status(127).await;
+        let _ = writer.close().await;
+        return;
+    };
+
+    let mut stdin = child.stdin.take().unwrap();
+    let mut stdout = child.stdout.take().unwrap();
+    let mut stderr = child.stderr.take().unwrap();
+
+    tokio::spawn(async move {
+        while let Some(message) = reader.wait().await {
+            let written = match message {
+                ChannelMsg::Data { data } => stdin.write_all(&data).await,
+                ChannelMsg::Eof => break,
+                _ => Ok(()),
+            };
+            if written.is_err() {
+                break;
+            }
+        }
+    });
+
+    let mut out = writer.make_writer();
+    let mut err = writer.make_writer_ext(Some(1));
+    let _ = tokio::join!(
+        tokio::io::copy(&mut stdout, &mut out),
+        tokio::io::copy(&mut stderr, &mut err),
+    );
+
+    let status = child.wait().await.ok().and_then(|status| status.code()).unwrap_or(1);
+    let _ = writer.exit_status(status as u32).await;
+    let _ = writer.eof().await;
+    let _ = writer.close().await;
+}
+
+enum OpenHandle {
+    File(fs::File),
+    Dir(Option<Vec<File>>),
+}
+
+// 直接操作本地文件系统的 SFTP v3 服务端
+struct SftpHandler {
+    root: PathBuf,
+    extensions: bool,
+    handles: HashMap<String, OpenHandle>,
+    next_handle: u64,
+}
+
+impl SftpHandler {
+    fn new(root: PathBuf, extensions: bool) -> Self {
+        SftpHandler {
+            root,
+            extensions,
+            handles: HashMap::new(),
+            next_handle: 0,
+        }
+    }
+
+    fn path(&self, path: &str) -> PathBuf {
+        resolve(&self.root, path)
+    }
+
+    fn insert(&mut self, handle: OpenHandle) -> String {
+        self.next_handle += 1;
+        let name = self.next_handle.to_string();
+        self.handles.insert(name.clone(), handle);
+        name
+    }
+
+    fn file(&mut self, handle: &str) -> Result<&mut fs::File, StatusCode> {
+        match self.handles.get_mut(handle) {
+            Some(OpenHandle::File(file)) => Ok(file),
+            _ => Err(StatusCode::Failure),
+        }
+    }
+}
+
+fn status_code(e: io::Error) -> StatusCode {
+    match e.kind() {
+        io::ErrorKind::NotFound => StatusCode::NoSuchFile,
+        io::ErrorKind::PermissionDenied => StatusCode::PermissionDenied,
+        _ => StatusCode::Failure,
+    }
+}
+
+fn ok(id: u32) -> Status {
+    Status {
+        id,
+        status_code: StatusCode::Ok,
+        error_message: "Ok".to_string(),
+        language_tag: "en-US".to_string(),
+    }
+}
+
+impl russh_sftp::server::Handler for SftpHandler {
+    type Error = StatusCode;
+
+    fn unimplemented(&self) -> Self::Error {
+        StatusCode::OpUnsupported
+    }
+
+    async fn init(&mut self, _version: u32, _extensions: HashMap<String, String>) -> Result<Version, Self::Error> {
+        let mut version = Version::new();
+        if self.extensions {
+            version.extensions.insert("posix-rename@openssh.com".to_string(), "1".to_string());
+        }
+        Ok(version)
+    }
+
+    async fn open(
+        &mut self,
+        id: u32,
+        filename: String,
+        pflags: OpenFlags,
+        _attrs: FileAttributes,
+    ) -> Result<Handle, Self::Error> {
+        let file = fs::OpenOptions::new()
+            .read(pflags.contains(OpenFlags::READ))
+            .write(pflags.contains(OpenFlags::WRITE))
+            .append(pflags.contains(OpenFlags::APPEND))
+            .create(pflags.contains(OpenFlags::CREATE))
+            .truncate(pflags.contains(OpenFlags::TRUNCATE))
+            .open(self.path(&filename))
+            .map_err(status_code)?;
+
+        Ok(Handle { id, handle: self.insert(OpenHandle::File(file)) })
+    }
+
+    async fn close(&mut self, id: u32, handle: String) -> Result<Status, Self::Error> {
+        self.handles.remove(&handle);
+        Ok(ok(id))
+    }
+
+    async fn read(&mut self, id: u32, handle: String, offset: u64, len: u32) -> Result<Data, Self::Error> {
+        let file = self.file(&handle)?;
+        file.seek(SeekFrom::Start(offset)).map_err(status_code)?;
+
+        let mut data = Vec::with_capacity(len as usize);
+        file.take(len as u64).read_to_end(&mut data).map_err(status_code)?;
+        if data.is_empty() {
+            return Err(StatusCode::Eof);
+        }
+        Ok(Data { id, data })
+    }
+
+    async fn write(&mut self, id: u32, handle: String, offset: u64, data: Vec<u8>) -> Result<Status, Self::Error> {
+        let file = self.file(&handle)?;
+        file.seek(SeekFrom::Start(offset)).map_err(status_code)?;
+        file.write_all(&data).map_err(status_code)?;
+        Ok(ok(id))
+    }
+
+    async fn lstat(&mut self, id: u32, path: String) -> Result<Attrs, Self::Error> {
+        let metadata = fs::symlink_metadata(self.path(&path)).map_err(status_code)?;
+        Ok(Attrs { id, attrs: FileAttributes::from(&metadata) })
+    }
+
+    async fn stat(&mut self, id: u32, path: String) -> Result<Attrs, Self::Error> {
+        let metadata = fs::metadata(self.path(&path)).map_err(status_code)?;
+        Ok(Attrs { id, attrs: FileAttributes::from(&metadata) })
+    }
+
+    async fn fstat(&mut self, id: u32, handle: String) -> Result<Attrs, Self::Error> {
+        let metadata = self.file(&handle)?.metadata().map_err(status_code)?;
+        Ok(Attrs { id, attrs: FileAttributes::from(&metadata) })
+    }
+
+    // 只支持修改时间，其他属性忽略
+    async fn setstat(&mut self, id: u32, path: String, attrs: FileAttributes) -> Result<Status, Self::Error> {
+        if let Some(mtime) = attrs.mtime {
+            let file = fs::OpenOptions::new().write(true).open(self.path(&path)).map_err(status_code)?;
+            file.set_modified(UNIX_EPOCH + Duration::from_secs(mtime as u64)).map_err(status_code)?;
+        }
+        Ok(ok(id))
+    }
+
+    async fn fsetstat(&mut self, id: u32, _handle: String, _attrs: FileAttributes) -> Result<Status, Self::Error> {
+        Ok(ok(id))
+    }
+
+    async fn opendir(&mut self, id: u32, path: String) -> Result<Handle, Self::Error> {
+        let mut files = Vec::new();
+        for entry in fs::read_dir(self.path(&path)).map_err(status_code)? {
+            let entry = entry.map_err(status_code)?;
+            let metadata = entry.metadata().map_err(status_code)?;
+            files.push(File::new(entry.file_name().to_string_lossy(), FileAttributes::from(&metadata)));
+        }
+
+        Ok(Handle { id, handle: self.insert(OpenHandle::Dir(Some(files))) })
+    }
+
+    // 第一次返回全部条目，之后返回 EOF
+    async fn readdir(&mut self, id: u32, handle: String) -> Result<Name, Self::Error> {
+        match self.handles.get_mut(&handle) {
+            Some(OpenHandle::Dir(files)) => match files.take() {
+                Some(files) if !files.is_empty() => Ok(Name { id, files }),
+                _ => Err(StatusCode::Eof),
+            },
+            _ => Err(StatusCode::Failure),
+        }
+    }
+
+    async fn remove(&mut self, id: u32, filename: String) -> Result<Status, Self::Error> {
+        fs::remove_file(self.path(&filename)).map_err(status_code)?;
+        Ok(ok(id))
+    }
+
+    async fn mkdir(&mut self, id: u32, path: String, _attrs: FileAttributes) -> Result<Status, Self::Error> {
+        fs::create_dir(self.path(&path)).map_err(status_code)?;
+        Ok(ok(id))
+    }
+
+    async fn rmdir(&mut self, id: u32, path: String) -> Result<Status, Self::Error> {
+        fs::remove_dir(self.path(&path)).map_err(status_code)?;
+        Ok(ok(id))
+    }
+
+    async fn realpath(&mut self, id: u32, path: String) -> Result<Name, Self::Error> {
+        let mut normalized = PathBuf::from("/");
+        for component in Path::new(&path).components() {
+            match component {
+                Component::Normal(part) => normalized.push(part),
+                Component::ParentDir => {
+                    normalized.pop();
+                }
+                _ => {}
+            }
+        }
+        Ok(Name { id, files: vec![File::dummy(normalized.to_string_lossy())] })
+    }
+
+    // 与 OpenSSH 一样，SFTP v3 的重命名在目标存在时失败；源不存在时先报告源不存在
+    async fn rename(&mut self, id: u32, oldpath: String, newpath: String) -> Result<Status, Self::Error> {
+        let destination = self.path(&newpath);
+        fs::symlink_metadata(self.path(&oldpath)).map_err(status_code)?;
+        if fs::symlink_metadata(&destination).is_ok() {
+            return Err(StatusCode::Failure);
+        }
+        fs::rename(self.path(&oldpath), destination).map_err(status_code)?;
+        Ok(ok(id))
+    }
+
+    // posix-rename@openssh.com：与 rename(2) 一样直接替换已存在的目标
+    async fn extended(&mut self, id: u32, request: String, data: Vec<u8>) -> Result<Packet, Self::Error> {
+        if !self.extensions || request != "posix-rename@openssh.com" {
+            return Err(StatusCode::OpUnsupported);
+        }
+        let mut data = data.as_slice();
+        let oldpath = read_string(&mut data)?;
+        let newpath = read_string(&mut data)?;
+        fs::rename(self.path(&oldpath), self.path(&newpath)).map_err(status_code)?;
+        Ok(Packet::Status(ok(id)))
+    }
+}
+
+// 扩展请求数据中的 SSH 字符串（4 字节长度 + 内容）
+fn read_string(data: &mut &[u8]) -> Result<String, StatusCode> {
+    if data.len() < 4 {
+        return Err(StatusCode::BadMessage);
+    }
+    let len = u32::from_be_bytes([data[0], data[1], data[2], data[3]]) as usize;
+    let value = data.get(4..4 + len).ok_or(StatusCode::BadMessage)?;
+    let value = String::from_utf8_lossy(value).to_string();
+    *data = &data[4 + len..];
+    Ok(value)
+}
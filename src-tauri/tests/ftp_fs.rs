@@ -0,0 +1,213 @@
+// 使用进程内的 FTP 服务器（libunftp）测试 FTP 后端
+// 服务器以临时目录为根目录，默认明文 FTP、被动模式，另有显式 TLS 和主动模式的用例
+
+mod common;
+
+use common::{PASSWORD, USERNAME};
+use libunftp::auth::{AuthenticationError, Authenticator, Credentials, Principal};
+use libunftp::options::{ActivePassiveMode, FtpsRequired};
+use openssl::asn1::Asn1Time;
+use openssl::bn::BigNum;
+use openssl::hash::MessageDigest;
+use openssl::pkey::PKey;
+use openssl::rsa::Rsa;
+use openssl::x509::{X509Builder, X509NameBuilder};
+use sftp_web_lib::{ops, ErrorCode, FtpFs, FtpMode, FtpOptions, FtpTls, NoProgress, RemoteFs, TimeoutConfig};
+use std::fs;
+use std::io::{BufRead, BufReader};
+use std::net::TcpStream;
+use std::path::Path;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use std::time::Duration;
+use tempfile::TempDir;
+use unftp_sbe_fs::Filesystem;
+
+#[derive(Debug)]
+struct PasswordAuthenticator;
+
+#[async_trait::async_trait]
+impl Authenticator for PasswordAuthenticator {
+    async fn authenticate(&self, username: &str, creds: &Credentials) -> Result<Principal, AuthenticationError> {
+        if username != USERNAME {
+            return Err(AuthenticationError::BadUser);
+        }
+        match creds.password.as_deref() {
+            Some(PASSWORD) => Ok(Principal { username: username.to_string() }),
+            _ => Err(AuthenticationError::BadPassword),
+        }
+    }
+}
+
+type Builder = libunftp::ServerBuilder<Filesystem, libunftp::auth::DefaultUser>;
+
+struct FtpServer {
+    port: u16,
+    root: TempDir,
+    // 显式 TLS 使用的自签名证书和私钥
+    _certs: TempDir,
+    _runtime: tokio::runtime::Runtime,
+}
+
+impl FtpServer {
+    fn start() -> Self {
+        FtpServer::start_with(|builder, _| builder)
+    }
+
+    fn start_with(configure: impl Fn(Builder, &Path) -> Builder) -> Self {
+        let root = tempfile::tempdir().unwrap();
+        let certs = tempfile::tempdir().unwrap();
+        write_self_signed_cert(certs.path());
+
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(2)
+            .enable_all()
+            .build()
+            .unwrap();
+
+        // libunftp 只接受监听地址，只能先占用一个空闲端口再释放；
+        // 释放后端口可能被其他进程抢占，此时监听失败，换一个端口重试
+        for _ in 0..10 {
+            let port = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+
+            let ftp_root = root.path().to_path_buf();
+            let builder = libunftp::ServerBuilder::with_authenticator(
+                Box::new(move || Filesystem::new(ftp_root.clone()).unwrap()),
+                Arc::new(PasswordAuthenticator),
+            );
+            let server = configure(builder, certs.path()).build().unwrap();
+            let listening = runtime.spawn(server.listen(format!("127.0.0.1:{}", port)));
+
+            // 等待服务器开始监听，并确认应答的是 FTP 服务器
+            for _ in 0..100 {
+                if listening.is_finished() {
+                    break;
+                }
+                if is_ftp_server(port) {
+                    return FtpServer {
+                        port,
+                        root,
+                        _certs: certs,
+                        _runtime: runtime,
+                    };
+                }
+                std::thread::sleep(Duration::from_millis(20));
+            }
+            listening.abort();
+        }
+
+        panic!("FTP server did not start");
+    }
+
+    fn connect(&self, password: &str) -> Result<FtpFs, sftp_web_lib::SftpError> {
+        self.connect_with(password, &FtpOptions::default())
+    }
+
+    fn connect_with(&self, password: &str, options: &FtpOptions) -> Result<FtpFs, sftp_web_lib::SftpError> {
+        FtpFs::connect("127.0.0.1", self.port, USERNAME, password, options, None, &TimeoutConfig::default())
+    }
+}
+
+fn is_ftp_server(port: u16) -> bool {
+    let Ok(stream) = TcpStream::connect(("127.0.0.1", port)) else {
+        return false;
+    };
+    stream.set_read_timeout(Some(Duration::from_secs(2))).unwrap();
+    let mut greeting = String::new();
+    BufReader::new(stream).read_line(&mut greeting).is_ok() && greeting.starts_with("220")
+}
+
+// 生成 127.0.0.1 的自签名证书，写入 dir/cert.pem 和 dir/key.pem
+fn write_self_signed_cert(dir: &Path) {
+    let key = PKey::from_rsa(Rsa::generate(2048).unwrap()).unwrap();
+
+    let mut name = X509NameBuilder::new().unwrap();
+    name.append_entry_by_text("CN", "127.0.0.1").unwrap();
+    let name = name.build();
+
+    let mut cert = X509Builder::new().unwrap();
+    cert.set_version(2).unwrap();
+    cert.set_serial_number(&BigNum::from_u32(1).unwrap().to_asn1_integer().unwrap()).unwrap();
+    cert.set_subject_name(&name).unwrap();
+    cert.set_issuer_name(&name).unwrap();
+    cert.set_pubkey(&key).unwrap();
+    cert.set_not_before(&Asn1Time::days_from_now(0).unwrap()).unwrap();
+    cert.set_not_after(&Asn1Time::days_from_now(1).unwrap()).unwrap();
+    cert.sign(&key, MessageDigest::sha256()).unwrap();
+
+    fs::write(dir.join("cert.pem"), cert.build().to_pem().unwrap()).unwrap();
+    fs::write(dir.join("key.pem"), key.private_key_to_pem_pkcs8().unwrap()).unwrap();
+}
+
+#[test]
+fn conformance() {
+    let server = FtpServer::start();
+    let ftp = server.connect(PASSWORD).unwrap();
+    common::conformance(&ftp, server.root.path());
+}
+
+#[test]
+fn wrong_password_is_auth_failure() {
+    let server = FtpServer::start();
+
+    let err = server.connect("wrong").err().unwrap();
+    assert_eq!(err.code, ErrorCode::AuthFailed);
+}
+
+// FTP 没有符号链接和属性修改，应返回 Unsupported 而不是未连接
+#[test]
+fn symlinks_and_attributes_are_unsupported() {
+    let server = FtpServer::start();
+    let ftp = server.connect(PASSWORD).unwrap();
+
+    assert_eq!(ftp.read_link("/link").unwrap_err().code, ErrorCode::Unsupported);
+    assert_eq!(ftp.symlink("/a.txt", "/link").unwrap_err().code, ErrorCode::Unsupported);
+    let err = ftp.set_attributes("/a.txt", &sftp_web_lib::SetStatOptions::default()).unwrap_err();
+    assert_eq!(err.code, ErrorCode::Unsupported);
+}
+
+#[test]
+fn explicit_tls_conformance() {
+    let server = FtpServer::start_with(|builder, certs| {
+        builder
+            .ftps(certs.join("cert.pem"), certs.join("key.pem"))
+            .ftps_required(FtpsRequired::All, FtpsRequired::All)
+    });
+    let options = FtpOptions {
+        tls: FtpTls::Explicit,
+        accept_invalid_certs: true,
+        ..FtpOptions::default()
+    };
+
+    // 服务器要求 TLS，明文连接无法登录
+    assert!(server.connect(PASSWORD).is_err());
+
+    let ftp = server.connect_with(PASSWORD, &options).unwrap();
+    common::conformance(&ftp, server.root.path());
+}
+
+#[test]
+fn active_mode_conformance() {
+    let server = FtpServer::start_with(|builder, _| builder.active_passive_mode(ActivePassiveMode::ActiveOnly));
+    let options = FtpOptions {
+        mode: FtpMode::Active,
+        ..FtpOptions::default()
+    };
+
+    let ftp = server.connect_with(PASSWORD, &options).unwrap();
+    common::conformance(&ftp, server.root.path());
+}
+
+// 服务器在数据传输结束后才拒绝上传（550），错误分类不能变成本地 IO 错误
+#[test]
+fn rejected_upload_keeps_error_code() {
+    let server = FtpServer::start();
+    let ftp = server.connect(PASSWORD).unwrap();
+    let local = tempfile::tempdir().unwrap();
+    let source = local.path().join("source.bin");
+    fs::write(&source, common::sample_data(100_000)).unwrap();
+
+    let cancel_flag = AtomicBool::new(false);
+    let err = ops::upload(&ftp, source.to_str().unwrap(), "/missing/file.bin", &cancel_flag, &mut NoProgress).unwrap_err();
+    assert_eq!(err.code, ErrorCode::NotFound);
+}
@@ -0,0 +1,176 @@
+// 通过 RemoteFs 接口测试本地磁盘后端，以及基于该接口的传输逻辑
+
+use sftp_web_lib::{ops, ErrorCode, FileType, LocalFs, NoProgress, RemoteFs, SetStatOptions};
+use std::fs;
+use std::io::{Read, Write};
+use std::path::Path;
+use std::sync::atomic::AtomicBool;
+
+fn path_str(path: &Path) -> &str {
+    path.to_str().unwrap()
+}
+
+#[test]
+fn list_and_stat() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("a.txt"), b"12345").unwrap();
+    fs::create_dir(dir.path().join("sub")).unwrap();
+
+    let mut files = LocalFs.list(path_str(dir.path())).unwrap();
+    files.sort_by(|a, b| a.name.cmp(&b.name));
+
+    assert_eq!(files.len(), 2);
+    assert_eq!(files[0].name, "a.txt");
+    assert_eq!(files[0].size, 5);
+    assert_eq!(files[0].file_type, FileType::File);
+    assert_eq!(files[0].path, path_str(&dir.path().join("a.txt")));
+    assert_eq!(files[1].name, "sub");
+    assert!(files[1].is_dir);
+    assert!(files[1].mode.starts_with('d'));
+
+    let stat = LocalFs.stat(path_str(&dir.path().join("a.txt"))).unwrap();
+    assert_eq!(stat.size, 5);
+    assert!(stat.modified.is_some());
+}
+
+#[cfg(unix)]
+#[test]
+fn symlinks_report_target() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::create_dir(dir.path().join("target")).unwrap();
+    std::os::unix::fs::symlink(dir.path().join("target"), dir.path().join("link")).unwrap();
+
+    let files = LocalFs.list(path_str(dir.path())).unwrap();
+    let link = files.iter().find(|file| file.name == "link").unwrap();
+
+    assert!(link.is_symlink);
+    assert!(link.target_is_dir);
+    assert_eq!(link.link_target.as_deref(), Some(path_str(&dir.path().join("target"))));
+}
+
+#[cfg(unix)]
+#[test]
+fn create_and_read_symlink() {
+    let dir = tempfile::tempdir().unwrap();
+    let link = dir.path().join("link");
+
+    LocalFs.symlink("target.txt", path_str(&link)).unwrap();
+    assert_eq!(LocalFs.read_link(path_str(&link)).unwrap(), "target.txt");
+
+    let err = LocalFs.read_link(path_str(&dir.path().join("missing"))).unwrap_err();
+    assert_eq!(err.code, ErrorCode::NotFound);
+}
+
+// 递归修改时目录和文件分别使用各自的权限，X 只保留原本可执行文件的执行位
+#[cfg(unix)]
+#[test]
+fn set_attributes_recursively() {
+    use std::os::unix::fs::PermissionsExt;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path().join("site");
+    fs::create_dir_all(root.join("sub")).unwrap();
+    fs::write(root.join("sub/page.html"), b"html").unwrap();
+    fs::write(root.join("run.sh"), b"#!/bin/sh").unwrap();
+    fs::set_permissions(root.join("run.sh"), fs::Permissions::from_mode(0o700)).unwrap();
+
+    let options = SetStatOptions {
+        file_mode: Some(0o755),
+        dir_mode: Some(0o750),
+        conditional_exec: true,
+        mtime: Some(1_700_000_000),
+        recursive: true,
+        ..SetStatOptions::default()
+    };
+    let summary = LocalFs.set_attributes(path_str(&root), &options).unwrap();
+
+    assert!(summary.errors.is_empty(), "{:?}", summary.errors);
+    assert_eq!(summary.updated, 4);
+    let mode = |path: &str| fs::metadata(root.join(path)).unwrap().permissions().mode() & 0o7777;
+    assert_eq!(mode("sub"), 0o750);
+    assert_eq!(mode("sub/page.html"), 0o644);
+    assert_eq!(mode("run.sh"), 0o755);
+    assert_eq!(
+        fs::metadata(root.join("sub/page.html")).unwrap().modified().unwrap(),
+        UNIX_EPOCH + Duration::from_secs(1_700_000_000)
+    );
+
+    let summary = LocalFs.set_attributes(path_str(&dir.path().join("missing")), &options).unwrap();
+    assert_eq!(summary.updated, 0);
+    assert_eq!(summary.errors[0].code, ErrorCode::NotFound);
+}
+
+#[test]
+fn write_then_read() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("data.bin");
+
+    let mut writer = LocalFs.open_write(path_str(&path)).unwrap();
+    writer.write_all(b"hello").unwrap();
+    drop(writer);
+
+    let mut content = String::new();
+    LocalFs.open_read(path_str(&path)).unwrap().read_to_string(&mut content).unwrap();
+    assert_eq!(content, "hello");
+}
+
+#[test]
+fn mkdir_and_remove() {
+    let dir = tempfile::tempdir().unwrap();
+    let sub = dir.path().join("sub");
+    let file = sub.join("file.txt");
+
+    LocalFs.mkdir(path_str(&sub)).unwrap();
+    fs::write(&file, b"x").unwrap();
+
+    assert!(LocalFs.remove_dir(path_str(&sub)).is_err());
+    LocalFs.remove_file(path_str(&file)).unwrap();
+    LocalFs.remove_dir(path_str(&sub)).unwrap();
+    assert!(!sub.exists());
+
+    let err = LocalFs.remove_file(path_str(&file)).unwrap_err();
+    assert_eq!(err.code, ErrorCode::NotFound);
+}
+
+#[test]
+fn rename_respects_overwrite() {
+    let dir = tempfile::tempdir().unwrap();
+    let a = dir.path().join("a.txt");
+    let b = dir.path().join("b.txt");
+    fs::write(&a, b"new").unwrap();
+    fs::write(&b, b"old").unwrap();
+
+    let err = LocalFs.rename(path_str(&a), path_str(&b), false).unwrap_err();
+    assert_eq!(err.code, ErrorCode::AlreadyExists);
+    assert_eq!(fs::read(&b).unwrap(), b"old");
+
+    LocalFs.rename(path_str(&a), path_str(&b), true).unwrap();
+    assert!(!a.exists());
+    assert_eq!(fs::read(&b).unwrap(), b"new");
+}
+
+#[test]
+fn transfers_through_local_backend() {
+    let remote = tempfile::tempdir().unwrap();
+    let local = tempfile::tempdir().unwrap();
+    let data: Vec<u8> = (0..200_000).map(|i| (i % 251) as u8).collect();
+
+    let source = local.path().join("source.bin");
+    let uploaded = remote.path().join("uploaded.bin");
+    let downloaded = local.path().join("nested/downloaded.bin");
+    fs::write(&source, &data).unwrap();
+
+    let cancel_flag = AtomicBool::new(false);
+    let bytes = ops::upload(&LocalFs, path_str(&source), path_str(&uploaded), &cancel_flag, &mut NoProgress).unwrap();
+    assert_eq!(bytes, data.len() as u64);
+
+    let mut last = (0, 0);
+    let bytes = ops::download(&LocalFs, path_str(&uploaded), path_str(&downloaded), &cancel_flag, &mut |done, total| {
+        last = (done, total);
+    }).unwrap();
+
+    assert_eq!(bytes, data.len() as u64);
+    assert_eq!(last, (data.len() as u64, data.len() as u64));
+    assert_eq!(fs::read(&downloaded).unwrap(), data);
+}
@@ -0,0 +1,175 @@
+// 两个连接之间的直接传输（ops::transfer），使用进程内 SFTP 服务器与本机文件系统互传
+// 本机一侧使用临时目录的真实路径，SFTP 一侧使用以服务器根目录为 / 的路径
+
+mod common;
+
+use common::{sample_data, TestServer};
+use sftp_web_lib::ops::{self, TransferRequest};
+use sftp_web_lib::{ErrorCode, LocalFs, NoProgress, ScpFs, SftpFs};
+use ssh2::Session;
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::AtomicBool;
+
+fn connect(server: &TestServer) -> Session {
+    let options = server.connect_options();
+    ops::connect(&options.host, options.port, &options.username, &options.password, None, None, &options.timeouts)
+        .expect("connect")
+}
+
+fn path_str(path: &Path) -> &str {
+    path.to_str().unwrap()
+}
+
+fn request<'a>(source: &'a str, destination: &'a str) -> TransferRequest<'a> {
+    TransferRequest {
+        source,
+        destination,
+        overwrite: false,
+        resume: false,
+    }
+}
+
+#[test]
+fn directory_round_trip_with_progress() {
+    let server = TestServer::start();
+    let local = tempfile::tempdir().unwrap();
+    let session = connect(&server);
+    let sftp = SftpFs::new(&session).unwrap();
+
+    let source = local.path().join("tree");
+    fs::create_dir_all(source.join("sub/empty")).unwrap();
+    fs::write(source.join("a.txt"), b"12345").unwrap();
+    fs::write(source.join("sub/b.bin"), vec![9u8; 200_000]).unwrap();
+
+    let cancel_flag = AtomicBool::new(false);
+    let mut last = (0, 0);
+    let bytes = ops::transfer(&LocalFs, &sftp, &request(path_str(&source), "/tree"), &cancel_flag, &mut |done, total| {
+        last = (done, total);
+    }).unwrap();
+
+    assert_eq!(bytes, 200_005);
+    assert_eq!(last, (200_005, 200_005));
+    assert_eq!(fs::read(server.path("/tree/a.txt")).unwrap(), b"12345");
+    assert_eq!(fs::read(server.path("/tree/sub/b.bin")).unwrap().len(), 200_000);
+    assert!(server.path("/tree/sub/empty").is_dir());
+
+    // 目标目录已存在时合并进去，已存在的文件仍需 overwrite
+    let back = local.path().join("back");
+    fs::create_dir(&back).unwrap();
+    let bytes = ops::transfer(&sftp, &LocalFs, &request("/tree", path_str(&back)), &cancel_flag, &mut NoProgress).unwrap();
+    assert_eq!(bytes, 200_005);
+    assert_eq!(fs::read(back.join("a.txt")).unwrap(), b"12345");
+    assert!(back.join("sub/empty").is_dir());
+
+    let err = ops::transfer(&sftp, &LocalFs, &request("/tree", path_str(&back)), &cancel_flag, &mut NoProgress).unwrap_err();
+    assert_eq!(err.code, ErrorCode::AlreadyExists);
+}
+
+#[test]
+fn existing_file_requires_overwrite() {
+    let server = TestServer::start();
+    let local = tempfile::tempdir().unwrap();
+    let session = connect(&server);
+    let sftp = SftpFs::new(&session).unwrap();
+
+    let source = local.path().join("new.txt");
+    fs::write(&source, b"new").unwrap();
+    fs::write(server.path("/old.txt"), b"old content").unwrap();
+    fs::create_dir(server.path("/dir")).unwrap();
+
+    let cancel_flag = AtomicBool::new(false);
+    let err = ops::transfer(&LocalFs, &sftp, &request(path_str(&source), "/old.txt"), &cancel_flag, &mut NoProgress).unwrap_err();
+    assert_eq!(err.code, ErrorCode::AlreadyExists);
+    assert_eq!(fs::read(server.path("/old.txt")).unwrap(), b"old content");
+
+    let overwrite = TransferRequest { overwrite: true, ..request(path_str(&source), "/old.txt") };
+    ops::transfer(&LocalFs, &sftp, &overwrite, &cancel_flag, &mut NoProgress).unwrap();
+    assert_eq!(fs::read(server.path("/old.txt")).unwrap(), b"new");
+
+    let into_dir = TransferRequest { overwrite: true, ..request(path_str(&source), "/dir") };
+    let err = ops::transfer(&LocalFs, &sftp, &into_dir, &cancel_flag, &mut NoProgress).unwrap_err();
+    assert_eq!(err.code, ErrorCode::AlreadyExists);
+
+    let err = ops::transfer(&sftp, &LocalFs, &request("/missing.txt", path_str(&source)), &cancel_flag, &mut NoProgress).unwrap_err();
+    assert_eq!(err.code, ErrorCode::NotFound);
+}
+
+#[test]
+fn resume_appends_to_partial_files() {
+    let server = TestServer::start();
+    let local = tempfile::tempdir().unwrap();
+    let session = connect(&server);
+    let sftp = SftpFs::new(&session).unwrap();
+    let data = sample_data(300_000);
+    let offset = 100_000;
+
+    // 本机到 SFTP：目标已有前一部分
+    let source = local.path().join("source.bin");
+    fs::write(&source, &data).unwrap();
+    fs::write(server.path("/partial.bin"), &data[..offset]).unwrap();
+
+    let cancel_flag = AtomicBool::new(false);
+    let resume = TransferRequest { resume: true, ..request(path_str(&source), "/partial.bin") };
+    let mut first = None;
+    let mut last = (0, 0);
+    let bytes = ops::transfer(&LocalFs, &sftp, &resume, &cancel_flag, &mut |done, total| {
+        if done > 0 {
+            first.get_or_insert(done);
+        }
+        last = (done, total);
+    }).unwrap();
+
+    assert_eq!(bytes, (data.len() - offset) as u64);
+    assert!(first.unwrap() >= offset as u64);
+    assert_eq!(last, (data.len() as u64, data.len() as u64));
+    assert_eq!(fs::read(server.path("/partial.bin")).unwrap(), data);
+
+    // 已完整时不再传输
+    let bytes = ops::transfer(&LocalFs, &sftp, &resume, &cancel_flag, &mut NoProgress).unwrap();
+    assert_eq!(bytes, 0);
+
+    // SFTP 到本机：从远程文件的偏移处读取
+    let downloaded = local.path().join("downloaded.bin");
+    fs::write(&downloaded, &data[..offset]).unwrap();
+    let resume = TransferRequest { resume: true, ..request("/partial.bin", path_str(&downloaded)) };
+    let bytes = ops::transfer(&sftp, &LocalFs, &resume, &cancel_flag, &mut NoProgress).unwrap();
+    assert_eq!(bytes, (data.len() - offset) as u64);
+    assert_eq!(fs::read(&downloaded).unwrap(), data);
+}
+
+#[test]
+fn resume_without_append_support_restarts() {
+    let server = TestServer::start_without_sftp();
+    let local = tempfile::tempdir().unwrap();
+    let session = connect(&server);
+    let scp = ScpFs::new(&session);
+
+    let source = local.path().join("source.txt");
+    let destination = server.root().join("partial.txt");
+    fs::write(&source, b"complete file").unwrap();
+    fs::write(&destination, b"complete").unwrap();
+
+    let cancel_flag = AtomicBool::new(false);
+    let resume = TransferRequest { resume: true, ..request(path_str(&source), path_str(&destination)) };
+    let bytes = ops::transfer(&LocalFs, &scp, &resume, &cancel_flag, &mut NoProgress).unwrap();
+
+    assert_eq!(bytes, 13);
+    assert_eq!(fs::read(&destination).unwrap(), b"complete file");
+}
+
+#[test]
+fn cancelled_transfer_stops() {
+    let server = TestServer::start();
+    let local = tempfile::tempdir().unwrap();
+    let session = connect(&server);
+    let sftp = SftpFs::new(&session).unwrap();
+
+    let source = local.path().join("big.bin");
+    fs::write(&source, vec![1u8; 1024 * 1024]).unwrap();
+
+    let cancel_flag = AtomicBool::new(true);
+    let err = ops::transfer(&LocalFs, &sftp, &request(path_str(&source), "/big.bin"), &cancel_flag, &mut NoProgress).unwrap_err();
+    assert_eq!(err.code, ErrorCode::Cancelled);
+    assert!(!server.path("/big.bin").exists());
+}
@@ -0,0 +1,225 @@
+// 使用进程内的 S3 兼容服务器（s3s-fs，以目录保存对象）测试 S3 后端
+// 服务器校验 SigV4 签名；临时目录下的 bucket 子目录即存储桶，地址使用路径形式
+
+mod common;
+
+use common::sample_data;
+use hyper_util::rt::TokioIo;
+use s3s::access::S3Access;
+use s3s::auth::SimpleAuth;
+use s3s::dto::UploadPartInput;
+use s3s::{s3_error, S3Request, S3Result};
+use s3s::service::S3ServiceBuilder;
+use sftp_web_lib::{ops, ErrorCode, NoProgress, RemoteFs, S3Fs, S3Options, TimeoutConfig};
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::atomic::AtomicBool;
+use tempfile::TempDir;
+
+const ACCESS_KEY: &str = "AKIATESTKEY";
+const SECRET_KEY: &str = "test-secret-key";
+const BUCKET: &str = "bucket";
+
+// 拒绝所有 UploadPart 请求，模拟分段上传中途权限被收回
+struct DenyUploadPart;
+
+#[async_trait::async_trait]
+impl S3Access for DenyUploadPart {
+    async fn upload_part(&self, _req: &mut S3Request<UploadPartInput>) -> S3Result<()> {
+        Err(s3_error!(AccessDenied))
+    }
+}
+
+struct S3Server {
+    port: u16,
+    root: TempDir,
+    _runtime: tokio::runtime::Runtime,
+}
+
+impl S3Server {
+    fn start() -> Self {
+        Self::start_with(|_| {})
+    }
+
+    fn start_with(configure: impl FnOnce(&mut S3ServiceBuilder)) -> Self {
+        let root = tempfile::tempdir().unwrap();
+        fs::create_dir(root.path().join(BUCKET)).unwrap();
+
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(2)
+            .enable_all()
+            .build()
+            .unwrap();
+
+        let listener = runtime.block_on(tokio::net::TcpListener::bind("127.0.0.1:0")).unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let mut builder = S3ServiceBuilder::new(s3s_fs::FileSystem::new(root.path()).unwrap());
+        builder.set_auth(SimpleAuth::from_single(ACCESS_KEY, SECRET_KEY));
+        configure(&mut builder);
+        let service = builder.build().into_shared();
+
+        runtime.spawn(async move {
+            loop {
+                let Ok((stream, _)) = listener.accept().await else { break };
+                let service = service.clone();
+                tokio::spawn(async move {
+                    let _ = hyper::server::conn::http1::Builder::new()
+                        .serve_connection(TokioIo::new(stream), service)
+                        .await;
+                });
+            }
+        });
+
+        S3Server {
+            port,
+            root,
+            _runtime: runtime,
+        }
+    }
+
+    fn connect_with(&self, secret_key: &str, bucket: &str) -> Result<S3Fs, sftp_web_lib::SftpError> {
+        let options = S3Options {
+            bucket: bucket.to_string(),
+            ..S3Options::default()
+        };
+        S3Fs::connect("127.0.0.1", self.port, ACCESS_KEY, secret_key, &options, None, &TimeoutConfig::default())
+    }
+
+    fn connect(&self) -> S3Fs {
+        self.connect_with(SECRET_KEY, BUCKET).expect("connect")
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.root.path().join(BUCKET).join(key)
+    }
+}
+
+#[test]
+fn bad_credentials_and_missing_bucket() {
+    let server = S3Server::start();
+
+    let err = server.connect_with("wrong", BUCKET).err().unwrap();
+    assert_eq!(err.code, ErrorCode::AuthFailed);
+
+    let err = server.connect_with(SECRET_KEY, "missing").err().unwrap();
+    assert_eq!(err.code, ErrorCode::NotFound);
+}
+
+#[test]
+fn conformance() {
+    let server = S3Server::start();
+    let s3 = server.connect();
+    common::conformance(&s3, &server.root.path().join(BUCKET));
+}
+
+// 没有对象的前缀只能由目录标记对象表示；对象键不能当作目录列出
+#[test]
+fn prefixes_map_to_directories() {
+    let server = S3Server::start();
+    fs::write(server.path("a.txt"), b"12345").unwrap();
+    fs::create_dir_all(server.path("sub/deeper")).unwrap();
+    fs::write(server.path("sub/deeper/x.txt"), b"x").unwrap();
+    fs::create_dir(server.path("empty")).unwrap();
+    let s3 = server.connect();
+
+    let sub = s3.list("/sub").unwrap();
+    assert_eq!(sub.len(), 1);
+    assert_eq!(sub[0].path, "/sub/deeper");
+    assert!(sub[0].is_dir);
+
+    assert!(s3.stat("/sub/deeper").unwrap().is_dir);
+    assert!(s3.stat("/empty").unwrap().is_dir);
+    assert_eq!(s3.stat("/sub/deeper/x.txt").unwrap().size, 1);
+
+    let err = s3.list("/a.txt").unwrap_err();
+    assert_eq!(err.code, ErrorCode::InvalidInput);
+}
+
+#[test]
+fn multipart_upload_and_resumed_download() {
+    let server = S3Server::start();
+    let local = tempfile::tempdir().unwrap();
+    let s3 = server.connect();
+    // 超过两段，最后一段不满
+    let data = sample_data(20 * 1024 * 1024 + 123);
+
+    let source = local.path().join("source.bin");
+    let downloaded = local.path().join("downloaded.bin");
+    fs::write(&source, &data).unwrap();
+
+    let cancel_flag = AtomicBool::new(false);
+    let bytes = ops::upload(&s3, source.to_str().unwrap(), "/dir/uploaded.bin", &cancel_flag, &mut NoProgress).unwrap();
+    assert_eq!(bytes, data.len() as u64);
+    assert_eq!(fs::read(server.path("dir/uploaded.bin")).unwrap(), data);
+
+    // 模拟中断的下载：本地已有前 5 MiB
+    let offset = 5 * 1024 * 1024;
+    fs::write(&downloaded, &data[..offset]).unwrap();
+
+    let mut first = None;
+    let mut last = (0, 0);
+    let bytes = ops::download_resume(&s3, "/dir/uploaded.bin", downloaded.to_str().unwrap(), &cancel_flag, &mut |done, total| {
+        first.get_or_insert(done);
+        last = (done, total);
+    }).unwrap();
+
+    assert_eq!(bytes, (data.len() - offset) as u64);
+    assert_eq!(first, Some(offset as u64));
+    assert_eq!(last, (data.len() as u64, data.len() as u64));
+    assert_eq!(fs::read(&downloaded).unwrap(), data);
+
+    // 已完整下载时不再传输
+    let bytes = ops::download_resume(&s3, "/dir/uploaded.bin", downloaded.to_str().unwrap(), &cancel_flag, &mut NoProgress).unwrap();
+    assert_eq!(bytes, 0);
+}
+
+#[test]
+fn abandoned_upload_is_aborted() {
+    let server = S3Server::start();
+    let s3 = server.connect();
+
+    {
+        let mut writer = s3.open_write("/partial.bin").unwrap();
+        // 超过一段，已开始分段上传
+        writer.write_all(&vec![7u8; 9 * 1024 * 1024]).unwrap();
+    }
+
+    assert!(!server.path("partial.bin").exists());
+    let err = s3.stat("/partial.bin").unwrap_err();
+    assert_eq!(err.code, ErrorCode::NotFound);
+}
+
+// 目录的复制和重命名逐个对象经 CopyObject 完成
+#[test]
+fn rename_and_copy_through_copy_object() {
+    let server = S3Server::start();
+    fs::create_dir_all(server.path("dir/nested")).unwrap();
+    fs::write(server.path("dir/nested/file.txt"), b"data").unwrap();
+    let s3 = server.connect();
+
+    let bytes = s3.copy("/dir", "/copy", false).unwrap();
+    assert_eq!(bytes, 4);
+    assert_eq!(fs::read(server.path("copy/nested/file.txt")).unwrap(), b"data");
+    assert_eq!(fs::read(server.path("dir/nested/file.txt")).unwrap(), b"data");
+
+    s3.rename("/copy", "/moved", false).unwrap();
+    assert_eq!(fs::read(server.path("moved/nested/file.txt")).unwrap(), b"data");
+    assert!(s3.stat("/copy/nested/file.txt").is_err());
+}
+
+// 分段上传中途的 S3 错误保留错误分类，而不是本地 IO 错误
+#[test]
+fn rejected_upload_part_keeps_error_code() {
+    let server = S3Server::start_with(|builder| builder.set_access(DenyUploadPart));
+    let local = tempfile::tempdir().unwrap();
+    let s3 = server.connect();
+    let source = local.path().join("source.bin");
+    fs::write(&source, sample_data(9 * 1024 * 1024)).unwrap();
+
+    let cancel_flag = AtomicBool::new(false);
+    let err = ops::upload(&s3, source.to_str().unwrap(), "/uploaded.bin", &cancel_flag, &mut NoProgress).unwrap_err();
+    assert_eq!(err.code, ErrorCode::PermissionDenied);
+    assert!(!server.path("uploaded.bin").exists());
+}
@@ -0,0 +1,134 @@
+// 没有 SFTP 子系统的服务器上的 SCP 降级后端测试
+// 测试服务器的 exec 直接在本机执行，因此远程路径使用服务器临时目录的真实路径
+
+mod common;
+
+use common::{sample_data, TestServer};
+use sftp_web_lib::{ops, ErrorCode, NoProgress, RemoteFs, ScpFs};
+use ssh2::Session;
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::AtomicBool;
+
+fn connect(server: &TestServer) -> Session {
+    let options = server.connect_options();
+    ops::connect(&options.host, options.port, &options.username, &options.password, None, None, &options.timeouts)
+        .expect("connect")
+}
+
+fn path_str(path: &Path) -> &str {
+    path.to_str().unwrap()
+}
+
+#[test]
+fn sftp_subsystem_is_unavailable() {
+    let server = TestServer::start_without_sftp();
+    let session = connect(&server);
+
+    assert!(session.sftp().is_err());
+}
+
+#[test]
+fn list_and_stat_through_ls() {
+    let server = TestServer::start_without_sftp();
+    fs::write(server.root().join("a b.txt"), b"12345").unwrap();
+    fs::create_dir(server.root().join("sub")).unwrap();
+    let session = connect(&server);
+    let scp = ScpFs::new(&session);
+
+    let mut files = scp.list(path_str(server.root())).unwrap();
+    files.sort_by(|a, b| a.name.cmp(&b.name));
+
+    assert_eq!(files.len(), 2);
+    assert_eq!(files[0].name, "a b.txt");
+    assert_eq!(files[0].size, 5);
+    assert_eq!(files[0].path, path_str(&server.root().join("a b.txt")));
+    assert!(files[1].is_dir);
+
+    let stat = scp.stat(path_str(&server.root().join("sub"))).unwrap();
+    assert_eq!(stat.name, "sub");
+    assert!(stat.is_dir);
+
+    let err = scp.stat(path_str(&server.root().join("missing"))).unwrap_err();
+    assert_eq!(err.code, ErrorCode::NotFound);
+}
+
+#[test]
+fn scp_round_trip_with_progress() {
+    let server = TestServer::start_without_sftp();
+    let local = tempfile::tempdir().unwrap();
+    let session = connect(&server);
+    let scp = ScpFs::new(&session);
+    let data = sample_data(300_000);
+
+    let source = local.path().join("source.bin");
+    let remote = server.root().join("uploaded.bin");
+    let downloaded = local.path().join("downloaded.bin");
+    fs::write(&source, &data).unwrap();
+
+    let cancel_flag = AtomicBool::new(false);
+    let bytes = ops::upload(&scp, path_str(&source), path_str(&remote), &cancel_flag, &mut NoProgress).unwrap();
+    assert_eq!(bytes, data.len() as u64);
+    assert_eq!(fs::read(&remote).unwrap(), data);
+
+    let mut last = (0, 0);
+    let bytes = ops::download(&scp, path_str(&remote), path_str(&downloaded), &cancel_flag, &mut |done, total| {
+        last = (done, total);
+    }).unwrap();
+
+    assert_eq!(bytes, data.len() as u64);
+    assert_eq!(last, (data.len() as u64, data.len() as u64));
+    assert_eq!(fs::read(&downloaded).unwrap(), data);
+}
+
+#[test]
+fn unsized_write_is_unsupported() {
+    let server = TestServer::start_without_sftp();
+    let session = connect(&server);
+
+    let err = ScpFs::new(&session).open_write(path_str(&server.root().join("x"))).err().unwrap();
+    assert_eq!(err.code, ErrorCode::Unsupported);
+}
+
+#[test]
+fn file_operations_through_commands() {
+    let server = TestServer::start_without_sftp();
+    let session = connect(&server);
+    let scp = ScpFs::new(&session);
+    let sub = server.root().join("sub");
+    let a = sub.join("a.txt");
+    let b = sub.join("b.txt");
+
+    scp.mkdir(path_str(&sub)).unwrap();
+    fs::write(&a, b"new").unwrap();
+    fs::write(&b, b"old").unwrap();
+
+    let err = scp.rename(path_str(&a), path_str(&b), false).unwrap_err();
+    assert_eq!(err.code, ErrorCode::AlreadyExists);
+    scp.rename(path_str(&a), path_str(&b), true).unwrap();
+    assert!(!a.exists());
+    assert_eq!(fs::read(&b).unwrap(), b"new");
+
+    assert!(scp.remove_dir(path_str(&sub)).is_err());
+    scp.remove_file(path_str(&b)).unwrap();
+    scp.remove_dir(path_str(&sub)).unwrap();
+    assert!(!sub.exists());
+
+    let err = scp.remove_file(path_str(&b)).unwrap_err();
+    assert_eq!(err.code, ErrorCode::NotFound);
+}
+
+// 源不存在时不能先删掉作为目标的空目录
+#[test]
+fn rename_missing_source_keeps_destination() {
+    let server = TestServer::start_without_sftp();
+    let session = connect(&server);
+    let scp = ScpFs::new(&session);
+    let missing = server.root().join("missing");
+    let target = server.root().join("target");
+    fs::create_dir(&target).unwrap();
+
+    let err = scp.rename(path_str(&missing), path_str(&target), true).unwrap_err();
+    assert_eq!(err.code, ErrorCode::NotFound);
+    assert!(target.is_dir());
+}
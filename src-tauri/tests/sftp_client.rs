@@ -0,0 +1,223 @@
+// SftpClient 对进程内 SFTP 服务器的端到端测试
+
+mod common;
+
+use common::{sample_data, TestServer, PASSWORD, USERNAME};
+use base64::{engine::general_purpose, Engine as _};
+use sftp_web_lib::{ops, ErrorCode, NoProgress, PrivateKey, SftpClient};
+use std::fs;
+use std::sync::atomic::Ordering;
+
+fn connect(server: &TestServer) -> SftpClient {
+    SftpClient::connect(&server.connect_options()).expect("connect")
+}
+
+#[test]
+fn password_auth_and_list_directory() {
+    let server = TestServer::start();
+    fs::write(server.path("/hello.txt"), b"hello world").unwrap();
+    fs::create_dir(server.path("/docs")).unwrap();
+
+    let client = connect(&server);
+    let mut files = client.list_dir("/").unwrap();
+    files.sort_by(|a, b| a.name.cmp(&b.name));
+
+    assert_eq!(files.len(), 2);
+    assert_eq!(files[0].name, "docs");
+    assert!(files[0].is_dir);
+    assert_eq!(files[1].name, "hello.txt");
+    assert_eq!(files[1].path, "/hello.txt");
+    assert_eq!(files[1].size, 11);
+    assert!(!files[1].is_dir);
+}
+
+#[test]
+fn wrong_password_is_auth_failed() {
+    let server = TestServer::start();
+    let mut options = server.connect_options();
+    options.password = "wrong".to_string();
+
+    let err = SftpClient::connect(&options).err().expect("auth should fail");
+    assert_eq!(err.code, ErrorCode::AuthFailed);
+}
+
+#[test]
+fn refused_connection_is_connection_failed() {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    drop(listener);
+
+    let mut options = sftp_web_lib::ConnectOptions::new("127.0.0.1", USERNAME, PASSWORD);
+    options.port = port;
+
+    let err = SftpClient::connect(&options).err().expect("connect should fail");
+    assert_eq!(err.code, ErrorCode::ConnectionFailed);
+}
+
+#[test]
+fn public_key_auth() {
+    let server = TestServer::start();
+    fs::write(server.path("/key.txt"), b"key").unwrap();
+
+    let mut options = server.connect_options();
+    options.password = String::new();
+    options.private_key = Some(PrivateKey { path: server.private_key_path(), passphrase: None });
+
+    let client = SftpClient::connect(&options).expect("connect with key");
+    let files = client.list_dir("/").unwrap();
+    assert_eq!(files.len(), 1);
+    assert_eq!(files[0].name, "key.txt");
+}
+
+#[test]
+fn missing_private_key_is_auth_failed() {
+    let server = TestServer::start();
+    let mut options = server.connect_options();
+    options.private_key = Some(PrivateKey { path: server.root().join("missing_key"), passphrase: None });
+
+    let err = SftpClient::connect(&options).err().expect("auth should fail");
+    assert_eq!(err.code, ErrorCode::AuthFailed);
+}
+
+#[test]
+fn host_key_is_checked_against_known_hosts() {
+    let server = TestServer::start();
+    let client = connect(&server);
+    let session = client.session();
+    let (key, _) = session.host_key().unwrap();
+    let known_hosts = server.root().join("known_hosts");
+    let entry = |key: &[u8]| format!("[127.0.0.1]:{} ssh-ed25519 {}\n", server.port, general_purpose::STANDARD.encode(key));
+
+    // 没有记录时接受
+    fs::write(&known_hosts, "").unwrap();
+    ops::check_host_key(session, "127.0.0.1", server.port, &known_hosts).unwrap();
+
+    fs::write(&known_hosts, entry(key)).unwrap();
+    ops::check_host_key(session, "127.0.0.1", server.port, &known_hosts).unwrap();
+
+    let mut other = key.to_vec();
+    *other.last_mut().unwrap() ^= 0xff;
+    fs::write(&known_hosts, entry(&other)).unwrap();
+    let err = ops::check_host_key(session, "127.0.0.1", server.port, &known_hosts).unwrap_err();
+    assert_eq!(err.code, ErrorCode::HostKeyMismatch);
+}
+
+#[test]
+fn upload_and_download_round_trip() {
+    let server = TestServer::start();
+    let local = tempfile::tempdir().unwrap();
+    let data = sample_data(3 * 1024 * 1024 + 17);
+
+    let source = local.path().join("source.bin");
+    fs::write(&source, &data).unwrap();
+
+    let client = connect(&server);
+    let mut reports = Vec::new();
+    let uploaded = client.upload(
+        source.to_str().unwrap(),
+        "/upload.bin",
+        &mut |done, total| reports.push((done, total)),
+    ).unwrap();
+
+    assert_eq!(uploaded, data.len() as u64);
+    assert_eq!(fs::read(server.path("/upload.bin")).unwrap(), data);
+    assert_eq!(reports.first(), Some(&(0, data.len() as u64)));
+    assert_eq!(reports.last(), Some(&(data.len() as u64, data.len() as u64)));
+
+    // 下载到尚不存在的子目录，验证会自动创建本地目录
+    let destination = local.path().join("nested/download.bin");
+    let downloaded = client.download("/upload.bin", destination.to_str().unwrap(), &mut NoProgress).unwrap();
+
+    assert_eq!(downloaded, data.len() as u64);
+    assert_eq!(fs::read(&destination).unwrap(), data);
+}
+
+#[test]
+fn download_can_be_cancelled() {
+    let server = TestServer::start();
+    let local = tempfile::tempdir().unwrap();
+    fs::write(server.path("/large.bin"), sample_data(8 * 1024 * 1024)).unwrap();
+
+    let client = connect(&server);
+    let cancel_flag = client.cancel_flag();
+    let destination = local.path().join("large.bin");
+
+    // 收到第一次非零进度后取消
+    let err = client.download("/large.bin", destination.to_str().unwrap(), &mut |done, _| {
+        if done > 0 {
+            cancel_flag.store(true, Ordering::SeqCst);
+        }
+    }).expect_err("download should be cancelled");
+
+    assert_eq!(err.code, ErrorCode::Cancelled);
+    assert!(fs::metadata(&destination).unwrap().len() < 8 * 1024 * 1024);
+
+    // 取消标志在传输结束后复位，同一连接可以继续使用
+    assert!(!client.cancel_flag().load(Ordering::SeqCst));
+    let downloaded = client.download("/large.bin", destination.to_str().unwrap(), &mut NoProgress).unwrap();
+    assert_eq!(downloaded, 8 * 1024 * 1024);
+}
+
+#[test]
+fn download_missing_file_is_not_found() {
+    let server = TestServer::start();
+    let local = tempfile::tempdir().unwrap();
+
+    let client = connect(&server);
+    let err = client.download("/missing.txt", local.path().join("missing.txt").to_str().unwrap(), &mut NoProgress)
+        .expect_err("download should fail");
+
+    assert_eq!(err.code, ErrorCode::NotFound);
+}
+
+#[test]
+fn mkdir_and_delete() {
+    let server = TestServer::start();
+    let client = connect(&server);
+
+    client.mkdir("/work").unwrap();
+    assert!(client.stat("/work").unwrap().is_dir);
+    assert!(client.mkdir("/work").is_err());
+
+    fs::write(server.path("/work/file.txt"), b"data").unwrap();
+
+    // 非空目录不能删除
+    assert!(client.remove_dir("/work").is_err());
+
+    client.remove_file("/work/file.txt").unwrap();
+    assert!(!server.path("/work/file.txt").exists());
+
+    client.remove_dir("/work").unwrap();
+    assert!(!server.path("/work").exists());
+}
+
+// 服务器支持 posix-rename 扩展和不支持时（先移除目标再重命名）的覆盖语义应一致
+#[test]
+fn rename_respects_overwrite() {
+    for server in [TestServer::start(), TestServer::start_without_extensions()] {
+        fs::write(server.path("/a.txt"), b"new").unwrap();
+        fs::write(server.path("/b.txt"), b"old").unwrap();
+
+        let client = connect(&server);
+        assert!(client.rename("/a.txt", "/b.txt", false).is_err());
+        assert_eq!(fs::read(server.path("/b.txt")).unwrap(), b"old");
+
+        client.rename("/a.txt", "/b.txt", true).unwrap();
+        assert!(!server.path("/a.txt").exists());
+        assert_eq!(fs::read(server.path("/b.txt")).unwrap(), b"new");
+    }
+}
+
+// 源不存在时重命名失败，不能先删掉已存在的目标
+#[test]
+fn rename_missing_source_keeps_destination() {
+    for server in [TestServer::start(), TestServer::start_without_extensions()] {
+        fs::write(server.path("/existing.txt"), b"keep").unwrap();
+
+        let client = connect(&server);
+        let err = client.rename("/missing.txt", "/existing.txt", true).expect_err("rename should fail");
+        assert_eq!(err.code, ErrorCode::NotFound);
+        assert_eq!(fs::read(server.path("/existing.txt")).unwrap(), b"keep");
+    }
+}
+
@@ -0,0 +1,207 @@
+// 目录同步：生成计划和按计划执行
+// 多数测试两侧都使用本机临时目录，最后一个测试把本机目录推送到进程内 SFTP 服务器
+
+mod common;
+
+use common::TestServer;
+use sftp_web_lib::sync::{self, SyncReason, SyncSummary};
+use sftp_web_lib::{ops, ErrorCode, LocalFs, NoProgress, SftpFs, SyncAction, SyncDirection, SyncOptions, SyncPlan};
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::AtomicBool;
+use std::time::{Duration, UNIX_EPOCH};
+
+fn path_str(path: &Path) -> &str {
+    path.to_str().unwrap()
+}
+
+fn write(path: &Path, content: &[u8], modified: u64) {
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, content).unwrap();
+    fs::File::options()
+        .write(true)
+        .open(path)
+        .unwrap()
+        .set_modified(UNIX_EPOCH + Duration::from_secs(modified))
+        .unwrap();
+}
+
+fn local_plan(left: &Path, right: &Path, options: &SyncOptions) -> SyncPlan {
+    sync::plan(&LocalFs, path_str(left), &LocalFs, path_str(right), options, &AtomicBool::new(false)).unwrap()
+}
+
+fn actions(plan: &SyncPlan) -> Vec<(&str, SyncAction, SyncReason)> {
+    plan.items.iter().map(|item| (item.path.as_str(), item.action, item.reason)).collect()
+}
+
+#[test]
+fn push_mirrors_left_with_excludes_and_deletes() {
+    let left = tempfile::tempdir().unwrap();
+    let right = tempfile::tempdir().unwrap();
+    write(&left.path().join("a.txt"), b"new file", 1_700_000_000);
+    write(&left.path().join("sub/b.txt"), b"longer content", 1_700_000_000);
+    write(&left.path().join("sub/debug.log"), b"log", 1_700_000_000);
+    write(&left.path().join("node_modules/x.js"), b"x", 1_700_000_000);
+    write(&right.path().join("sub/b.txt"), b"short", 1_700_000_000);
+    write(&right.path().join("extra.txt"), b"extra", 1_700_000_000);
+    write(&right.path().join("old/nested/y.txt"), b"y", 1_700_000_000);
+
+    let options = SyncOptions {
+        exclude: vec!["*.log".to_string(), "node_modules".to_string()],
+        delete_extraneous: true,
+        ..SyncOptions::default()
+    };
+    let plan = local_plan(left.path(), right.path(), &options);
+
+    assert_eq!(actions(&plan), vec![
+        ("a.txt", SyncAction::Upload, SyncReason::Missing),
+        ("extra.txt", SyncAction::DeleteRight, SyncReason::Extraneous),
+        ("old", SyncAction::DeleteRight, SyncReason::Extraneous),
+        ("sub/b.txt", SyncAction::Upload, SyncReason::SizeDiffers),
+    ]);
+
+    let mut last = (0, 0);
+    let summary = sync::apply(&LocalFs, &LocalFs, &plan, &AtomicBool::new(false), &mut |done, total| {
+        last = (done, total);
+    }).unwrap();
+
+    assert_eq!(summary, SyncSummary { uploaded: 2, downloaded: 0, deleted: 2, conflicts: 0, bytes: 22 });
+    assert_eq!(last, (22, 22));
+    assert_eq!(fs::read(right.path().join("a.txt")).unwrap(), b"new file");
+    assert_eq!(fs::read(right.path().join("sub/b.txt")).unwrap(), b"longer content");
+    assert!(!right.path().join("extra.txt").exists());
+    assert!(!right.path().join("old").exists());
+    assert!(!right.path().join("sub/debug.log").exists());
+    assert!(!right.path().join("node_modules").exists());
+
+    // 修改时间已同步，再次比较没有差异
+    assert!(local_plan(left.path(), right.path(), &options).items.is_empty());
+}
+
+#[test]
+fn pull_creates_missing_root_and_keeps_extraneous() {
+    let left = tempfile::tempdir().unwrap();
+    let right = tempfile::tempdir().unwrap();
+    let target = left.path().join("mirror");
+    write(&right.path().join("dir/file.txt"), b"remote", 1_700_000_000);
+
+    let options = SyncOptions {
+        direction: SyncDirection::Pull,
+        ..SyncOptions::default()
+    };
+    let plan = local_plan(&target, right.path(), &options);
+    assert_eq!(actions(&plan), vec![
+        ("dir", SyncAction::Download, SyncReason::Missing),
+        ("dir/file.txt", SyncAction::Download, SyncReason::Missing),
+    ]);
+
+    sync::apply(&LocalFs, &LocalFs, &plan, &AtomicBool::new(false), &mut NoProgress).unwrap();
+    assert_eq!(fs::read(target.join("dir/file.txt")).unwrap(), b"remote");
+
+    write(&target.join("local-only.txt"), b"keep", 1_700_000_000);
+    assert!(local_plan(&target, right.path(), &options).items.is_empty());
+}
+
+#[test]
+fn two_way_prefers_newer_side() {
+    let left = tempfile::tempdir().unwrap();
+    let right = tempfile::tempdir().unwrap();
+    write(&left.path().join("left-newer.txt"), b"left v2", 1_700_000_100);
+    write(&right.path().join("left-newer.txt"), b"left v1", 1_700_000_000);
+    write(&left.path().join("right-newer.txt"), b"right v1", 1_700_000_000);
+    write(&right.path().join("right-newer.txt"), b"right v2!", 1_700_000_100);
+    write(&left.path().join("both.txt"), b"left edit", 1_700_000_000);
+    write(&right.path().join("both.txt"), b"right", 1_700_000_001);
+    write(&left.path().join("kind"), b"file", 1_700_000_000);
+    write(&right.path().join("kind/inner.txt"), b"dir", 1_700_000_000);
+    write(&right.path().join("only-right.txt"), b"r", 1_700_000_000);
+
+    let options = SyncOptions {
+        direction: SyncDirection::TwoWay,
+        delete_extraneous: true,
+        ..SyncOptions::default()
+    };
+    let plan = local_plan(left.path(), right.path(), &options);
+    assert_eq!(actions(&plan), vec![
+        ("both.txt", SyncAction::Conflict, SyncReason::BothModified),
+        ("kind", SyncAction::Conflict, SyncReason::TypeMismatch),
+        ("left-newer.txt", SyncAction::Upload, SyncReason::Modified),
+        ("only-right.txt", SyncAction::Download, SyncReason::Missing),
+        ("right-newer.txt", SyncAction::Download, SyncReason::SizeDiffers),
+    ]);
+
+    let summary = sync::apply(&LocalFs, &LocalFs, &plan, &AtomicBool::new(false), &mut NoProgress).unwrap();
+    assert_eq!(summary.conflicts, 2);
+    assert_eq!(fs::read(right.path().join("left-newer.txt")).unwrap(), b"left v2");
+    assert_eq!(fs::read(left.path().join("right-newer.txt")).unwrap(), b"right v2!");
+    assert_eq!(fs::read(left.path().join("both.txt")).unwrap(), b"left edit");
+    assert_eq!(fs::read(right.path().join("both.txt")).unwrap(), b"right");
+}
+
+#[test]
+fn checksum_detects_same_size_changes() {
+    let left = tempfile::tempdir().unwrap();
+    let right = tempfile::tempdir().unwrap();
+    // 目标较新且大小相同，只看修改时间时视为已同步；内容相同但目标较旧时会重复复制
+    write(&left.path().join("same-size.txt"), b"aaaa", 1_700_000_000);
+    write(&right.path().join("same-size.txt"), b"bbbb", 1_700_000_100);
+    write(&left.path().join("identical.txt"), b"same", 1_700_000_000);
+    write(&right.path().join("identical.txt"), b"same", 1_600_000_000);
+
+    let plan = local_plan(left.path(), right.path(), &SyncOptions::default());
+    assert_eq!(actions(&plan), vec![("identical.txt", SyncAction::Upload, SyncReason::Modified)]);
+
+    let options = SyncOptions {
+        checksum: true,
+        ..SyncOptions::default()
+    };
+    let plan = local_plan(left.path(), right.path(), &options);
+    assert_eq!(actions(&plan), vec![("same-size.txt", SyncAction::Upload, SyncReason::ChecksumDiffers)]);
+}
+
+#[test]
+fn invalid_exclude_and_cancellation() {
+    let left = tempfile::tempdir().unwrap();
+    let right = tempfile::tempdir().unwrap();
+    write(&left.path().join("a.txt"), b"a", 1_700_000_000);
+
+    let options = SyncOptions {
+        exclude: vec!["[".to_string()],
+        ..SyncOptions::default()
+    };
+    let err = sync::plan(&LocalFs, path_str(left.path()), &LocalFs, path_str(right.path()), &options, &AtomicBool::new(false)).unwrap_err();
+    assert_eq!(err.code, ErrorCode::InvalidInput);
+
+    let plan = local_plan(left.path(), right.path(), &SyncOptions::default());
+    let err = sync::apply(&LocalFs, &LocalFs, &plan, &AtomicBool::new(true), &mut NoProgress).unwrap_err();
+    assert_eq!(err.code, ErrorCode::Cancelled);
+    assert!(!right.path().join("a.txt").exists());
+}
+
+#[test]
+fn push_to_sftp_preserves_modified_time() {
+    let server = TestServer::start();
+    let local = tempfile::tempdir().unwrap();
+    let options = server.connect_options();
+    let session = ops::connect(&options.host, options.port, &options.username, &options.password, None, None, &options.timeouts)
+        .expect("connect");
+    let sftp = SftpFs::new(&session).unwrap();
+
+    write(&local.path().join("site/index.html"), b"<html></html>", 1_700_000_000);
+    write(&local.path().join("site/css/style.css"), b"body {}", 1_700_000_000);
+
+    let cancel_flag = AtomicBool::new(false);
+    let source = local.path().join("site");
+    let plan = sync::plan(&LocalFs, path_str(&source), &sftp, "/www", &SyncOptions::default(), &cancel_flag).unwrap();
+    assert_eq!(plan.items.len(), 3);
+
+    let summary = sync::apply(&LocalFs, &sftp, &plan, &cancel_flag, &mut NoProgress).unwrap();
+    assert_eq!(summary.uploaded, 3);
+    assert_eq!(fs::read(server.path("/www/css/style.css")).unwrap(), b"body {}");
+
+    let modified = fs::metadata(server.path("/www/index.html")).unwrap().modified().unwrap();
+    assert_eq!(modified, UNIX_EPOCH + Duration::from_secs(1_700_000_000));
+
+    let plan = sync::plan(&LocalFs, path_str(&source), &sftp, "/www", &SyncOptions::default(), &cancel_flag).unwrap();
+    assert!(plan.items.is_empty());
+}
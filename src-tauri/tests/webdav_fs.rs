@@ -0,0 +1,218 @@
+// 使用进程内的 WebDAV 服务器（dav-server）测试 WebDAV 后端
+// 服务器前加一层认证检查，可选 Basic 或 Digest；根目录挂在 /dav 下，与 Nextcloud 等服务的子路径一致
+
+mod common;
+
+use common::{sample_data, PASSWORD, USERNAME};
+use base64::{engine::general_purpose, Engine as _};
+use dav_server::body::Body;
+use dav_server::fakels::FakeLs;
+use dav_server::localfs::LocalFs;
+use dav_server::DavHandler;
+use hyper::header::{AUTHORIZATION, WWW_AUTHENTICATE};
+use hyper::{Request, Response, StatusCode};
+use hyper_util::rt::TokioIo;
+use md5::{Digest, Md5};
+use sftp_web_lib::{ops, ErrorCode, NoProgress, TimeoutConfig, WebDavFs, WebDavOptions};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::AtomicBool;
+use std::sync::{Arc, Mutex};
+use tempfile::TempDir;
+
+const REALM: &str = "test";
+
+#[derive(Clone, Copy)]
+enum AuthScheme {
+    Basic,
+    Digest,
+}
+
+fn md5_hex(text: &str) -> String {
+    format!("{:x}", Md5::digest(text.as_bytes()))
+}
+
+fn digest_param<'a>(header: &'a str, name: &str) -> Option<&'a str> {
+    header.split(',').find_map(|part| {
+        let (key, value) = part.trim().split_once('=')?;
+        (key.trim_start_matches("Digest ").trim() == name).then(|| value.trim_matches('"'))
+    })
+}
+
+fn authorized(scheme: AuthScheme, nonce: &str, method: &str, header: Option<&str>) -> bool {
+    let Some(header) = header else { return false };
+    match scheme {
+        AuthScheme::Basic => {
+            let expected = general_purpose::STANDARD.encode(format!("{}:{}", USERNAME, PASSWORD));
+            header == format!("Basic {}", expected)
+        }
+        AuthScheme::Digest => {
+            let param = |name| digest_param(header, name).unwrap_or_default();
+            if !header.starts_with("Digest ") || param("username") != USERNAME || param("nonce") != nonce {
+                return false;
+            }
+
+            let ha1 = md5_hex(&format!("{}:{}:{}", USERNAME, REALM, PASSWORD));
+            let ha2 = md5_hex(&format!("{}:{}", method, param("uri")));
+            let expected = md5_hex(&format!("{}:{}:{}:{}:auth:{}", ha1, nonce, param("nc"), param("cnonce"), ha2));
+            param("qop") == "auth" && param("response") == expected
+        }
+    }
+}
+
+struct DavServer {
+    port: u16,
+    root: TempDir,
+    // 当前有效的 Digest nonce，更换后旧 nonce 的请求返回 401
+    nonce: Arc<Mutex<String>>,
+    _runtime: tokio::runtime::Runtime,
+}
+
+impl DavServer {
+    fn start(scheme: AuthScheme) -> Self {
+        let root = tempfile::tempdir().unwrap();
+        let nonce = Arc::new(Mutex::new("dcd98b7102dd2f0e8b11d0f600bfb0c093".to_string()));
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(2)
+            .enable_all()
+            .build()
+            .unwrap();
+
+        let listener = runtime.block_on(tokio::net::TcpListener::bind("127.0.0.1:0")).unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let handler = Arc::new(DavHandler::builder()
+            .filesystem(LocalFs::new(root.path(), false, false, false))
+            .locksystem(FakeLs::new())
+            .strip_prefix("/dav")
+            .build_handler());
+
+        let server_nonce = nonce.clone();
+        runtime.spawn(async move {
+            loop {
+                let Ok((stream, _)) = listener.accept().await else { break };
+                let (handler, nonce) = (handler.clone(), server_nonce.clone());
+                tokio::spawn(async move {
+                    let service = hyper::service::service_fn(move |request: Request<hyper::body::Incoming>| {
+                        let handler = handler.clone();
+                        let nonce = nonce.lock().unwrap().clone();
+                        async move {
+                            let header = request.headers().get(AUTHORIZATION).and_then(|value| value.to_str().ok());
+                            if !authorized(scheme, &nonce, request.method().as_str(), header) {
+                                let challenge = match scheme {
+                                    AuthScheme::Basic => format!(r#"Basic realm="{}""#, REALM),
+                                    AuthScheme::Digest => format!(r#"Digest realm="{}", qop="auth", nonce="{}", algorithm=MD5"#, REALM, nonce),
+                                };
+                                let response = Response::builder()
+                                    .status(StatusCode::UNAUTHORIZED)
+                                    .header(WWW_AUTHENTICATE, challenge)
+                                    .body(Body::empty())
+                                    .unwrap();
+                                return Ok::<_, std::convert::Infallible>(response);
+                            }
+                            Ok(handler.handle(request).await)
+                        }
+                    });
+                    let _ = hyper::server::conn::http1::Builder::new()
+                        .serve_connection(TokioIo::new(stream), service)
+                        .await;
+                });
+            }
+        });
+
+        DavServer {
+            port,
+            root,
+            nonce,
+            _runtime: runtime,
+        }
+    }
+
+    // 模拟服务器让 nonce 过期
+    fn rotate_nonce(&self) {
+        let mut nonce = self.nonce.lock().unwrap();
+        *nonce = md5_hex(&nonce);
+    }
+
+    fn connect(&self, password: &str) -> Result<WebDavFs, sftp_web_lib::SftpError> {
+        let options = WebDavOptions {
+            base_path: "/dav".to_string(),
+            ..WebDavOptions::default()
+        };
+        WebDavFs::connect("127.0.0.1", self.port, USERNAME, password, &options, None, &TimeoutConfig::default())
+    }
+
+    fn path(&self, name: &str) -> PathBuf {
+        self.root.path().join(name)
+    }
+}
+
+#[test]
+fn conformance() {
+    for scheme in [AuthScheme::Basic, AuthScheme::Digest] {
+        let server = DavServer::start(scheme);
+        let dav = server.connect(PASSWORD).unwrap();
+        common::conformance(&dav, server.root.path());
+    }
+}
+
+#[test]
+fn wrong_password_is_auth_failure() {
+    for scheme in [AuthScheme::Basic, AuthScheme::Digest] {
+        let server = DavServer::start(scheme);
+
+        let err = server.connect("wrong").err().unwrap();
+        assert_eq!(err.code, ErrorCode::AuthFailed);
+    }
+}
+
+#[test]
+fn server_side_copy() {
+    let server = DavServer::start(AuthScheme::Digest);
+    fs::create_dir(server.path("dir")).unwrap();
+    fs::write(server.path("dir/file.txt"), b"data").unwrap();
+    let dav = server.connect(PASSWORD).unwrap();
+
+    dav.copy("/dir", "/copy", false).unwrap();
+    assert_eq!(fs::read(server.path("copy/file.txt")).unwrap(), b"data");
+    assert_eq!(fs::read(server.path("dir/file.txt")).unwrap(), b"data");
+
+    let err = dav.copy("/dir/file.txt", "/copy/file.txt", false).unwrap_err();
+    assert_eq!(err.code, ErrorCode::AlreadyExists);
+}
+
+// 流式的 GET 和 PUT 也要在 nonce 过期后重新认证
+#[test]
+fn transfers_reauthenticate_after_nonce_expires() {
+    let server = DavServer::start(AuthScheme::Digest);
+    let local = tempfile::tempdir().unwrap();
+    let dav = server.connect(PASSWORD).unwrap();
+    let data = sample_data(100_000);
+
+    let source = local.path().join("source.bin");
+    let downloaded = local.path().join("downloaded.bin");
+    fs::write(&source, &data).unwrap();
+    let cancel_flag = AtomicBool::new(false);
+
+    server.rotate_nonce();
+    ops::upload(&dav, source.to_str().unwrap(), "/uploaded.bin", &cancel_flag, &mut NoProgress).unwrap();
+    assert_eq!(fs::read(server.path("uploaded.bin")).unwrap(), data);
+
+    server.rotate_nonce();
+    ops::download(&dav, "/uploaded.bin", downloaded.to_str().unwrap(), &cancel_flag, &mut NoProgress).unwrap();
+    assert_eq!(fs::read(&downloaded).unwrap(), data);
+}
+
+// 服务器拒绝上传（父目录不存在时返回 409）时保留 HTTP 状态对应的错误分类，而不是本地 IO 错误
+#[test]
+fn rejected_upload_keeps_error_code() {
+    let server = DavServer::start(AuthScheme::Basic);
+    let local = tempfile::tempdir().unwrap();
+    let dav = server.connect(PASSWORD).unwrap();
+    let source = local.path().join("source.bin");
+    fs::write(&source, sample_data(1000)).unwrap();
+
+    let cancel_flag = AtomicBool::new(false);
+    let err = ops::upload(&dav, source.to_str().unwrap(), "/missing/file.bin", &cancel_flag, &mut NoProgress).unwrap_err();
+    assert_eq!(err.code, ErrorCode::NotFound);
+}
@@ -0,0 +1,40 @@
+// 后端命令返回的错误对象，code 对应 Rust 侧的 ErrorCode
+export type ErrorCode =
+  | 'ConnectionFailed'
+  | 'ConnectionLost'
+  | 'NotConnected'
+  | 'AuthFailed'
+  | 'HostKeyMismatch'
+  | 'ProxyFailed'
+  | 'NotFound'
+  | 'PermissionDenied'
+  | 'AlreadyExists'
+  | 'Timeout'
+  | 'Cancelled'
+  | 'Unsupported'
+  | 'InvalidInput'
+  | 'LocalIo'
+  | 'Failure'
+  | 'Internal';
+
+export interface BackendError {
+  code: ErrorCode;
+  sftp_status: number | null;
+  message: string;
+}
+
+export function isBackendError(err: unknown): err is BackendError {
+  return typeof err === 'object' && err !== null && 'code' in err && 'message' in err;
+}
+
+// 将 invoke 抛出的错误转换为可显示的文本
+export function errorMessage(err: unknown): string {
+  if (isBackendError(err)) {
+    return err.message;
+  }
+  return String(err);
+}
+
+export function errorCode(err: unknown): ErrorCode | undefined {
+  return isBackendError(err) ? err.code : undefined;
+}
@@ -1,5 +1,30 @@
 import { ref, reactive } from 'vue';
 
+export type Protocol = 'sftp' | 'ftp' | 'webdav' | 's3';
+
+// 与后端 FtpOptions 对应
+export interface FtpOptions {
+  tls: 'none' | 'explicit' | 'implicit';
+  mode: 'passive' | 'active';
+  accept_invalid_certs: boolean;
+}
+
+// 与后端 WebDavOptions 对应
+export interface WebDavOptions {
+  https: boolean;
+  base_path: string;
+  accept_invalid_certs: boolean;
+}
+
+// 与后端 S3Options 对应
+export interface S3Options {
+  bucket: string;
+  region: string;
+  https: boolean;
+  virtual_hosted: boolean;
+  accept_invalid_certs: boolean;
+}
+
 export interface SavedConnection {
   id: string;
   name: string;
@@ -11,6 +36,10 @@ export interface SavedConnection {
   createdAt: string;
   lastUsed?: string;
   description?: string;
+  protocol?: Protocol; // 旧版本保存的连接没有该字段，视为 SFTP
+  ftp?: FtpOptions;
+  webdav?: WebDavOptions;
+  s3?: S3Options;
 }
 
 const STORAGE_KEY = 'sftp_saved_connections';
@@ -61,7 +90,8 @@ export function useConnectionStorage() {
     const existingIndex = savedConnections.value.findIndex(
       conn => conn.host === connection.host && 
               conn.port === connection.port && 
-              conn.username === connection.username
+              conn.username === connection.username &&
+              (conn.protocol || 'sftp') === (connection.protocol || 'sftp')
     );
 
     if (existingIndex > -1) {